@@ -262,7 +262,9 @@ pub unsafe extern "C" fn _start(mut cursor: *const ()) {
                  */
                 let mut cursor2: *mut Word = start.stack_pointer as _;
                 let argc = *cursor2.offset(0);
-                let at_execfn = *cursor2.offset(1);
+                // The guest path of the program, or argv[0] if it could not
+                // be copied to a safe place.
+                let at_execfn = copy_string(start.at_execfn).unwrap_or(*cursor2.offset(1));
 
                 // skip argv[]
                 cursor2 = cursor2.offset((argc + 1 + 1) as _);
@@ -283,13 +285,11 @@ pub unsafe extern "C" fn _start(mut cursor: *const ()) {
                         AT_PHNUM => *cursor2.offset(1) = start.at_phnum,
                         AT_ENTRY => *cursor2.offset(1) = start.at_entry,
                         AT_BASE => *cursor2.offset(1) = at_base,
-                        AT_EXECFN => {
-                            /* stmt->start.at_execfn can't be used for now since it is
-                             * currently stored in a location that will be scratched
-                             * by the process (below the final stack pointer).  */
-
-                            *cursor2.offset(1) = at_execfn
-                        }
+                        AT_EXECFN => *cursor2.offset(1) = at_execfn,
+                        // The others are kept as is, e.g. `AT_RANDOM` (the 16
+                        // random bytes are above the strings of the initial
+                        // stack) and `AT_SYSINFO_EHDR` (the vDSO is mapped by
+                        // the kernel away from the load addresses).
                         _ => {}
                     }
 
@@ -327,6 +327,52 @@ pub unsafe extern "C" fn _start(mut cursor: *const ()) {
     }
 }
 
+/// Copies the null-terminated string at `string` into new anonymous pages,
+/// and returns the address of the copy.
+///
+/// The strings of the load script are stored below the final stack pointer,
+/// where they will be scratched by the program, so the ones which must outlive
+/// the loader (e.g. the one of `AT_EXECFN`) are copied first.
+unsafe fn copy_string(string: Word) -> Option<Word> {
+    let string = string as *const u8;
+    let mut length = 0;
+    while *string.add(length) != 0 {
+        length += 1;
+    }
+    length += 1;
+
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    let address = sc::syscall!(
+        MMAP,
+        0,
+        length,
+        PROT_READ | PROT_WRITE,
+        MAP_PRIVATE | MAP_ANONYMOUS,
+        (-1isize) as usize,
+        0
+    );
+    #[cfg(any(target_arch = "arm", target_arch = "x86"))]
+    let address = sc::syscall!(
+        MMAP2,
+        0,
+        length,
+        PROT_READ | PROT_WRITE,
+        MAP_PRIVATE | MAP_ANONYMOUS,
+        (-1isize) as usize,
+        0
+    );
+    // mmap() returns -errno on failure, which is in the last page.
+    if address > (-4096isize) as usize {
+        return None;
+    }
+
+    let copy = address as *mut u8;
+    for i in 0..length {
+        *copy.add(i) = *string.add(i);
+    }
+    Some(address as Word)
+}
+
 struct Stderr {}
 
 impl Write for Stderr {
@@ -358,7 +404,7 @@ fn panic_handler(panic_info: &PanicInfo<'_>) -> ! {
         panic_info
     );
     unsafe {
-        sc::syscall!(EXIT, 182);
+        sc::syscall!(EXIT, PANIC_EXIT_CODE);
     }
     unreachable!()
 }
@@ -3,6 +3,10 @@ pub type Word = u32;
 #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
 pub type Word = u64;
 
+/// The exit code of the loader when it fails (e.g. a statement of the load
+/// script cannot be done), after it printed the reason on stderr.
+pub const PANIC_EXIT_CODE: i32 = 182;
+
 #[cfg_attr(any(target_arch = "x86_64", target_arch = "aarch64"), repr(C, u64))]
 #[cfg_attr(any(target_arch = "x86", target_arch = "arm"), repr(C, u32))]
 #[allow(dead_code)]
@@ -60,6 +64,9 @@ pub struct LoadStatementStart {
     pub stack_pointer: Word,
     /// The entry address of the executable, or the entry address of the loader if `PT_INTERP` exists.
     pub entry_point: Word,
+    /// The values of the entries of the auxiliary vector which describe the
+    /// executable instead of the loader. The other entries (e.g. `AT_RANDOM`
+    /// and `AT_SYSINFO_EHDR`) are left as set by the kernel.
     pub at_phdr: Word,
     pub at_phent: Word,
     pub at_phnum: Word,
@@ -0,0 +1,131 @@
+//! A small benchmark harness, used to measure the overhead of proot-rs.
+//!
+//! Each workload issues the same system call a fixed number of times. It is run
+//! once natively (in the proot-rs process, without being traced) and once as
+//! the first tracee of a new `PRoot` instance, so that the overhead of the
+//! syscall translation can be computed.
+
+use std::time::{Duration, Instant};
+
+use crate::errors::*;
+use crate::filesystem::FileSystem;
+use crate::process::proot::PRoot;
+
+/// A workload of the benchmark: a name, and a function issuing the system call
+/// to be measured once.
+struct Workload {
+    name: &'static str,
+    func: fn(),
+}
+
+/// The fixed set of workloads, which should only contain syscalls that are
+/// translated by proot-rs.
+const WORKLOADS: &[Workload] = &[
+    Workload {
+        name: "stat",
+        func: workload_stat,
+    },
+    Workload {
+        name: "getcwd",
+        func: workload_getcwd,
+    },
+];
+
+fn workload_stat() {
+    let _ = nix::sys::stat::stat("/");
+}
+
+fn workload_getcwd() {
+    let _ = nix::unistd::getcwd();
+}
+
+/// Result of one workload.
+#[derive(Debug)]
+pub struct Measurement {
+    pub name: &'static str,
+    pub iterations: usize,
+    /// Time spent without proot-rs.
+    pub native: Duration,
+    /// Time spent under proot-rs, including the startup of the tracee.
+    pub traced: Duration,
+}
+
+impl Measurement {
+    /// Number of syscalls per second under proot-rs.
+    pub fn syscalls_per_sec(&self) -> f64 {
+        self.iterations as f64 / self.traced.as_secs_f64()
+    }
+
+    /// Overhead of proot-rs for each syscall, in microseconds.
+    pub fn overhead_per_syscall_us(&self) -> f64 {
+        (self.traced.as_secs_f64() - self.native.as_secs_f64()) * 1e6 / self.iterations as f64
+    }
+}
+
+/// Run all workloads `iterations` times in a guest whose file-system is `fs`,
+/// and print a report on stdout. With `chroot_only`, the paths are not
+/// translated (see `--chroot-only`), which shows the part of the overhead
+/// which is due to the translation rather than to the stops of ptrace.
+pub fn run(fs: FileSystem, iterations: usize, chroot_only: bool) -> Result<()> {
+    let measurements = WORKLOADS
+        .iter()
+        .map(|workload| measure(workload, fs.clone(), iterations, chroot_only))
+        .collect::<Result<Vec<_>>>()?;
+
+    println!(
+        "{:<10} {:>10} {:>12} {:>12} {:>14} {:>20}",
+        "workload",
+        "iterations",
+        "native (s)",
+        "proot-rs (s)",
+        "syscalls/sec",
+        "overhead/syscall (us)"
+    );
+    for m in &measurements {
+        println!(
+            "{:<10} {:>10} {:>12.6} {:>12.6} {:>14.0} {:>20.3}",
+            m.name,
+            m.iterations,
+            m.native.as_secs_f64(),
+            m.traced.as_secs_f64(),
+            m.syscalls_per_sec(),
+            m.overhead_per_syscall_us()
+        );
+    }
+    Ok(())
+}
+
+fn measure(
+    workload: &Workload,
+    fs: FileSystem,
+    iterations: usize,
+    chroot_only: bool,
+) -> Result<Measurement> {
+    let func = workload.func;
+
+    let start = Instant::now();
+    (0..iterations).for_each(|_| func());
+    let native = start.elapsed();
+
+    let mut proot = PRoot::new();
+    proot.init()?;
+    proot.info_bag.chroot_only = chroot_only;
+    let start = Instant::now();
+    proot.launch_function(fs, || (0..iterations).for_each(|_| func()))?;
+    proot.event_loop()?;
+    let traced = start.elapsed();
+
+    if proot.init_exit_code != Some(0) {
+        return Err(Error::msg(format!(
+            "benchmark workload {} exited with a bad exit code: {:?}",
+            workload.name, proot.init_exit_code
+        )));
+    }
+
+    Ok(Measurement {
+        name: workload.name,
+        iterations,
+        native,
+        traced,
+    })
+}
@@ -1,16 +1,123 @@
-use clap::{crate_version, App, Arg};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
+use clap::{crate_version, App, Arg, ArgMatches};
+use libc::c_int;
+use nix::sched::CpuSet;
+
+use crate::config_file::ConfigFile;
 use crate::errors::*;
+use crate::filesystem::rootfs_image::extract_rootfs_image;
+use crate::filesystem::synthetic_proc::create_synthetic_proc;
+use crate::filesystem::synthetic_sys::create_synthetic_sys;
+use crate::filesystem::temp::TempDir;
 use crate::filesystem::validation::{binding_validator, path_validator};
 use crate::filesystem::FileSystem;
+use crate::kernel::execve::{INTERPRETER_REWRITE_LIMIT, MAX_INTERPRETER_REWRITE_LIMIT};
+use crate::kernel::fake_root::parse_id_mapping;
+use crate::kernel::socket::socket_family::parse_socket_families;
+use crate::kernel::standard::sched_affinity::parse_cpu_list;
+use crate::kernel::standard::unhandled_path::UnhandledSyscallAction;
+use crate::kernel::syscall::parse_syscall_list;
+use crate::logging::{self, LogFormat};
 
 pub const DEFAULT_ROOTFS: &'static str = "/";
 pub const DEFAULT_CWD: &'static str = "/";
 
+/// The configuration of proot-rs, as given on the command line.
+#[derive(Debug)]
+pub struct Config {
+    /// The initial `FileSystem` of the first tracee.
+    pub fs: FileSystem,
+    /// If the rootfs was given as an image with `--rootfs-is-file`, the
+    /// directory where it was extracted, which is deleted when dropped.
+    pub rootfs_image: Option<TempDir>,
+    /// If `--mount-proc` was given, the directory of the synthetic `/proc`
+    /// bound in the guest, which is deleted when dropped.
+    pub synthetic_proc: Option<TempDir>,
+    /// If `--mount-sys` was given, the directory of the filtered `/sys` bound
+    /// in the guest, which is deleted when dropped.
+    pub synthetic_sys: Option<TempDir>,
+    /// The command to run in the guest rootfs.
+    pub command: Vec<String>,
+    /// Variables added to the environment of `command`, from the
+    /// configuration file.
+    pub env: Vec<(String, String)>,
+    /// If set, run the built-in benchmark workload with this number of
+    /// iterations, instead of `command`.
+    pub benchmark: Option<usize>,
+    /// Whether the uptime reported by `sysinfo()` and `times()` should start
+    /// from the launch of the sandbox.
+    pub virtual_uptime: bool,
+    /// Whether the paths in `/proc` should be emulated.
+    pub proc_emulation: bool,
+    /// Whether `/dev/null`, `/dev/zero` and `/dev/full` should be emulated.
+    pub dev_emulation: bool,
+    /// Whether the pids of the other processes of the host are hidden from
+    /// the listings of `/proc`.
+    pub hide_host_pids: bool,
+    /// Whether only the executables of `execve()` are translated.
+    pub chroot_only: bool,
+    /// Whether proot-rs adopts and reaps the orphaned processes of the guest.
+    pub init: bool,
+    /// Whether the guest sees itself as root.
+    pub fake_root: bool,
+    /// The mappings of `--map-uid`, as `(host uid, guest uid)`.
+    pub uid_mappings: Vec<(u32, u32)>,
+    /// The mappings of `--map-gid`, as `(host gid, guest gid)`.
+    pub gid_mappings: Vec<(u32, u32)>,
+    /// Whether io_uring can be used in the guest.
+    pub allow_io_uring: bool,
+    /// Whether `bpf()` can be used in the guest.
+    pub allow_bpf: bool,
+    /// Whether `perf_event_open()` can be used in the guest.
+    pub allow_perf: bool,
+    /// Whether the kernel keyrings can be used in the guest.
+    pub allow_keyring: bool,
+    /// Whether `setns()` can be used in the guest.
+    pub allow_setns: bool,
+    /// Whether `sync()` only flushes the file-systems of the guest.
+    pub scoped_sync: bool,
+    /// If set, the cpus the guest is restricted to.
+    pub cpus: Option<CpuSet>,
+    /// Whether the message queues of the guest are separated from the host.
+    pub mq_namespace: bool,
+    /// Whether proot-rs should die from the signal which killed the init
+    /// process, instead of exiting with 128+signal.
+    pub exit_code_passthrough: bool,
+    /// Whether statistics of the syscalls should be printed at exit.
+    pub collect_stats: bool,
+    /// If set, the number of seconds after which the guest is killed.
+    pub timeout: Option<u32>,
+    /// The number of interpreters which `execve()` can follow.
+    pub max_interp_depth: usize,
+    /// Whether the seccomp filter should be printed, instead of running
+    /// `command`.
+    pub seccomp_bpf_dump: bool,
+    /// Whether the ELF executable of `command` should be described, instead
+    /// of running it.
+    pub dump_elf: bool,
+    /// If set, the guest path whose translation should be described, instead
+    /// of running `command`.
+    pub trace_path: Option<PathBuf>,
+    /// If set, the only syscalls printed in the trace log.
+    pub strace_filter: Option<HashSet<usize>>,
+    /// If set, the only address families of the sockets of the guest.
+    pub socket_families: Option<Vec<c_int>>,
+    /// If set, what to do with the syscalls whose path is not translated.
+    pub unhandled_syscall_action: Option<UnhandledSyscallAction>,
+}
+
 pub fn get_args_parser() -> App<'static, 'static> {
     App::new("proot-rs")
         .about("chroot, mount --bind, and binfmt_misc without privilege/setup.")
         .version(crate_version!())
+        .arg(Arg::with_name("config")
+            .long("config")
+            .help("Read the rootfs, the bindings, the cwd, the environment and the command from the TOML *file*, the other options take precedence.")
+            .value_name("file")
+            .takes_value(true)
+            .validator(path_validator))
         .arg(Arg::with_name("rootfs")
             .short("r")
             .long("rootfs")
@@ -18,6 +125,9 @@ pub fn get_args_parser() -> App<'static, 'static> {
             .takes_value(true)
             .default_value(DEFAULT_ROOTFS)
             .validator(path_validator))
+        .arg(Arg::with_name("rootfs-is-file")
+            .long("rootfs-is-file")
+            .help("Use the tar archive *path* of --rootfs as the guest root file-system, by extracting it to a temporary directory which is deleted at exit."))
         .arg(Arg::with_name("bind")
             .short("b")
             .long("bind")
@@ -31,25 +141,220 @@ pub fn get_args_parser() -> App<'static, 'static> {
             .help("Set the initial working directory to *path*.")
             .takes_value(true)
             .default_value(DEFAULT_CWD))
+        .arg(Arg::with_name("benchmark")
+            .long("benchmark")
+            .help("Instead of running *command*, measure the overhead of proot-rs by running a fixed workload *iterations* times.")
+            .value_name("iterations")
+            .takes_value(true)
+            .conflicts_with("command")
+            .validator(|value| value.parse::<usize>().map(|_| ()).map_err(|e| e.to_string())))
+        .arg(Arg::with_name("virtual-uptime")
+            .long("virtual-uptime")
+            .help("Make the uptime reported by sysinfo() and times() start from the launch of proot-rs."))
+        .arg(Arg::with_name("no-proc-emulation")
+            .long("no-proc-emulation")
+            .help("Disable the emulation of /proc, so that the host /proc shows through (useful for debugging)."))
+        .arg(Arg::with_name("mount-proc")
+            .long("mount-proc")
+            .help("Bind a minimal synthetic /proc (self, mounts, cpuinfo) on the /proc directory of the rootfs.")
+            .conflicts_with("no-proc-emulation"))
+        .arg(Arg::with_name("mount-sys")
+            .long("mount-sys")
+            .help("Bind a filtered /sys (the cpus and the memory settings of the host, an empty fs/cgroup) on the /sys directory of the rootfs."))
+        .arg(Arg::with_name("emulate-dev")
+            .long("emulate-dev")
+            .help("Emulate /dev/null, /dev/zero and /dev/full with the devices of the host, even if the rootfs has no device nodes."))
+        .arg(Arg::with_name("hide-host-pids")
+            .long("hide-host-pids")
+            .help("Only list the processes of the guest in the host /proc, the pids of the other processes of the host are hidden (but still accessible)."))
+        .arg(Arg::with_name("chroot-only")
+            .long("chroot-only")
+            .help("Only look up the executables of execve() in the rootfs, the other paths are NOT translated (faster, no bindings allowed)."))
+        .arg(Arg::with_name("init")
+            .long("init")
+            .help("Make proot-rs the subreaper of the guest, so that the orphaned processes (e.g. daemons) are reaped by proot-rs instead of the init process of the host."))
+        .arg(Arg::with_name("fake-root")
+            .long("fake-root")
+            .help("Make *command* see itself as root: the ids are 0, the files of the user of proot-rs are owned by root, and chown() and chmod() record the ownership and the mode (e.g. setuid) instead of failing with EPERM."))
+        .arg(Arg::with_name("map-uid")
+            .long("map-uid")
+            .help("With --fake-root, map the host uid *host* to the guest uid *guest* (e.g. in stat() and getuid()), and back in chown(). Format: host:guest")
+            .value_name("host:guest")
+            .multiple(true)
+            .number_of_values(1)
+            .takes_value(true)
+            .requires("fake-root")
+            .validator(|value| parse_id_mapping(&value).map(|_| ()).map_err(|e| e.to_string())))
+        .arg(Arg::with_name("map-gid")
+            .long("map-gid")
+            .help("With --fake-root, map the host gid *host* to the guest gid *guest* (e.g. in stat() and getgid()), and back in chown(). Format: host:guest")
+            .value_name("host:guest")
+            .multiple(true)
+            .number_of_values(1)
+            .takes_value(true)
+            .requires("fake-root")
+            .validator(|value| parse_id_mapping(&value).map(|_| ()).map_err(|e| e.to_string())))
+        .arg(Arg::with_name("allow-io-uring")
+            .long("allow-io-uring")
+            .help("Allow the use of io_uring, whose operations bypass the path translation of proot-rs."))
+        .arg(Arg::with_name("allow-bpf")
+            .long("allow-bpf")
+            .help("Allow the use of bpf(), whose programs can observe the host."))
+        .arg(Arg::with_name("allow-perf")
+            .long("allow-perf")
+            .help("Allow the use of perf_event_open(), whose events can observe the host."))
+        .arg(Arg::with_name("allow-keyring")
+            .long("allow-keyring")
+            .help("Allow the use of add_key(), request_key() and keyctl(), whose keyrings are shared with the host."))
+        .arg(Arg::with_name("allow-setns")
+            .long("allow-setns")
+            .help("Allow the use of setns(), which joins namespaces that proot-rs does not manage."))
+        .arg(Arg::with_name("scoped-sync")
+            .long("scoped-sync")
+            .help("Make sync() only flush the file-systems of the rootfs and the bindings of directories, once each, instead of the ones of the whole host. The guest is blocked until they are flushed."))
+        .arg(Arg::with_name("cpus")
+            .long("cpus")
+            .help("Restrict the guest to the cpus in *list* (e.g. 0-3,6), even if it changes its cpu affinity.")
+            .value_name("list")
+            .takes_value(true)
+            .validator(|value| parse_cpu_list(&value).map(|_| ()).map_err(|e| e.to_string())))
+        .arg(Arg::with_name("mq-namespace")
+            .long("mq-namespace")
+            .help("Prefix the names of the POSIX message queues, so that the guest does not share them with the host."))
+        .arg(Arg::with_name("exit-code-128")
+            .long("exit-code-128")
+            .help("When *command* is killed by a signal, exit with 128+signal (default).")
+            .overrides_with("exit-code-passthrough"))
+        .arg(Arg::with_name("exit-code-passthrough")
+            .long("exit-code-passthrough")
+            .help("When *command* is killed by a signal, kill proot-rs with the same signal.")
+            .overrides_with("exit-code-128"))
+        .arg(Arg::with_name("collect-stats")
+            .long("collect-stats")
+            .help("Count the system calls of *command*, and print a summary on stderr at exit."))
+        .arg(Arg::with_name("timeout")
+            .long("timeout")
+            .help("Send SIGTERM to *command* after *seconds*, then SIGKILL to the guest 5 seconds later, and exit with 124.")
+            .value_name("seconds")
+            .takes_value(true)
+            .conflicts_with_all(&["benchmark", "seccomp-bpf-dump", "dump-elf"])
+            .validator(|value| match value.parse::<u32>() {
+                Ok(0) => Err("should be at least 1".to_string()),
+                Ok(_) => Ok(()),
+                Err(e) => Err(e.to_string()),
+            }))
+        .arg(Arg::with_name("max-interp-depth")
+            .long("max-interp-depth")
+            .help("Follow up to *depth* interpreters (e.g. scripts run by scripts) in execve(), instead of 4 as the kernel does.")
+            .value_name("depth")
+            .takes_value(true)
+            .validator(|value| match value.parse::<usize>() {
+                Ok(depth) if depth > MAX_INTERPRETER_REWRITE_LIMIT => {
+                    Err(format!("should be at most {}", MAX_INTERPRETER_REWRITE_LIMIT))
+                }
+                Ok(_) => Ok(()),
+                Err(e) => Err(e.to_string()),
+            }))
+        .arg(Arg::with_name("seccomp-bpf-dump")
+            .long("seccomp-bpf-dump")
+            .help("Instead of running *command*, print the seccomp filter of the system calls intercepted by proot-rs.")
+            .conflicts_with("command"))
+        .arg(Arg::with_name("dump-elf")
+            .long("dump-elf")
+            .help("Instead of running *command*, print the ELF headers, the interpreter and the mappings of its executable (given by its guest path).")
+            .conflicts_with_all(&["benchmark", "seccomp-bpf-dump"]))
+        .arg(Arg::with_name("trace-path")
+            .long("trace-path")
+            .help("Instead of running *command*, print the canonical guest path, the bindings considered and the host path of *guestpath*.")
+            .value_name("guestpath")
+            .takes_value(true)
+            .conflicts_with_all(&["benchmark", "seccomp-bpf-dump", "dump-elf", "timeout", "command"]))
+        .arg(Arg::with_name("log-format")
+            .long("log-format")
+            .help("Print the logs of proot-rs (filtered with RUST_LOG) in *format*.")
+            .value_name("format")
+            .takes_value(true)
+            .possible_values(LogFormat::NAMES)
+            .default_value("plain"))
+        .arg(Arg::with_name("color")
+            .long("color")
+            .help("Color the logs of proot-rs: *when* is auto (only on a terminal, the default), always or never.")
+            .value_name("when")
+            .takes_value(true)
+            .possible_values(logging::COLOR_NAMES))
+        .arg(Arg::with_name("socket-families")
+            .long("socket-families")
+            .help("Only allow the sockets of the address families in *list* (e.g. unix,inet,inet6), the others fail with EAFNOSUPPORT.")
+            .value_name("list")
+            .takes_value(true)
+            .validator(|value| parse_socket_families(&value).map(|_| ()).map_err(|e| e.to_string())))
+        .arg(Arg::with_name("strace-filter")
+            .long("strace-filter")
+            .help("Only print the system calls in *list* (e.g. open,openat,execve) in the trace log (RUST_LOG=trace).")
+            .value_name("list")
+            .takes_value(true)
+            .validator(|value| parse_syscall_list(&value).map(|_| ()).map_err(|e| e.to_string())))
+        .arg(Arg::with_name("fail-on-unhandled-syscall")
+            .long("fail-on-unhandled-syscall")
+            .help("Log an error when a system call takes a path which is not translated by proot-rs, and with *action* enosys make it fail with ENOSYS (log only logs it).")
+            .value_name("action")
+            .takes_value(true)
+            .possible_values(UnhandledSyscallAction::NAMES))
         .arg(Arg::with_name("command")
             .multiple(true))
 }
 
-pub fn parse_config() -> Result<(FileSystem, Vec<String>)> {
+pub fn parse_config() -> Result<Config> {
     let app = get_args_parser();
 
-    let mut fs: FileSystem = FileSystem::new();
-
     let matches = app.get_matches();
 
+    // options --log-format and --color, applied first so that the rest of the
+    // parsing is logged
+    let log_format: LogFormat = matches.value_of("log-format").unwrap().parse().unwrap();
+    let log_color = matches
+        .value_of("color")
+        .map(|value| logging::parse_color(value).unwrap());
+    logging::init(log_format, log_color);
+
     debug!("proot-rs startup with args:\n{:#?}", matches);
 
-    // option -r
-    let rootfs: &str = matches.value_of("rootfs").unwrap();
+    config_from_matches(&matches)
+}
+
+/// Builds the `Config` from the parsed command line (and the configuration
+/// file given with `--config`, if any).
+fn config_from_matches(matches: &ArgMatches) -> Result<Config> {
+    let mut fs: FileSystem = FileSystem::new();
+
+    // option --config
+    let config_file = match matches.value_of("config") {
+        Some(path) => ConfigFile::load(Path::new(path))?,
+        None => ConfigFile::default(),
+    };
+
+    // option -r, the default value is overridden by the config file
+    let rootfs: &str = match config_file.rootfs {
+        Some(ref rootfs) if matches.occurrences_of("rootfs") == 0 => rootfs,
+        _ => matches.value_of("rootfs").unwrap(),
+    };
+    // option --rootfs-is-file
+    let rootfs_image = if matches.is_present("rootfs-is-file") {
+        Some(extract_rootfs_image(Path::new(rootfs))?)
+    } else {
+        None
+    };
     // -r *path* is equivalent to -b *path*:/
-    fs.set_root(rootfs)?;
+    match rootfs_image {
+        Some(ref rootfs_image) => fs.set_root(&rootfs_image.path)?,
+        None => fs.set_root(rootfs)?,
+    }
 
-    // option(s) -b
+    // option(s) -b, after the bindings of the config file so that they take
+    // precedence
+    for binding in &config_file.bind {
+        fs.add_binding(&binding.host, &binding.guest)?;
+    }
     if let Some(bindings) = matches.values_of("bind") {
         let raw_bindings_str: Vec<&str> = bindings.collect::<Vec<&str>>();
 
@@ -59,15 +364,262 @@ pub fn parse_config() -> Result<(FileSystem, Vec<String>)> {
         }
     }
 
-    // option -w
-    let cwd: &str = matches.value_of("cwd").unwrap();
+    // option --mount-proc, after the bindings so that they are listed in its
+    // mounts
+    let synthetic_proc = if matches.is_present("mount-proc") {
+        let synthetic_proc = create_synthetic_proc(&fs)?;
+        fs.add_binding(&synthetic_proc.path, "/proc")?;
+        Some(synthetic_proc)
+    } else {
+        None
+    };
+
+    // option --mount-sys
+    let synthetic_sys = if matches.is_present("mount-sys") {
+        Some(create_synthetic_sys(&mut fs)?)
+    } else {
+        None
+    };
+
+    // option -w, the default value is overridden by the config file
+    let cwd: &str = match config_file.cwd {
+        Some(ref cwd) if matches.occurrences_of("cwd") == 0 => cwd,
+        _ => matches.value_of("cwd").unwrap(),
+    };
     fs.set_cwd(cwd)?;
 
     // command
-    let command: Vec<String> = match matches.values_of("command") {
-        Some(values) => values.map(|s| s.into()).collect(),
-        None => ["/bin/sh".into()].into(),
+    let command: Vec<String> = match (matches.values_of("command"), config_file.command) {
+        (Some(values), _) => values.map(|s| s.into()).collect(),
+        (None, Some(command)) => command,
+        (None, None) => ["/bin/sh".into()].into(),
     };
 
-    Ok((fs, command))
+    // environment of the config file
+    let env: Vec<(String, String)> = config_file.env.into_iter().collect();
+
+    // option --benchmark
+    let benchmark = matches
+        .value_of("benchmark")
+        .map(|value| value.parse::<usize>().unwrap());
+
+    // option --virtual-uptime
+    let virtual_uptime = matches.is_present("virtual-uptime");
+
+    // option --no-proc-emulation
+    let proc_emulation = !matches.is_present("no-proc-emulation");
+
+    // option --emulate-dev
+    let dev_emulation = matches.is_present("emulate-dev");
+
+    // option --hide-host-pids
+    let hide_host_pids = matches.is_present("hide-host-pids");
+
+    // option --chroot-only, whose paths are not translated through the
+    // bindings
+    let chroot_only = matches.is_present("chroot-only");
+    if chroot_only && fs.get_bindings().count() > 1 {
+        return Err(Error::errno_with_msg(
+            EINVAL,
+            "--chroot-only cannot be used with bindings (--bind, --mount-proc, --mount-sys or the config file)",
+        ));
+    }
+
+    // option --init
+    let init = matches.is_present("init");
+
+    // option --fake-root
+    let fake_root = matches.is_present("fake-root");
+
+    // options --map-uid and --map-gid
+    let id_mappings = |name: &str| -> Vec<(u32, u32)> {
+        matches.values_of(name).map_or(vec![], |values| {
+            values
+                .map(|value| parse_id_mapping(value).unwrap())
+                .collect()
+        })
+    };
+    let uid_mappings = id_mappings("map-uid");
+    let gid_mappings = id_mappings("map-gid");
+
+    // option --allow-io-uring
+    let allow_io_uring = matches.is_present("allow-io-uring");
+
+    // options --allow-bpf and --allow-perf
+    let allow_bpf = matches.is_present("allow-bpf");
+    let allow_perf = matches.is_present("allow-perf");
+
+    // option --allow-keyring
+    let allow_keyring = matches.is_present("allow-keyring");
+
+    // option --allow-setns
+    let allow_setns = matches.is_present("allow-setns");
+
+    // option --scoped-sync
+    let scoped_sync = matches.is_present("scoped-sync");
+
+    // option --cpus
+    let cpus = matches
+        .value_of("cpus")
+        .map(|value| parse_cpu_list(value).unwrap());
+
+    // option --mq-namespace
+    let mq_namespace = matches.is_present("mq-namespace");
+
+    // options --exit-code-128 and --exit-code-passthrough
+    let exit_code_passthrough = matches.is_present("exit-code-passthrough");
+
+    // option --collect-stats
+    let collect_stats = matches.is_present("collect-stats");
+
+    // option --timeout
+    let timeout = matches
+        .value_of("timeout")
+        .map(|value| value.parse::<u32>().unwrap());
+
+    // option --max-interp-depth
+    let max_interp_depth = matches
+        .value_of("max-interp-depth")
+        .map_or(INTERPRETER_REWRITE_LIMIT, |value| {
+            value.parse::<usize>().unwrap()
+        });
+
+    // option --seccomp-bpf-dump
+    let seccomp_bpf_dump = matches.is_present("seccomp-bpf-dump");
+
+    // option --dump-elf
+    let dump_elf = matches.is_present("dump-elf");
+
+    // option --trace-path
+    let trace_path = matches.value_of("trace-path").map(PathBuf::from);
+
+    // option --strace-filter
+    let strace_filter = matches
+        .value_of("strace-filter")
+        .map(|value| parse_syscall_list(value).unwrap());
+
+    // option --socket-families
+    let socket_families = matches
+        .value_of("socket-families")
+        .map(|value| parse_socket_families(value).unwrap());
+
+    // option --fail-on-unhandled-syscall
+    let unhandled_syscall_action = matches
+        .value_of("fail-on-unhandled-syscall")
+        .map(|value| value.parse::<UnhandledSyscallAction>().unwrap());
+
+    Ok(Config {
+        fs,
+        rootfs_image,
+        synthetic_proc,
+        synthetic_sys,
+        command,
+        env,
+        benchmark,
+        virtual_uptime,
+        proc_emulation,
+        dev_emulation,
+        hide_host_pids,
+        chroot_only,
+        init,
+        fake_root,
+        uid_mappings,
+        gid_mappings,
+        allow_io_uring,
+        allow_bpf,
+        allow_perf,
+        allow_keyring,
+        allow_setns,
+        scoped_sync,
+        cpus,
+        mq_namespace,
+        exit_code_passthrough,
+        collect_stats,
+        timeout,
+        max_interp_depth,
+        seccomp_bpf_dump,
+        dump_elf,
+        trace_path,
+        strace_filter,
+        socket_families,
+        unhandled_syscall_action,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::filesystem::binding::Side;
+    use crate::utils::tests::get_test_rootfs_path;
+
+    #[test]
+    fn test_config_from_config_file() {
+        let rootfs = get_test_rootfs_path();
+        let dir = TempDir::new("prefix_test_config_from_config_file");
+        dir.create_dir().unwrap();
+        let config_path = dir.path.join("proot-rs.toml");
+        fs::write(
+            &config_path,
+            format!(
+                "rootfs = {:?}\n\
+                 cwd = \"/home\"\n\
+                 command = [\"/bin/echo\", \"hello\"]\n\
+                 [[bind]]\n\
+                 host = \"/etc\"\n\
+                 guest = \"/home\"\n\
+                 [env]\n\
+                 TERM = \"xterm\"\n",
+                rootfs
+            ),
+        )
+        .unwrap();
+        let config_path = config_path.to_str().unwrap();
+
+        let matches = get_args_parser().get_matches_from(vec!["proot-rs", "--config", config_path]);
+        let config = config_from_matches(&matches).unwrap();
+        assert_eq!(config.fs.get_root(), rootfs);
+        assert_eq!(config.fs.get_cwd(), Path::new("/home"));
+        let bindings: Vec<(&PathBuf, &PathBuf)> = config
+            .fs
+            .get_bindings()
+            .map(|binding| (binding.get_path(Side::Host), binding.get_path(Side::Guest)))
+            .collect();
+        assert_eq!(
+            bindings,
+            vec![
+                (&PathBuf::from("/etc"), &PathBuf::from("/home")),
+                (&rootfs, &PathBuf::from("/")),
+            ]
+        );
+        assert_eq!(config.command, vec!["/bin/echo", "hello"]);
+        assert_eq!(config.env, vec![("TERM".to_string(), "xterm".to_string())]);
+
+        // The command line takes precedence.
+        let matches = get_args_parser().get_matches_from(vec![
+            "proot-rs",
+            "--config",
+            config_path,
+            "-w",
+            "/",
+            "-b",
+            "/tmp:/tmp",
+            "/bin/true",
+        ]);
+        let config = config_from_matches(&matches).unwrap();
+        assert_eq!(config.fs.get_root(), rootfs);
+        assert_eq!(config.fs.get_cwd(), Path::new("/"));
+        assert_eq!(
+            config
+                .fs
+                .get_bindings()
+                .next()
+                .unwrap()
+                .get_path(Side::Guest),
+            Path::new("/tmp")
+        );
+        assert_eq!(config.command, vec!["/bin/true"]);
+    }
 }
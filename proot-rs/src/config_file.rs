@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::errors::*;
+
+/// The content of a configuration file given with `--config`, e.g.:
+///
+/// ```toml
+/// rootfs = "/path/to/rootfs"
+/// cwd = "/root"
+/// command = ["/bin/sh", "-l"]
+///
+/// [[bind]]
+/// host = "/home/user/src"
+/// guest = "/src"
+///
+/// [env]
+/// TERM = "xterm"
+/// ```
+///
+/// All the keys are optional. The command line takes precedence: `--rootfs`,
+/// `--cwd` and *command* replace the ones of the file, and the bindings of
+/// `--bind` are added after (so on top of) the ones of the file.
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Host path of the guest root file-system.
+    pub rootfs: Option<String>,
+    /// The bindings, in the order they are added.
+    #[serde(default)]
+    pub bind: Vec<BindingConfig>,
+    /// Initial working directory in the guest.
+    pub cwd: Option<String>,
+    /// Variables added to the environment of the command.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// The command to run in the guest rootfs.
+    pub command: Option<Vec<String>>,
+}
+
+/// A binding of the configuration file, the same as `-b host:guest`.
+///
+/// There is no `ro` key: proot-rs has no read-only bindings to enforce, and
+/// every syscall which writes through a path or a fd would have to check for
+/// them. The key is rejected, rather than ignored, so that a binding is never
+/// thought to be read-only while the guest can write to it.
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct BindingConfig {
+    pub host: String,
+    pub guest: String,
+}
+
+impl ConfigFile {
+    /// Reads and parses the TOML configuration file at `path`.
+    pub fn load(path: &Path) -> Result<ConfigFile> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read the config file {:?}", path))?;
+        let config_file = ConfigFile::parse(&content)
+            .with_context(|| format!("Invalid config file {:?}", path))?;
+        Ok(config_file)
+    }
+
+    fn parse(content: &str) -> Result<ConfigFile> {
+        toml::from_str(content).errno(EINVAL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_file_parse() {
+        assert_eq!(ConfigFile::parse("").unwrap(), ConfigFile::default());
+
+        let config_file = ConfigFile::parse(
+            r#"
+            rootfs = "/rootfs"
+            cwd = "/root"
+            command = ["/bin/echo", "hello"]
+
+            [[bind]]
+            host = "/etc"
+            guest = "/home"
+
+            [[bind]]
+            host = "/tmp"
+            guest = "/tmp"
+
+            [env]
+            TERM = "xterm"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config_file.rootfs.as_deref(), Some("/rootfs"));
+        assert_eq!(config_file.cwd.as_deref(), Some("/root"));
+        assert_eq!(
+            config_file.command,
+            Some(vec!["/bin/echo".to_string(), "hello".to_string()])
+        );
+        assert_eq!(
+            config_file.bind,
+            vec![
+                BindingConfig {
+                    host: "/etc".into(),
+                    guest: "/home".into(),
+                },
+                BindingConfig {
+                    host: "/tmp".into(),
+                    guest: "/tmp".into(),
+                },
+            ]
+        );
+        assert_eq!(
+            config_file.env.get("TERM").map(String::as_str),
+            Some("xterm")
+        );
+
+        assert_eq!(
+            ConfigFile::parse("root = \"/rootfs\"")
+                .unwrap_err()
+                .get_errno(),
+            EINVAL
+        );
+        // The bindings have no other key, and in particular no `ro` key.
+        assert_eq!(
+            ConfigFile::parse("[[bind]]\nhost = \"/etc\"\nguest = \"/etc\"\nro = true")
+                .unwrap_err()
+                .get_errno(),
+            EINVAL
+        );
+    }
+}
@@ -7,8 +7,23 @@ use crate::filesystem::FileSystem;
 
 use super::ext::{PathBufExt, PathExt};
 
+/// Maximum number of symlinks followed while canonicalizing a path, which is
+/// the same as `MAXSYMLINKS` in the kernel.
+const MAX_SYMLINKS: usize = 40;
+
 pub trait Canonicalizer {
-    fn canonicalize<P: AsRef<Path>>(&self, path: P, deref_final: bool) -> Result<PathBuf>;
+    fn canonicalize<P: AsRef<Path>>(&self, path: P, deref_final: bool) -> Result<PathBuf> {
+        self.canonicalize_following(path, deref_final, 0)
+    }
+
+    /// Same as `canonicalize()`, `symlinks` being the number of symlinks
+    /// already followed.
+    fn canonicalize_following<P: AsRef<Path>>(
+        &self,
+        path: P,
+        deref_final: bool,
+        symlinks: usize,
+    ) -> Result<PathBuf>;
 }
 
 impl Canonicalizer for FileSystem {
@@ -51,7 +66,14 @@ impl Canonicalizer for FileSystem {
     /// - An error occurred while calling `Substitutor::substitute()` to convert
     ///   to the host side path
     /// - A non-final component in path is not a directory.
-    fn canonicalize<P: AsRef<Path>>(&self, guest_path: P, deref_final: bool) -> Result<PathBuf> {
+    /// - More than `MAX_SYMLINKS` symlinks are followed (e.g. with a symlink
+    ///   loop), in which case `ELOOP` is returned.
+    fn canonicalize_following<P: AsRef<Path>>(
+        &self,
+        guest_path: P,
+        deref_final: bool,
+        symlinks: usize,
+    ) -> Result<PathBuf> {
         let guest_path = guest_path.as_ref();
         // The `guest_path` must be absolute path
         if guest_path.is_relative() {
@@ -85,7 +107,12 @@ impl Canonicalizer for FileSystem {
                     continue;
                 }
                 Component::ParentDir => {
-                    guest_path_new.pop();
+                    // ".." never goes above the guest root, as with chroot(2). This also
+                    // applies to the ".." of the symlinks, since their value is
+                    // canonicalized the same way.
+                    if guest_path_new.parent().is_some() {
+                        guest_path_new.pop();
+                    }
                     continue;
                 }
                 Component::Normal(path_part) => {
@@ -120,7 +147,6 @@ impl Canonicalizer for FileSystem {
                     }
                     if file_type.is_symlink() {
                         // we need to deref
-                        // TODO: add test for this
                         let link_value = host_path.read_link()?;
                         let mut new_user_path = if link_value.is_absolute() {
                             // link_value is a absolute path, so we need to replace user_path
@@ -142,9 +168,19 @@ impl Canonicalizer for FileSystem {
                             // recover the trailing slash
                             new_user_path.try_add_trailing_slash();
                         }
+                        if symlinks == MAX_SYMLINKS {
+                            return Err(Error::errno_with_msg(
+                                Errno::ELOOP,
+                                format!("Too many symlinks when canonicalizing {:?}", guest_path),
+                            ));
+                        }
                         // use new_user_path to call this function again and return
                         // TODO: Can be optimized by replacing `it`
-                        return self.canonicalize(&new_user_path, deref_final);
+                        return self.canonicalize_following(
+                            new_user_path,
+                            deref_final,
+                            symlinks + 1,
+                        );
                     }
                     // we cannot go through a path which is neither a directory nor a symlink
                     if !is_last_component
@@ -162,6 +198,9 @@ impl Canonicalizer for FileSystem {
             }
         }
 
+        debug_assert!(guest_path_new
+            .components()
+            .all(|component| component != Component::ParentDir));
         Ok(guest_path_new)
     }
 }
@@ -174,6 +213,7 @@ mod tests {
 
     use super::*;
     use crate::filesystem::ext::PathExt;
+    use crate::filesystem::temp::TempDir;
     use crate::filesystem::FileSystem;
     use crate::utils::tests::get_test_rootfs_path;
 
@@ -232,6 +272,100 @@ mod tests {
         let path = PathBuf::from("/../bin");
         assert_eq!(fs.canonicalize(&path, false), Ok("/bin".into()));
     }
+    #[test]
+    fn test_canonicalize_cannot_escape_root() {
+        use std::os::unix::fs::symlink;
+
+        use crate::filesystem::Translator;
+
+        let rootfs = TempDir::new("prefix_test_canonicalize_cannot_escape_root");
+        rootfs.create_dir().unwrap();
+        let rootfs_path = &rootfs.path;
+        std::fs::create_dir_all(rootfs_path.join("etc/dir")).unwrap();
+        std::fs::write(rootfs_path.join("etc/passwd"), b"guest").unwrap();
+        symlink("../../../../../../..", rootfs_path.join("up")).unwrap();
+        symlink("/../../../etc", rootfs_path.join("etc/dir/abs")).unwrap();
+        symlink(
+            "../../up/../etc/dir/chain_2",
+            rootfs_path.join("etc/dir/chain_1"),
+        )
+        .unwrap();
+        symlink(
+            "../../../up/up/etc/passwd",
+            rootfs_path.join("etc/dir/chain_2"),
+        )
+        .unwrap();
+
+        let fs = FileSystem::with_root(&rootfs_path).unwrap();
+        let root = fs.get_root().to_path_buf();
+
+        for (guest_path, expected) in &[
+            ("/../../../etc/passwd", "/etc/passwd"),
+            ("/etc/dir/../../../../../../etc/passwd", "/etc/passwd"),
+            ("/up/etc/passwd", "/etc/passwd"),
+            ("/up/../../up/etc/dir/../passwd", "/etc/passwd"),
+            ("/etc/dir/abs/passwd", "/etc/passwd"),
+            ("/etc/dir/chain_1", "/etc/passwd"),
+            ("/etc/dir/abs/../../..", "/"),
+        ] {
+            assert_eq!(
+                fs.canonicalize(guest_path, true),
+                Ok(PathBuf::from(expected)),
+                "{}",
+                guest_path
+            );
+            let (_, host_path) = fs.translate_path(guest_path, true).unwrap();
+            assert!(host_path.starts_with(&root), "{:?}", host_path);
+        }
+    }
+
+    #[test]
+    fn test_canonicalize_symlink_loop() {
+        use std::os::unix::fs::symlink;
+
+        let rootfs = TempDir::new("prefix_test_canonicalize_symlink_loop");
+        rootfs.create_dir().unwrap();
+        let rootfs_path = &rootfs.path;
+        std::fs::write(rootfs_path.join("file"), b"guest").unwrap();
+        symlink("loop", rootfs_path.join("loop")).unwrap();
+        symlink("/ping", rootfs_path.join("pong")).unwrap();
+        symlink("/pong", rootfs_path.join("ping")).unwrap();
+        // `chain_0` -> `chain_1` -> ... -> `chain_40` -> `file`
+        for i in 0..=MAX_SYMLINKS {
+            let target = if i == MAX_SYMLINKS {
+                "file".to_string()
+            } else {
+                format!("chain_{}", i + 1)
+            };
+            symlink(target, rootfs_path.join(format!("chain_{}", i))).unwrap();
+        }
+
+        let fs = FileSystem::with_root(&rootfs_path).unwrap();
+
+        for (guest_path, deref_final) in &[
+            ("/loop", true),
+            ("/loop/file", false),
+            ("/ping", true),
+            ("/pong/file", false),
+            ("/chain_0", true),
+        ] {
+            assert_eq!(
+                fs.canonicalize(guest_path, *deref_final),
+                Err(Error::errno(Errno::ELOOP)),
+                "{}",
+                guest_path
+            );
+        }
+
+        // The final component is not followed.
+        assert_eq!(fs.canonicalize("/loop", false), Ok(PathBuf::from("/loop")));
+        // Exactly `MAX_SYMLINKS` symlinks can be followed.
+        assert_eq!(
+            fs.canonicalize("/chain_1", true),
+            Ok(PathBuf::from("/file"))
+        );
+    }
+
     #[test]
     fn test_canonicalize_normal_path() {
         let rootfs_path = get_test_rootfs_path();
@@ -102,12 +102,17 @@ impl FileSystem {
             if from_side == Host
                 && !self.root.eq(&PathBuf::from("/"))
                 && self.belongs_to_guestfs(path)
+                && self.root.starts_with(binding_path)
             {
                 // Avoid false positive when a prefix of the rootfs is
                 // used as an asymmetric binding, ex.:
                 //
                 //     proot -m /usr:/location -r /usr/local/slackware
                 //
+                // However, a binding whose host path is inside the rootfs
+                // (e.g. `-b ${rootfs}/tmp:/home`) is still used, otherwise a
+                // path inside it (such as the cwd obtained from `fchdir()`)
+                // would be detranslated to `/tmp` instead of `/home`.
                 continue;
             }
 
@@ -117,6 +122,22 @@ impl FileSystem {
         None
     }
 
+    /// Returns all the bindings, the most recent first.
+    pub fn get_bindings(&self) -> impl Iterator<Item = &Binding> {
+        self.bindings.iter()
+    }
+
+    /// Returns the bindings whose guest path is an entry of the directory
+    /// `guest_dir`, the most recent first.
+    pub fn get_bindings_in_dir<'a>(
+        &'a self,
+        guest_dir: &'a Path,
+    ) -> impl Iterator<Item = &'a Binding> + 'a {
+        self.bindings
+            .iter()
+            .filter(move |binding| binding.get_path(Side::Guest).parent() == Some(guest_dir))
+    }
+
     #[inline]
     /// Checks if a `host_path` is a file, does exist and is executable.
     pub fn check_host_path_executable(host_path: &Path) -> Result<()> {
@@ -159,14 +180,25 @@ impl FileSystem {
         }
 
         let (canonical_guest_path, host_path) = self.translate_absolute_path(guest_path, true)?;
+        self.set_translated_cwd(canonical_guest_path, &host_path)
+    }
 
+    /// Same as `set_cwd()`, but for a path which has already been translated,
+    /// e.g. by `Tracee::translate_path_at()`, so that it is not canonicalized
+    /// a second time. `canonical_guest_path` and `host_path` are the two
+    /// paths returned by the translation.
+    pub fn set_translated_cwd(
+        &mut self,
+        canonical_guest_path: PathBuf,
+        host_path: &Path,
+    ) -> Result<()> {
         // To change cwd to a dir, the tracee must have execute (`x`) permission to this
         // dir, FIXME: This may be wrong, because we need to check if tracee has
         // permission
         if !host_path.metadata()?.is_dir() {
             return Err(Error::errno(Errno::ENOTDIR));
         }
-        nix::unistd::access(&host_path, AccessFlags::X_OK)?;
+        nix::unistd::access(host_path, AccessFlags::X_OK)?;
 
         self.cwd = canonical_guest_path;
         Ok(())
@@ -186,6 +218,77 @@ impl FileSystem {
         Ok(())
     }
 
+    /// Emulates `pivot_root(new_root, put_old)` inside the sandbox: the guest
+    /// directory `new_root` becomes the new `/`, and the old root is moved to
+    /// `put_old`, which must be at or under `new_root`.
+    ///
+    /// Both paths must be absolute and canonical guest side paths. All the
+    /// bindings are moved along with the root. If `put_old` is the same as
+    /// `new_root` (i.e. `pivot_root(".", ".")`), the old root is stacked on top
+    /// of the new one, which is usually followed by an unmount; it is simply
+    /// dropped here.
+    pub fn pivot_root<P1, P2>(&mut self, new_root: P1, put_old: P2) -> Result<()>
+    where
+        P1: AsRef<Path>,
+        P2: AsRef<Path>,
+    {
+        let new_root = new_root.as_ref();
+        let put_old = put_old.as_ref();
+
+        if new_root == Path::new("/") {
+            return Err(Error::errno_with_msg(
+                EBUSY,
+                "new_root of pivot_root() is the current root",
+            ));
+        }
+        if !put_old.starts_with(new_root) {
+            return Err(Error::errno_with_msg(
+                EINVAL,
+                format!(
+                    "put_old {:?} of pivot_root() is not under new_root {:?}",
+                    put_old, new_root
+                ),
+            ));
+        }
+
+        let new_root_host = self.substitute(new_root, Side::Guest)?;
+        // The new guest path of the old root, or `None` if it is dropped.
+        let put_old = Some(put_old.strip_prefix(new_root).unwrap())
+            .filter(|path| !path.as_os_str().is_empty())
+            .map(|path| Path::new("/").join(path));
+        // Computes the new guest path of `guest_path`, if still reachable.
+        let rebase = |guest_path: &Path| -> Option<PathBuf> {
+            match guest_path.strip_prefix(new_root) {
+                Ok(path) => Some(Path::new("/").join(path)),
+                Err(_) => put_old
+                    .as_ref()
+                    .map(|put_old| put_old.join(guest_path.strip_prefix("/").unwrap())),
+            }
+        };
+
+        let mut bindings: Vec<Binding> = self
+            .bindings
+            .iter()
+            .filter_map(|binding| {
+                rebase(binding.get_path(Side::Guest))
+                    .map(|guest| Binding::new(binding.get_path(Side::Host), guest, true))
+            })
+            .collect();
+        // The root binding must be the last one, since it matches every path.
+        bindings.push(Binding::new(&new_root_host, "/", true));
+
+        let cwd = match self.cwd.as_path() {
+            // Like the kernel, a cwd on the old root is moved to the new root.
+            cwd if cwd == Path::new("/") => PathBuf::from("/"),
+            cwd => rebase(cwd).unwrap_or_else(|| PathBuf::from("/")),
+        };
+
+        self.bindings = bindings;
+        self.root = new_root_host;
+        self.cwd = cwd;
+        Ok(())
+    }
+
     #[inline]
     pub fn get_root(&self) -> &Path {
         &self.root
@@ -406,4 +509,74 @@ mod tests {
 
         Ok(())
     }
+
+    /// The cwd inside a binding should be recorded as a guest path, and should
+    /// survive a round trip through its host path (e.g. with `fchdir()`).
+    #[test]
+    fn test_fs_set_cwd_in_binding() -> Result<()> {
+        let root_path = get_test_rootfs_path();
+        let mut fs = FileSystem::with_root(&root_path)?;
+        fs.add_binding("/etc", "/home")?;
+        fs.add_binding(root_path.join("etc"), "/tmp")?;
+
+        fs.set_cwd("/home")?;
+        assert_eq!(fs.get_cwd(), Path::new("/home"));
+        let host_path = fs.translate_path(fs.get_cwd(), true)?.1;
+        assert_eq!(host_path, Path::new("/etc"));
+        assert_eq!(
+            fs.detranslate_path(&host_path, None)?,
+            Some(PathBuf::from("/home"))
+        );
+
+        fs.set_cwd("/tmp/../tmp/")?;
+        assert_eq!(fs.get_cwd(), Path::new("/tmp"));
+        let host_path = fs.translate_path(fs.get_cwd(), true)?.1;
+        assert_eq!(host_path, root_path.join("etc"));
+        assert_eq!(
+            fs.detranslate_path(&host_path, None)?,
+            Some(PathBuf::from("/tmp"))
+        );
+
+        Ok(())
+    }
+
+    /// Unit test for `FileSystem::pivot_root()`
+    #[test]
+    fn test_fs_pivot_root() -> Result<()> {
+        let root_path = get_test_rootfs_path();
+        let mut fs = FileSystem::with_root(&root_path)?;
+        fs.add_binding("/etc", "/home")?;
+        fs.set_cwd("/home")?;
+
+        // `new_root` cannot be the current root.
+        assert_eq!(fs.pivot_root("/", "/tmp").unwrap_err().get_errno(), EBUSY);
+        // `put_old` must be under `new_root`.
+        assert_eq!(
+            fs.pivot_root("/usr", "/tmp").unwrap_err().get_errno(),
+            EINVAL
+        );
+
+        fs.pivot_root("/usr", "/usr/lib")?;
+        assert_eq!(fs.get_root(), root_path.join("usr"));
+        assert_eq!(
+            fs.substitute("/bin", Side::Guest)?,
+            root_path.join("usr/bin")
+        );
+        // the old root and its bindings are moved to `put_old`.
+        assert_eq!(
+            fs.substitute("/lib/bin", Side::Guest)?,
+            root_path.join("bin")
+        );
+        assert_eq!(fs.substitute("/lib/home", Side::Guest)?, Path::new("/etc"));
+        assert_eq!(fs.get_cwd(), Path::new("/lib/home"));
+
+        // `pivot_root(".", ".")` drops the old root.
+        fs.pivot_root("/lib", "/lib")?;
+        assert_eq!(fs.get_root(), root_path);
+        assert_eq!(fs.substitute("/bin", Side::Guest)?, root_path.join("bin"));
+        assert_eq!(fs.substitute("/home", Side::Guest)?, Path::new("/etc"));
+        assert_eq!(fs.get_cwd(), Path::new("/home"));
+
+        Ok(())
+    }
 }
@@ -3,8 +3,12 @@ pub mod canonicalization;
 pub mod ext;
 mod fs;
 pub mod readers;
+pub mod rootfs_image;
 pub mod substitution;
+pub mod synthetic_proc;
+pub mod synthetic_sys;
 pub mod temp;
+pub mod trace;
 mod translation;
 pub mod validation;
 
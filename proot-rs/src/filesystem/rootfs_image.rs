@@ -0,0 +1,88 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::errors::*;
+use crate::filesystem::temp::TempDir;
+
+/// Extracts the rootfs archive `image` into a new temporary directory, which
+/// is deleted when the returned `TempDir` is dropped.
+///
+/// The archive is extracted with the `tar` program of the host, which needs no
+/// privilege and detects the compression by itself. Images which need to be
+/// mounted (e.g. squashfs or ext4) are not supported, `tar` fails on them.
+///
+/// The owners of the files are not restored, and special files which cannot
+/// be created without privilege (e.g. device nodes) make the extraction fail.
+pub fn extract_rootfs_image(image: &Path) -> Result<TempDir> {
+    let rootfs = TempDir::new("proot-rs-rootfs");
+    rootfs
+        .create_dir()
+        .with_context(|| format!("Failed to create a directory to extract {:?}", image))?;
+
+    debug!("extracting the rootfs {:?} into {:?}", image, rootfs.path);
+    let status = Command::new("tar")
+        .arg("--extract")
+        .arg("--no-same-owner")
+        .arg("--file")
+        .arg(image)
+        .arg("--directory")
+        .arg(&rootfs.path)
+        .status()
+        .with_context(|| format!("Failed to run tar to extract {:?}", image))?;
+    if !status.success() {
+        return Err(Error::errno_with_msg(
+            EINVAL,
+            format!(
+                "Failed to extract the rootfs {:?} ({}), only tar archives are supported",
+                image, status
+            ),
+        ));
+    }
+
+    Ok(rootfs)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::filesystem::temp::TempFile;
+
+    #[test]
+    fn test_extract_rootfs_image() {
+        let source = TempDir::new("test-rootfs-image-source");
+        source.create_dir().unwrap();
+        fs::create_dir(source.path.join("etc")).unwrap();
+        fs::write(source.path.join("etc/hostname"), b"image").unwrap();
+
+        let image = TempFile::new("test-rootfs-image");
+        let status = Command::new("tar")
+            .arg("--create")
+            .arg("--file")
+            .arg(&image.path)
+            .arg("--directory")
+            .arg(&source.path)
+            .arg(".")
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let rootfs_path = {
+            let rootfs = extract_rootfs_image(&image.path).unwrap();
+            assert_eq!(
+                fs::read(rootfs.path.join("etc/hostname")).unwrap(),
+                b"image"
+            );
+            rootfs.path.clone()
+        };
+        // The extracted rootfs is deleted along with the `TempDir`.
+        assert!(!rootfs_path.exists());
+
+        // Anything else than an archive is rejected.
+        assert_eq!(
+            extract_rootfs_image(&source.path.join("etc/hostname")).map(|_| ()),
+            Err(Error::errno(EINVAL))
+        );
+    }
+}
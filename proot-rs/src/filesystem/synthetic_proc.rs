@@ -0,0 +1,96 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use crate::errors::*;
+use crate::filesystem::binding::Side;
+use crate::filesystem::temp::TempDir;
+use crate::filesystem::FileSystem;
+
+/// Creates the minimal `/proc` of `--mount-proc` in a new temporary directory,
+/// which is deleted when the returned `TempDir` is dropped. It is meant to be
+/// bound on `/proc` of the guest.
+///
+/// Only a few entries are provided:
+/// - `self`, `self/fd`: plain directories, since the links of `/proc/self`
+///   (`exe`, `cwd` and the fds) are emulated by the path translation, see
+///   `Tracee::translate_magic_proc_link()`;
+/// - `mounts`: the rootfs and the bindings of `fs`, with their guest paths;
+/// - `cpuinfo`: a copy of the one of the host, if it can be read.
+pub fn create_synthetic_proc(fs: &FileSystem) -> Result<TempDir> {
+    let proc = TempDir::new("proot-rs-proc");
+    proc.create_dir()
+        .context("Failed to create the directory of the synthetic /proc")?;
+
+    fs::create_dir_all(proc.path.join("self/fd"))?;
+
+    let mut mounts = fs::File::create(proc.path.join("mounts"))?;
+    mounts.write_all(format_mounts(fs).as_bytes())?;
+
+    // An empty cpuinfo is better than none, most programs only count the
+    // "processor" lines.
+    let cpuinfo = fs::read("/proc/cpuinfo").unwrap_or_default();
+    fs::write(proc.path.join("cpuinfo"), cpuinfo)?;
+
+    Ok(proc)
+}
+
+/// Returns the content of `/proc/mounts`, in the format of `fstab(5)`.
+fn format_mounts(fs: &FileSystem) -> String {
+    let mut mounts = String::from("rootfs / rootfs rw 0 0\n");
+    // The oldest bindings first, as they would have been mounted.
+    let bindings: Vec<_> = fs.get_bindings().collect();
+    for binding in bindings.iter().rev() {
+        let guest_path = binding.get_path(Side::Guest);
+        if guest_path == Path::new("/") {
+            continue;
+        }
+        mounts.push_str(&format!(
+            "none {} none rw,bind 0 0\n",
+            escape_mount_path(guest_path)
+        ));
+    }
+    mounts.push_str("proc /proc proc rw 0 0\n");
+    mounts
+}
+
+/// Escapes the whitespaces and backslashes of `path` in octal, as the kernel
+/// does in `/proc/mounts`.
+fn escape_mount_path(path: &Path) -> String {
+    let mut escaped = String::new();
+    for c in path.to_string_lossy().chars() {
+        match c {
+            ' ' | '\t' | '\n' | '\\' => escaped.push_str(&format!("\\{:03o}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tests::get_test_rootfs_path;
+
+    #[test]
+    fn test_create_synthetic_proc() {
+        let mut fs = FileSystem::with_root(get_test_rootfs_path()).unwrap();
+        fs.add_binding("/etc", "/home").unwrap();
+
+        let proc_path = {
+            let proc = create_synthetic_proc(&fs).unwrap();
+            assert!(proc.path.join("self/fd").is_dir());
+            assert!(proc.path.join("cpuinfo").is_file());
+            assert_eq!(
+                fs::read_to_string(proc.path.join("mounts")).unwrap(),
+                "rootfs / rootfs rw 0 0\n\
+                 none /home none rw,bind 0 0\n\
+                 proc /proc proc rw 0 0\n"
+            );
+            proc.path.clone()
+        };
+        assert!(!proc_path.exists());
+
+        assert_eq!(escape_mount_path(Path::new("/a b\\c")), "/a\\040b\\134c");
+    }
+}
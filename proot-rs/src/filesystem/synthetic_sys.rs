@@ -0,0 +1,76 @@
+use std::fs;
+use std::path::Path;
+
+use crate::errors::*;
+use crate::filesystem::temp::TempDir;
+use crate::filesystem::FileSystem;
+
+/// The directories of the host `/sys` which show through the synthetic `/sys`
+/// of `--mount-sys`: the description of the cpus (e.g. read to count them)
+/// and the settings of the memory management.
+const PASSTHROUGH_DIRS: [&str; 2] = ["devices/system/cpu", "kernel/mm"];
+
+/// The directories of the synthetic `/sys` which are left empty, so that the
+/// programs which look for them (e.g. the cgroups, used by container tooling)
+/// do not fail.
+const STUB_DIRS: [&str; 1] = ["fs/cgroup"];
+
+/// Creates the filtered `/sys` of `--mount-sys` in a new temporary directory,
+/// which is deleted when the returned `TempDir` is dropped, and binds it on
+/// `/sys` of the guest in `fs`.
+///
+/// Only the directories of `PASSTHROUGH_DIRS` are bound from the host (if
+/// they exist there), the rest of the host `/sys` is hidden; the directories
+/// of `STUB_DIRS` are empty.
+pub fn create_synthetic_sys(fs: &mut FileSystem) -> Result<TempDir> {
+    let sys = TempDir::new("proot-rs-sys");
+    sys.create_dir()
+        .context("Failed to create the directory of the synthetic /sys")?;
+
+    for dir in STUB_DIRS.iter().chain(PASSTHROUGH_DIRS.iter()) {
+        fs::create_dir_all(sys.path.join(dir))?;
+    }
+    fs.add_binding(&sys.path, "/sys")?;
+
+    let host_sys = Path::new("/sys");
+    for dir in PASSTHROUGH_DIRS.iter() {
+        let host_path = host_sys.join(dir);
+        if host_path.is_dir() {
+            fs.add_binding(&host_path, Path::new("/sys").join(dir))?;
+        }
+    }
+
+    Ok(sys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filesystem::Translator;
+    use crate::utils::tests::get_test_rootfs_path;
+
+    #[test]
+    fn test_create_synthetic_sys() {
+        let mut fs = FileSystem::with_root(get_test_rootfs_path()).unwrap();
+
+        let sys_path = {
+            let sys = create_synthetic_sys(&mut fs).unwrap();
+            let host_path_of = |guest_path: &str| fs.translate_path(guest_path, true).unwrap().1;
+
+            assert!(host_path_of("/sys/fs/cgroup").is_dir());
+            // Filtered out.
+            assert!(!host_path_of("/sys/class").exists());
+            // Passed through.
+            let online = Path::new("/sys/devices/system/cpu/online");
+            if online.exists() {
+                assert_eq!(host_path_of("/sys/devices/system/cpu/online"), online);
+                assert_eq!(
+                    fs::read_to_string(host_path_of("/sys/devices/system/cpu/online")).unwrap(),
+                    fs::read_to_string(online).unwrap()
+                );
+            }
+            sys.path.clone()
+        };
+        assert!(!sys_path.exists());
+    }
+}
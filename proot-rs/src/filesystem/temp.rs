@@ -1,9 +1,20 @@
 use crate::errors::Result;
 use nix::unistd::getpid;
+use std::collections::hash_map::RandomState;
 use std::env;
 use std::fs;
-use std::path::PathBuf;
-
+use std::hash::{BuildHasher, Hasher};
+use std::io::ErrorKind;
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A file in the temporary directory, which is deleted when dropped (including
+/// while unwinding a panic).
+///
+/// Its name is made of a prefix, the pid of proot-rs and a random suffix, so
+/// that concurrent instances of proot-rs (or several `PRoot` in the same
+/// process) never share it.
 #[derive(Debug)]
 pub struct TempFile {
     pub path: PathBuf,
@@ -13,28 +24,102 @@ impl TempFile {
     #[inline]
     fn create_temp_path(prefix: &str) -> PathBuf {
         PathBuf::from(format!(
-            "{}/{}-{}-XXXXXX",
+            "{}/{}-{}-{}",
             env::temp_dir().to_str().unwrap(),
             prefix,
-            getpid()
+            getpid(),
+            TempFile::random_suffix()
         ))
     }
 
+    /// Returns 16 random hexadecimal digits. `RandomState` is seeded by the OS,
+    /// and the counter makes the suffixes of a same process distinct anyway.
+    fn random_suffix() -> String {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_usize(COUNTER.fetch_add(1, Ordering::Relaxed));
+        format!("{:016x}", hasher.finish())
+    }
+
     pub fn new(prefix: &str) -> Self {
         Self {
             path: TempFile::create_temp_path(prefix),
         }
     }
 
+    /// Creates the file, which must not exist yet (i.e. with `O_EXCL`), so that
+    /// a file created by someone else is never clobbered.
     pub fn create_file(&self) -> Result<fs::File> {
-        Ok(fs::File::create(&self.path)?)
+        Ok(fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&self.path)?)
     }
 }
 
 impl Drop for TempFile {
     fn drop(&mut self) {
-        if self.path.exists() {
-            fs::remove_file(&self.path).expect("delete temp file");
+        // This may run while unwinding a panic, so it must not panic itself.
+        if let Err(err) = fs::remove_file(&self.path) {
+            if err.kind() != ErrorKind::NotFound {
+                warn!("Failed to delete temp file {:?}: {}", self.path, err);
+            }
+        }
+    }
+}
+
+/// A directory in the temporary directory, named as a `TempFile`, which is
+/// deleted with its content when dropped.
+#[derive(Debug)]
+pub struct TempDir {
+    pub path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            path: TempFile::create_temp_path(prefix),
+        }
+    }
+
+    /// Creates the directory, which must not exist yet.
+    pub fn create_dir(&self) -> Result<()> {
+        Ok(fs::DirBuilder::new().mode(0o700).create(&self.path)?)
+    }
+}
+
+/// Adds the write permission to `path` and the directories under it, so that
+/// their content can be deleted.
+fn make_dirs_writable(path: &Path) -> std::io::Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(());
+    }
+    let mut permissions = metadata.permissions();
+    permissions.set_mode(permissions.mode() | 0o700);
+    fs::set_permissions(path, permissions)?;
+    for entry in fs::read_dir(path)? {
+        make_dirs_writable(&entry?.path())?;
+    }
+    Ok(())
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        // This may run while unwinding a panic, so it must not panic itself.
+        // Read-only directories (e.g. extracted from an archive) are made
+        // writable first.
+        let result = fs::remove_dir_all(&self.path).or_else(|err| match err.kind() {
+            ErrorKind::PermissionDenied => {
+                make_dirs_writable(&self.path).and_then(|_| fs::remove_dir_all(&self.path))
+            }
+            _ => Err(err),
+        });
+        if let Err(err) = result {
+            if err.kind() != ErrorKind::NotFound {
+                warn!("Failed to delete temp directory {:?}: {}", self.path, err);
+            }
         }
     }
 }
@@ -53,6 +138,42 @@ mod tests {
         assert!(temp_file.path.parent().unwrap().eq(&env::temp_dir()));
     }
 
+    #[test]
+    fn test_temp_file_paths_are_unique() {
+        let temp_file_1 = TempFile::new("test-unique");
+        let temp_file_2 = TempFile::new("test-unique");
+
+        assert_ne!(temp_file_1.path, temp_file_2.path);
+    }
+
+    #[test]
+    fn test_temp_file_is_not_clobbered() {
+        let temp_file = TempFile::new("test-clobber");
+        temp_file
+            .create_file()
+            .unwrap()
+            .write_all(b"first")
+            .unwrap();
+
+        // the file is only created once
+        assert!(temp_file.create_file().is_err());
+        assert_eq!(fs::read(&temp_file.path).unwrap(), b"first");
+    }
+
+    #[test]
+    fn test_temp_file_is_deleted_on_panic() {
+        let temp_file_path = std::sync::Mutex::new(PathBuf::new());
+        let result = std::panic::catch_unwind(|| {
+            let temp_file = TempFile::new("test-panic");
+            temp_file.create_file().unwrap();
+            *temp_file_path.lock().unwrap() = temp_file.path.clone();
+            panic!("panic while the temp file exists");
+        });
+
+        assert!(result.is_err());
+        assert!(!temp_file_path.lock().unwrap().exists());
+    }
+
     #[test]
     fn test_temp_file_is_created_and_deleted() {
         let temp_file_path = {
@@ -84,4 +205,22 @@ mod tests {
         // but it must be deleted when the TempFile is dropped (so when proot-rs stops)
         assert!(!temp_file_path.exists());
     }
+
+    #[test]
+    fn test_temp_dir_is_deleted_with_read_only_content() {
+        let temp_dir_path = {
+            let temp_dir = TempDir::new("test-dir");
+            temp_dir.create_dir().unwrap();
+            assert!(temp_dir.create_dir().is_err());
+
+            let read_only_dir = temp_dir.path.join("read_only");
+            fs::create_dir(&read_only_dir).unwrap();
+            fs::write(read_only_dir.join("file"), b"content").unwrap();
+            fs::set_permissions(&read_only_dir, fs::Permissions::from_mode(0o500)).unwrap();
+
+            temp_dir.path.clone()
+        };
+
+        assert!(!temp_dir_path.exists());
+    }
 }
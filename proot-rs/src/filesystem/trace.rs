@@ -0,0 +1,80 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::errors::*;
+use crate::filesystem::binding::Side;
+use crate::filesystem::{FileSystem, Translator};
+
+/// Returns the steps of the translation of `guest_path`, for the
+/// `--trace-path` option: its canonical guest path, every binding considered
+/// for it (the most recent first, as they are looked up), and its host path.
+///
+/// A relative `guest_path` is relative to the initial cwd. As for `stat()`, a
+/// final symlink is dereferenced.
+pub fn trace_path(fs: &FileSystem, guest_path: &Path) -> Result<String> {
+    let (canonical_guest_path, host_path) = fs.translate_path(guest_path, true)?;
+    let used_binding = fs.get_first_appropriate_binding(&canonical_guest_path, Side::Guest);
+    let mut trace = String::new();
+
+    writeln!(trace, "Guest path: {}", guest_path.display())?;
+    writeln!(
+        trace,
+        "Canonical guest path: {}",
+        canonical_guest_path.display()
+    )?;
+    writeln!(trace, "Bindings:")?;
+    for binding in fs.get_bindings() {
+        let verdict = if used_binding.map_or(false, |used| std::ptr::eq(used, binding)) {
+            "used"
+        } else if canonical_guest_path.starts_with(binding.get_path(Side::Guest)) {
+            // Hidden by a more recent binding.
+            "shadowed"
+        } else {
+            "no match"
+        };
+        writeln!(
+            trace,
+            "  {}:{} [{}]",
+            binding.get_path(Side::Host).display(),
+            binding.get_path(Side::Guest).display(),
+            verdict
+        )?;
+    }
+    writeln!(trace, "Host path: {}", host_path.display())?;
+
+    Ok(trace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tests::get_test_rootfs_path;
+
+    #[test]
+    fn test_trace_path_through_binding() {
+        let rootfs_path = get_test_rootfs_path();
+        let mut fs = FileSystem::with_root(&rootfs_path).unwrap();
+        fs.add_binding("/etc", "/home").unwrap();
+        fs.add_binding("/tmp", "/tmp").unwrap();
+        fs.set_cwd("/tmp").unwrap();
+
+        assert_eq!(
+            trace_path(&fs, Path::new("../home/./passwd")).unwrap(),
+            format!(
+                "Guest path: ../home/./passwd\n\
+                 Canonical guest path: /home/passwd\n\
+                 Bindings:\n\
+                 \x20 /tmp:/tmp [no match]\n\
+                 \x20 /etc:/home [used]\n\
+                 \x20 {}:/ [shadowed]\n\
+                 Host path: /etc/passwd\n",
+                rootfs_path.display()
+            )
+        );
+
+        assert_eq!(
+            trace_path(&fs, Path::new("/home/passwd/file")).unwrap_err(),
+            Error::errno(ENOTDIR)
+        );
+    }
+}
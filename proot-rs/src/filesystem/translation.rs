@@ -1,4 +1,4 @@
-use crate::errors::Result;
+use crate::errors::{Result, WithContext};
 
 use crate::filesystem::binding::Side::{Guest, Host};
 use crate::filesystem::canonicalization::Canonicalizer;
@@ -19,6 +19,10 @@ pub trait Translator {
         guest_path: P,
         deref_final: bool,
     ) -> Result<(PathBuf, PathBuf)>;
+    fn translate_paths<P: AsRef<Path>>(
+        &self,
+        guest_paths: &[(P, bool)],
+    ) -> Result<Vec<(PathBuf, PathBuf)>>;
     fn detranslate_path<P: AsRef<Path>>(
         &self,
         host_path: P,
@@ -54,6 +58,10 @@ impl Translator for FileSystem {
     /// representing two paths. The `canonical_guest_path` is the path on the
     /// guest side and the `host_path` is the path on the host side. Both
     /// paths are canonical.
+    ///
+    /// Both are obtained in a single canonicalization, so the callers which
+    /// also need the guest path (e.g. to update the cwd) should keep it rather
+    /// than canonicalizing the path again.
     fn translate_absolute_path<P: AsRef<Path>>(
         &self,
         guest_path: P,
@@ -70,6 +78,33 @@ impl Translator for FileSystem {
         Ok((canonical_guest_path, host_path))
     }
 
+    /// Translates several paths from `guest` to `host` in one call, e.g. the
+    /// source and the destination of `rename()`. Each path comes with its own
+    /// `deref_final` flag, see [`Translator::translate_path()`].
+    ///
+    /// The results are only returned if all the paths have been translated,
+    /// so that a syscall is never left with partially translated arguments.
+    /// On error, the message tells which path (by its index) failed.
+    fn translate_paths<P: AsRef<Path>>(
+        &self,
+        guest_paths: &[(P, bool)],
+    ) -> Result<Vec<(PathBuf, PathBuf)>> {
+        guest_paths
+            .iter()
+            .enumerate()
+            .map(|(index, (guest_path, deref_final))| {
+                self.translate_path(guest_path, *deref_final)
+                    .with_context(|| {
+                        format!(
+                            "Failed to translate path #{} {:?}",
+                            index,
+                            guest_path.as_ref()
+                        )
+                    })
+            })
+            .collect()
+    }
+
     /// Translates a path from `host` to `guest`.
     ///
     /// `path` must canonicalized;
@@ -95,19 +130,16 @@ impl Translator for FileSystem {
 
         // Is it a symlink?
         if let Some(referrer_path) = referrer {
-            follow_binding = false;
+            // Some links in "/proc" are generated dynamically by the kernel
+            // (e.g. "/proc/<pid>/fd/N"), from the host path of the file, so
+            // bindings are resolved as for any host path.
+            follow_binding = referrer_path.starts_with("/proc");
 
             // In some cases bindings have to be resolved.
-            if referrer_path.starts_with("/proc") {
-                // Some links in "/proc" are generated dynamically by the kernel.
-                // PRoot has to emulate some of them.
-                //TODO: readlink_proc2
-                unimplemented!(" /proc/.. referrer paths not supported!");
-            } else if !self.belongs_to_guestfs(referrer_path) {
+            if !follow_binding && !self.belongs_to_guestfs(referrer_path) {
                 let maybe_binding_referree = self.get_first_appropriate_binding(host_path, Host);
-                let binding_referrer = self
-                    .get_first_appropriate_binding(referrer_path, Host)
-                    .unwrap();
+                let maybe_binding_referrer =
+                    self.get_first_appropriate_binding(referrer_path, Host);
 
                 // Resolve bindings for symlinks that belong
                 // to a binding and point to the same binding.
@@ -117,7 +149,9 @@ impl Translator for FileSystem {
                 // should appear as "/foo/a -> /foo/b" in the
                 // guest rootfs namespace for consistency
                 // reasons.
-                if let Some(binding_referree) = maybe_binding_referree {
+                if let (Some(binding_referree), Some(binding_referrer)) =
+                    (maybe_binding_referree, maybe_binding_referrer)
+                {
                     follow_binding =
                         binding_referree.get_path(Host) == binding_referrer.get_path(Host);
                 }
@@ -203,6 +237,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_translate_path_guest_and_host_consistent() {
+        let rootfs_path = get_test_rootfs_path();
+        let mut fs = FileSystem::with_root(&rootfs_path).unwrap();
+        fs.add_binding("/etc", "/home").unwrap();
+
+        for (guest_path, deref_final) in &[
+            ("/etc/../bin/./sleep", true),
+            ("/home/passwd", false),
+            ("/home/../home/", true),
+            ("/tmp/impossible", false),
+        ] {
+            let (canonical_guest_path, host_path) =
+                fs.translate_path(guest_path, *deref_final).unwrap();
+            // The guest path is the canonical one...
+            assert_eq!(
+                canonical_guest_path,
+                fs.canonicalize(Path::new(guest_path), *deref_final)
+                    .unwrap()
+            );
+            // ...and the host path is where it is substituted.
+            let substituted = fs.substitute(&canonical_guest_path, Guest).unwrap();
+            let mut expected_host_path = substituted.clone();
+            if guest_path.ends_with('/') {
+                expected_host_path.try_add_trailing_slash();
+            }
+            assert_eq!(host_path, expected_host_path);
+            // Translating the canonical guest path again gives the same result.
+            assert_eq!(
+                fs.translate_path(&canonical_guest_path, *deref_final),
+                Ok((canonical_guest_path, substituted))
+            );
+        }
+    }
+
+    #[test]
+    fn test_translate_paths() {
+        let rootfs_path = get_test_rootfs_path();
+        let mut fs = FileSystem::with_root(&rootfs_path).unwrap();
+        fs.add_binding("/etc", "/home").unwrap();
+
+        // source and destination of a `rename()`
+        assert_eq!(
+            fs.translate_paths(&[("/home/passwd", false), ("/tmp/../tmp/passwd", false)]),
+            Ok(vec![
+                (PathBuf::from("/home/passwd"), PathBuf::from("/etc/passwd")),
+                (PathBuf::from("/tmp/passwd"), rootfs_path.join("tmp/passwd")),
+            ])
+        );
+
+        // the error tells which path failed
+        let error = fs
+            .translate_paths(&[("/home/passwd", false), ("/home/passwd/impossible", false)])
+            .unwrap_err();
+        assert_eq!(error.get_errno(), nix::errno::Errno::ENOTDIR);
+        assert!(error.to_string().contains("path #1"));
+    }
+
     #[test]
     fn test_detranslate_path_root() {
         let rootfs_path = PathBuf::from(get_test_rootfs_path());
@@ -242,6 +334,11 @@ mod tests {
             fs.detranslate_path(&Path::new("/etc/passwd"), None),
             Ok(Some(PathBuf::from("/tmp/passwd")))
         );
+        // the magic links of "/proc" point to host paths
+        assert_eq!(
+            fs.detranslate_path(&Path::new("/etc/passwd"), Some(Path::new("/proc/1/fd/3"))),
+            Ok(Some(PathBuf::from("/tmp/passwd")))
+        );
     }
 
     #[test]
@@ -258,4 +355,59 @@ mod tests {
 
         //TODO: detranslate symlink tests
     }
+
+    #[test]
+    fn test_detranslate_path_exact_binding() {
+        let rootfs_path = get_test_rootfs_path();
+
+        // "${rootfs}" on the host, "/" on the guest
+        let mut fs = FileSystem::with_root(&rootfs_path).unwrap();
+        fs.add_binding("/etc", "/tmp").unwrap();
+        fs.add_binding(rootfs_path.join("bin"), "/home").unwrap();
+
+        // The host path of a binding itself, not a path under it.
+        assert_eq!(
+            fs.detranslate_path("/etc", None),
+            Ok(Some(PathBuf::from("/tmp")))
+        );
+        assert_eq!(
+            fs.detranslate_path(rootfs_path.join("bin"), None),
+            Ok(Some(PathBuf::from("/home")))
+        );
+        // Which is the reverse of the translation of the guest path.
+        assert_eq!(
+            fs.translate_path("/tmp", true),
+            Ok((PathBuf::from("/tmp"), PathBuf::from("/etc")))
+        );
+
+        // The prefixes are compared by components, not by characters.
+        assert_eq!(fs.detranslate_path("/etcetera", None), Ok(None));
+        assert_eq!(
+            fs.detranslate_path(rootfs_path.join("binary"), None),
+            Ok(Some(PathBuf::from("/binary")))
+        );
+    }
+
+    #[test]
+    fn test_detranslate_path_binding_inside_rootfs() {
+        let rootfs_path = get_test_rootfs_path();
+
+        // "${rootfs}" on the host, "/" on the guest
+        let mut fs = FileSystem::with_root(&rootfs_path).unwrap();
+
+        // "${rootfs}/etc" on the host, "/home" on the guest
+        fs.add_binding(rootfs_path.join("etc"), "/home").unwrap();
+
+        // "${rootfs}/etc/passwd" -> "/home/passwd"
+        assert_eq!(
+            fs.detranslate_path(rootfs_path.join("etc/passwd"), None),
+            Ok(Some(PathBuf::from("/home/passwd")))
+        );
+
+        // "${rootfs}/bin/sleep" -> "/bin/sleep", the root binding still applies
+        assert_eq!(
+            fs.detranslate_path(rootfs_path.join("bin/sleep"), None),
+            Ok(Some(PathBuf::from("/bin/sleep")))
+        );
+    }
 }
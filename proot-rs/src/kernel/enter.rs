@@ -13,6 +13,9 @@ use crate::register::Original;
 pub fn translate(info_bag: &InfoBag, tracee: &mut Tracee) -> Result<()> {
     let sys_num = tracee.regs.get_sys_num(Original);
     let sys_type = syscall_group_from_sysnum(sys_num);
+    if info_bag.chroot_only && sys_type.translates_paths() {
+        return Ok(());
+    }
 
     match sys_type {
         Accept => accept::enter(),
@@ -20,29 +23,41 @@ pub fn translate(info_bag: &InfoBag, tracee: &mut Tracee) -> Result<()> {
         Brk => brk::enter(),
         Chdir => chdir::enter(tracee),
         ChmodAccessMkNodAt => chmod_access_mknod_at::enter(tracee),
+        Denied => denied::enter(tracee, info_bag),
         DirLinkAttr => dir_link_attr::enter(tracee),
-        Execve => execve::enter(tracee, &info_bag.loader),
+        Execve => execve::enter(tracee, info_bag),
         GetCwd => getcwd::enter(tracee),
         GetSockOrPeerName => get_sockorpeer_name::enter(),
         InotifyAddWatch => inotify_add_watch::enter(),
         Link => link_rename::enter(tracee),
         LinkAt => link_at::enter(tracee),
-        Mount => mount::enter(),
+        LookupDcookie => lookup_dcookie::enter(tracee),
+        Memory => memory::enter(tracee, info_bag),
+        MessageQueue => mq::enter(tracee, info_bag),
+        Mount => mount::enter(tracee),
         Open => open::enter(tracee),
         OpenAt => open_at::enter(tracee),
-        PivotRoot => pivot_root::enter(),
+        PivotRoot => pivot_root::enter(tracee),
+        ProcessVm => process_vm::enter(tracee),
         Ptrace => ptrace::enter(),
         ReadLink => dir_link_attr::enter(tracee),
-        ReadLinkAt => unlink_mkdir_at::enter(tracee),
+        ReadLinkAt => readlink_at::enter(tracee),
         Rename => link_rename::enter(tracee),
         RenameAt => rename_at::enter(tracee),
-        SocketCall => socketcall::enter(),
+        SchedAffinity => sched_affinity::enter(tracee, info_bag),
+        SendRecvMsg => send_recv_msg::enter(tracee),
+        SetHostname => sethostname::enter(tracee),
+        SocketCall => socketcall::enter(tracee, info_bag),
+        SocketFamily => socket_family::enter(tracee, info_bag),
         StandardSyscall => standard_syscall::enter(tracee),
         StatAt => stat_at::enter(tracee),
+        Swap => swap::enter(tracee),
+        Sync => sync::enter(tracee, info_bag),
         SymLink => sym_link::enter(tracee),
         SymLinkAt => sym_link_at::enter(tracee),
         Wait => wait::enter(),
         UnlinkMkdirAt => unlink_mkdir_at::enter(tracee),
+        UnhandledPath => unhandled_path::enter(tracee, info_bag),
         _ => Ok(()),
     }
 }
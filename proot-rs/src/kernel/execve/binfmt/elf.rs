@@ -42,6 +42,13 @@ pub enum ExecutableClass {
     Class64 = 2,
 }
 
+/// The class of the executables which can be started by the loader of
+/// proot-rs, which is only built for the architecture of proot-rs itself.
+#[cfg(target_pointer_width = "32")]
+pub const NATIVE_CLASS: ExecutableClass = ExecutableClass::Class32;
+#[cfg(target_pointer_width = "64")]
+pub const NATIVE_CLASS: ExecutableClass = ExecutableClass::Class64;
+
 #[repr(C)]
 #[derive(Debug, PartialEq)]
 pub struct ProgramHeader32 {
@@ -259,6 +266,25 @@ pub(super) fn load_elf(fs: &FileSystem, parameters: &mut ExecveParameters) -> Re
     let mut load_info = LoadInfo::from(fs, &parameters.host_path)
         .with_context(|| format!("Failed to parse elf file: {:?}", parameters.host_path))?;
 
+    // Only one loader is bundled, and the syscalls of the tracees are always
+    // decoded with the native ABI: the executables of the other class (e.g. the
+    // 32-bit ones on x86_64) would crash in the loader, so they are rejected as
+    // the kernel does for an unsupported binary format.
+    let classes = std::iter::once(&load_info)
+        .chain(load_info.interp.as_deref())
+        .map(|load_info| load_info.elf_header.get_class());
+    for class in classes {
+        if class != NATIVE_CLASS {
+            return Err(Error::errno_with_msg(
+                ENOEXEC,
+                format!(
+                    "{:?} executables are not supported by this build of proot-rs, only {:?}",
+                    class, NATIVE_CLASS
+                ),
+            ));
+        }
+    }
+
     load_info.raw_path = Some(parameters.raw_guest_path.clone());
     load_info.user_path = Some(parameters.canonical_guest_path.clone());
     load_info.host_path = Some(parameters.host_path.clone());
@@ -282,6 +308,9 @@ pub(super) fn load_elf(fs: &FileSystem, parameters: &mut ExecveParameters) -> Re
 mod tests {
     use super::*;
     use crate::errors::Error;
+    use crate::filesystem::temp::TempFile;
+    use crate::utils::tests::get_test_rootfs_path;
+    use std::io::Write;
     use std::path::PathBuf;
 
     #[test]
@@ -310,5 +339,35 @@ mod tests {
         );
         assert!(apply!(elf_header, |header| header.is_exec_or_dyn()).is_ok());
         assert!(apply!(elf_header, |header| header.is_known_phentsize()).is_ok());
+        assert_eq!(elf_header.get_class(), NATIVE_CLASS);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_load_elf_foreign_class() {
+        use crate::utils::tests::x86_executable;
+
+        // A minimal 32-bit executable, with no program headers.
+        let elf = x86_executable(0x0804_8000);
+
+        let file = TempFile::new("prefix_test_load_elf_foreign_class");
+        file.create_file().unwrap().write_all(&elf).unwrap();
+
+        let mut parameters = ExecveParameters {
+            raw_guest_path: PathBuf::from("/bin/foreign"),
+            canonical_guest_path: PathBuf::from("/bin/foreign"),
+            host_path: file.path.clone(),
+            argv: vec![],
+        };
+        assert_eq!(
+            load_elf(&FileSystem::new(), &mut parameters).err(),
+            Some(Error::errno(ENOEXEC))
+        );
+
+        // The native executables are still loaded.
+        let rootfs_path = get_test_rootfs_path();
+        let fs = FileSystem::with_root(&rootfs_path).unwrap();
+        parameters.host_path = rootfs_path.join("bin/sleep");
+        assert!(load_elf(&fs, &mut parameters).is_ok());
     }
 }
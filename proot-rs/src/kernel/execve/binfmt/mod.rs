@@ -25,10 +25,16 @@ enum LoadResult {
 }
 
 /// To avoid infinite loops when parsing the interpreter, we need to set a limit
-/// on the amount of rewriting we can do to the interpreter.
+/// on the amount of rewriting we can do to the interpreter. It can be raised
+/// with the `--max-interp-depth` option, up to
+/// `MAX_INTERPRETER_REWRITE_LIMIT`.
 ///
 /// In the Linux kernel, this value is 4. https://elixir.bootlin.com/linux/v5.14-rc3/source/fs/exec.c#L1745
-const INTERPRETER_REWRITE_LIMIT: usize = 4;
+pub const INTERPRETER_REWRITE_LIMIT: usize = 4;
+
+/// The highest value accepted by `--max-interp-depth`, so that a loop of
+/// scripts still fails quickly.
+pub const MAX_INTERPRETER_REWRITE_LIMIT: usize = 16;
 
 /// This function designed to solve the problem of loading different types
 /// executable files. To load an executable, the external module should call
@@ -37,12 +43,19 @@ const INTERPRETER_REWRITE_LIMIT: usize = 4;
 /// If an executable is loaded successfully, a `LoadInfo` will be returned for
 /// further execution. Note that `parameters` may be modified by loader
 /// functions.
-pub(super) fn load(fs: &FileSystem, parameters: &mut ExecveParameters) -> Result<LoadInfo> {
+///
+/// `rewrite_limit` is the number of interpreters which can be followed, see
+/// `INTERPRETER_REWRITE_LIMIT`.
+pub(super) fn load(
+    fs: &FileSystem,
+    parameters: &mut ExecveParameters,
+    rewrite_limit: usize,
+) -> Result<LoadInfo> {
     const LOADERS: [fn(&FileSystem, &mut ExecveParameters) -> Result<LoadResult>; 2] =
         [shebang::load_script, elf::load_elf];
 
     // Limit the number of loads to avoid infinite loops of the interpreter
-    for _ in 0..(INTERPRETER_REWRITE_LIMIT + 1) {
+    for _ in 0..(rewrite_limit + 1) {
         let mut restart = false;
         let mut last_error = None;
         // Iterate through each load function
@@ -105,3 +118,53 @@ pub(super) fn load(fs: &FileSystem, parameters: &mut ExecveParameters) -> Result
         "failed to load executable file, max interpreter rewrite limit exceeded",
     ));
 }
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::filesystem::temp::TempDir;
+    use crate::kernel::execve::params::Arg;
+    use crate::utils::tests::get_test_rootfs_path;
+
+    #[test]
+    fn test_load_interpreter_chain() {
+        // `/tmp/script1` is run by `/tmp/script2`, ..., and `/tmp/script5` by
+        // `/bin/sh`: 5 interpreters.
+        let dir = TempDir::new("prefix_test_load_interpreter_chain");
+        dir.create_dir().unwrap();
+        for i in 1..=5 {
+            let interpreter = match i {
+                5 => "/bin/sh".to_string(),
+                i => format!("/tmp/script{}", i + 1),
+            };
+            let path = dir.path.join(format!("script{}", i));
+            fs::write(&path, format!("#!{}\n", interpreter)).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        let mut fs = FileSystem::with_root(get_test_rootfs_path()).unwrap();
+        fs.add_binding(&dir.path, "/tmp").unwrap();
+
+        let load_chain = |rewrite_limit| {
+            let mut parameters = ExecveParameters {
+                raw_guest_path: PathBuf::from("/tmp/script1"),
+                canonical_guest_path: Default::default(),
+                host_path: Default::default(),
+                argv: vec![Arg::CStringInSelf(CString::new("script1").unwrap())],
+            };
+            load(&fs, &mut parameters, rewrite_limit).map(|_| parameters.argv.len())
+        };
+
+        assert_eq!(
+            load_chain(INTERPRETER_REWRITE_LIMIT).unwrap_err(),
+            Error::errno(ELOOP)
+        );
+        // `/bin/sh /tmp/script5 /tmp/script4 ... /tmp/script1`
+        assert_eq!(load_chain(5), Ok(6));
+        assert_eq!(load_chain(MAX_INTERPRETER_REWRITE_LIMIT), Ok(6));
+    }
+}
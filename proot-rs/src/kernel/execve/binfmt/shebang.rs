@@ -1,12 +1,13 @@
 use crate::errors::*;
 use crate::errors::{Error, Result};
 use crate::filesystem::FileSystem;
+use crate::kernel::execve::open_executable;
 use crate::kernel::execve::params::{Arg, ExecveParameters};
 use std::ffi::CString;
 use std::io::ErrorKind;
+use std::io::Read;
 use std::os::unix::prelude::OsStrExt;
 use std::path::{Path, PathBuf};
-use std::{fs::File, io::Read};
 
 use super::LoadResult;
 
@@ -37,8 +38,14 @@ const BINPRM_BUF_SIZE: usize = 256;
 ///  - Append `interpreter` and `optional-arg`(if exists) to the front of argv.
 ///  - Replace the path of the executable(`parameters.raw_guest_path`) with the
 ///    path of the `interpreter`.
+///
+/// As with the kernel, a relative `interpreter` (e.g. `#!bin/python`) is
+/// relative to the cwd of the tracee, not to the directory of the script. It
+/// is kept as is in argv, but `parameters.raw_guest_path` is made absolute
+/// with the guest cwd, so that it is translated through the bindings like any
+/// other guest path.
 pub(super) fn load_script(
-    _fs: &FileSystem,
+    fs: &FileSystem,
     parameters: &mut ExecveParameters,
 ) -> Result<LoadResult> {
     // Extract shebang from script file
@@ -67,7 +74,11 @@ pub(super) fn load_script(
     );
 
     // reset raw_guest_path to the new interpreter.
-    parameters.raw_guest_path = extract_result.interpreter;
+    parameters.raw_guest_path = if extract_result.interpreter.is_relative() {
+        fs.get_cwd().join(&extract_result.interpreter)
+    } else {
+        extract_result.interpreter
+    };
 
     return Ok(LoadResult::RestartWithNewParameters);
 }
@@ -85,7 +96,7 @@ pub(super) fn load_script(
 /// `optional-arg`. Note that optional-arg is treated as one argument and not as
 /// multiple arguments.
 fn extract(host_path: &Path) -> Result<ExtractResult> {
-    let mut file = File::open(host_path)?;
+    let mut file = open_executable(host_path)?;
     let mut buffer = [0u8; BINPRM_BUF_SIZE];
 
     // Read bytes from the beginning of the file, allowing the file length to be
@@ -194,7 +205,10 @@ fn extract(host_path: &Path) -> Result<ExtractResult> {
 
 #[cfg(test)]
 mod tests {
+    use std::fs;
+
     use super::*;
+    use crate::filesystem::temp::TempDir;
     use crate::utils::tests::get_test_rootfs_path;
 
     #[test]
@@ -207,4 +221,38 @@ mod tests {
             extract(&rootfs_path.join("bin/sleep"))
         );
     }
+
+    #[test]
+    fn test_load_script_relative_interpreter() {
+        let rootfs = TempDir::new("prefix_test_load_script_relative_interpreter");
+        rootfs.create_dir().unwrap();
+        fs::create_dir_all(rootfs.path.join("usr/lib")).unwrap();
+        fs::write(rootfs.path.join("usr/lib/script"), "#!bin/interp -x\n").unwrap();
+        let mut file_system = FileSystem::with_root(&rootfs.path).unwrap();
+        file_system.set_cwd("/usr").unwrap();
+
+        let mut parameters = ExecveParameters {
+            raw_guest_path: PathBuf::from("lib/script"),
+            canonical_guest_path: Default::default(),
+            host_path: Default::default(),
+            argv: vec![Arg::CStringInSelf(CString::new("lib/script").unwrap())],
+        };
+        parameters.update_path(&file_system).unwrap();
+        assert!(matches!(
+            load_script(&file_system, &mut parameters),
+            Ok(LoadResult::RestartWithNewParameters)
+        ));
+
+        // Relative to the cwd, not to the directory of the script.
+        assert_eq!(parameters.raw_guest_path, PathBuf::from("/usr/bin/interp"));
+        let argv: Vec<&[u8]> = parameters
+            .argv
+            .iter()
+            .map(|arg| match arg {
+                Arg::CStringInSelf(arg) => arg.as_bytes(),
+                Arg::CStringInTracee(_) => unreachable!(),
+            })
+            .collect();
+        assert_eq!(argv, vec![&b"bin/interp"[..], b"-x", b"lib/script"]);
+    }
 }
@@ -0,0 +1,181 @@
+use std::fmt::Write;
+use std::io::{Seek, SeekFrom};
+use std::path::Path;
+
+use nix::sys::mman::{MapFlags, ProtFlags};
+
+use crate::errors::*;
+use crate::filesystem::readers::ExtraReader;
+use crate::filesystem::{FileSystem, Translator};
+use crate::kernel::execve::binfmt::elf::{
+    ElfHeader, ExecutableClass, ProgramHeader, PF_R, PF_W, PF_X, PT_DYNAMIC, PT_GNU_STACK,
+    PT_INTERP, PT_LOAD,
+};
+use crate::kernel::execve::load_info::LoadInfo;
+use crate::kernel::execve::open_executable;
+use crate::register::Word;
+
+/// Returns a description of the ELF executable at `guest_path`, for the
+/// `--dump-elf` option: its ELF header, its program headers, its interpreter,
+/// and the mappings which the loader would create for it and its interpreter
+/// (with the final addresses of the position independent executables).
+///
+/// Nothing is executed, and the scripts are not followed to their
+/// interpreter.
+pub fn dump_elf(fs: &FileSystem, guest_path: &Path) -> Result<String> {
+    let host_path = fs.translate_path(guest_path, true)?.1;
+    let mut dump = String::new();
+
+    let mut file = open_executable(&host_path)?;
+    let (elf_header, file) = ElfHeader::extract_from(&mut file)?;
+    let class = elf_header.get_class();
+    let e_type = get!(elf_header, e_type)?;
+    let e_machine = get!(elf_header, e_machine)?;
+    let e_entry = get!(elf_header, e_entry, Word)?;
+    let e_phoff = get!(elf_header, e_phoff, u64)?;
+    let e_phnum = get!(elf_header, e_phnum)?;
+    writeln!(dump, "ELF header:")?;
+    writeln!(dump, "  class: {:?}", class)?;
+    writeln!(dump, "  type: {}", e_type)?;
+    writeln!(dump, "  machine: {}", e_machine)?;
+    writeln!(dump, "  entry: 0x{:x}", e_entry)?;
+    writeln!(dump, "  program headers: {} at 0x{:x}", e_phnum, e_phoff)?;
+
+    writeln!(dump, "Program headers:")?;
+    file.seek(SeekFrom::Start(e_phoff))?;
+    for _ in 0..e_phnum {
+        let program_header = match class {
+            ExecutableClass::Class32 => ProgramHeader::ProgramHeader32(file.read_struct()?),
+            ExecutableClass::Class64 => ProgramHeader::ProgramHeader64(file.read_struct()?),
+        };
+        let p_type = get!(program_header, p_type)?;
+        let p_flags = get!(program_header, p_flags)?;
+        let type_name = match p_type {
+            PT_LOAD => "LOAD".to_string(),
+            PT_DYNAMIC => "DYNAMIC".to_string(),
+            PT_INTERP => "INTERP".to_string(),
+            PT_GNU_STACK => "GNU_STACK".to_string(),
+            p_type => format!("0x{:x}", p_type),
+        };
+        writeln!(
+            dump,
+            "  {:<10} {} vaddr 0x{:x} filesz 0x{:x} memsz 0x{:x}",
+            type_name,
+            rwx(
+                p_flags & PF_R != 0,
+                p_flags & PF_W != 0,
+                p_flags & PF_X != 0
+            ),
+            get!(program_header, p_vaddr, Word)?,
+            get!(program_header, p_filesz, Word)?,
+            get!(program_header, p_memsz, Word)?,
+        )?;
+    }
+
+    let mut load_info = LoadInfo::from(fs, &host_path)?;
+    load_info.compute_load_addresses(false)?;
+    match load_info.interp {
+        Some(ref interp) => writeln!(
+            dump,
+            "Interpreter: {}",
+            interp.user_path.as_ref().unwrap().display()
+        )?,
+        None => writeln!(dump, "Interpreter: none")?,
+    }
+    writeln!(dump, "Mappings:")?;
+    write_mappings(&mut dump, &load_info)?;
+    if let Some(ref interp) = load_info.interp {
+        writeln!(dump, "Mappings of the interpreter:")?;
+        write_mappings(&mut dump, interp)?;
+    }
+
+    Ok(dump)
+}
+
+fn write_mappings(dump: &mut String, load_info: &LoadInfo) -> Result<()> {
+    for mapping in &load_info.mappings {
+        write!(
+            dump,
+            "  0x{:x}-0x{:x} {}",
+            mapping.addr,
+            mapping.addr + mapping.length,
+            rwx(
+                mapping.prot.contains(ProtFlags::PROT_READ),
+                mapping.prot.contains(ProtFlags::PROT_WRITE),
+                mapping.prot.contains(ProtFlags::PROT_EXEC),
+            ),
+        )?;
+        if mapping.flags.contains(MapFlags::MAP_ANONYMOUS) {
+            write!(dump, " anonymous")?;
+        } else {
+            write!(dump, " offset 0x{:x}", mapping.offset)?;
+        }
+        if mapping.clear_length != 0 {
+            write!(dump, " clear 0x{:x}", mapping.clear_length)?;
+        }
+        writeln!(dump)?;
+    }
+    Ok(())
+}
+
+/// Returns the permissions in the format of `/proc/<pid>/maps`, e.g. `r-x`.
+fn rwx(read: bool, write: bool, exec: bool) -> String {
+    [(read, 'r'), (write, 'w'), (exec, 'x')]
+        .iter()
+        .map(|&(allowed, c)| if allowed { c } else { '-' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::filesystem::temp::TempDir;
+    use crate::utils::tests::get_test_rootfs_path;
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_dump_elf_known_executable() {
+        use crate::utils::tests::x86_64_executable;
+
+        // A single segment whose end is not in the file (e.g. the .bss).
+        let elf = x86_64_executable(0x40_1000, &[(0x40_0000, 0x1000, 0x2000)]);
+
+        let rootfs = TempDir::new("prefix_test_dump_elf_known_executable");
+        rootfs.create_dir().unwrap();
+        fs::write(rootfs.path.join("known"), &elf).unwrap();
+        let fs = FileSystem::with_root(&rootfs.path).unwrap();
+
+        assert_eq!(
+            dump_elf(&fs, Path::new("/known")).unwrap(),
+            "ELF header:\n\
+             \x20 class: Class64\n\
+             \x20 type: 2\n\
+             \x20 machine: 62\n\
+             \x20 entry: 0x401000\n\
+             \x20 program headers: 1 at 0x40\n\
+             Program headers:\n\
+             \x20 LOAD       r-x vaddr 0x400000 filesz 0x1000 memsz 0x2000\n\
+             Interpreter: none\n\
+             Mappings:\n\
+             \x20 0x400000-0x402000 r-x offset 0x0 clear 0x1000\n\
+             \x20 0x402000-0x403000 r-x anonymous\n"
+        );
+    }
+
+    #[test]
+    fn test_dump_elf_dynamic_executable() {
+        let fs = FileSystem::with_root(get_test_rootfs_path()).unwrap();
+        let dump = dump_elf(&fs, &PathBuf::from("/bin/sleep")).unwrap();
+        assert!(dump.contains("  INTERP "), "{}", dump);
+        assert!(!dump.contains("Interpreter: none"), "{}", dump);
+        assert!(dump.contains("Mappings of the interpreter:\n"), "{}", dump);
+
+        assert_eq!(
+            dump_elf(&fs, Path::new("/etc/passwd")).unwrap_err(),
+            Error::errno(ENOEXEC)
+        );
+    }
+}
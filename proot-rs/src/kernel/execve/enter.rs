@@ -9,7 +9,13 @@ use crate::process::tracee::Tracee;
 use crate::register::PtraceWriter;
 use crate::register::{Current, PtraceReader, SysArg, SysArg1, SysArg2};
 
-pub fn translate(tracee: &mut Tracee, loader: &dyn LoaderFile) -> Result<()> {
+/// `max_interp_depth` is the number of interpreters (e.g. of scripts) which
+/// can be followed, see `--max-interp-depth`.
+pub fn translate(
+    tracee: &mut Tracee,
+    loader: &dyn LoaderFile,
+    max_interp_depth: usize,
+) -> Result<()> {
     //TODO: implement this part for ptrace translation
     //	if (IS_NOTIFICATION_PTRACED_LOAD_DONE(tracee)) {
     //		/* Syscalls can now be reported to its ptracer.  */
@@ -41,8 +47,11 @@ pub fn translate(tracee: &mut Tracee, loader: &dyn LoaderFile) -> Result<()> {
     };
 
     // Try to parse and load this executable
-    let load_info = binfmt::load(&tracee.fs.borrow(), &mut parameters)
+    let mut load_info = binfmt::load(&tracee.fs.borrow(), &mut parameters, max_interp_depth)
         .with_context(|| format!("failed to load file {:?}", raw_guest_path))?;
+    // As for the kernel, `AT_EXECFN` is the path given to `execve()`, even if
+    // it is a script run by an interpreter.
+    load_info.raw_path = Some(raw_guest_path);
 
     tracee.new_exe = Some(Rc::new(RefCell::new(parameters.host_path)));
     tracee.load_info = Some(load_info);
@@ -105,7 +114,10 @@ mod tests {
 
                     // if the file executed by execve exists, we expect the translation to go well.
                     if file_exists {
-                        assert_eq!(Ok(()), translate(tracee, &info_bag.loader));
+                        assert_eq!(
+                            Ok(()),
+                            translate(tracee, &info_bag.loader, info_bag.max_interp_depth)
+                        );
                         at_least_one_translation_occured = true;
                     }
                     false
@@ -43,7 +43,10 @@ pub fn transfert_load_script(tracee: &mut Tracee) -> Result<()> {
     let stack_pointer = tracee.regs.get(Current, StackPointer) as usize;
 
     let load_info = tracee.load_info.as_ref().unwrap();
-    // collect strings
+    // collect strings: the guest paths of the executable and its interpreter,
+    // which are opened by the loader through the path translation, and the
+    // guest path of `AT_EXECFN`, which the loader copies before starting the
+    // program.
     let string1_bytes = load_info.user_path.as_ref().unwrap().as_os_str().as_bytes();
     let string1_size = string1_bytes.len() + 1;
     let string2_bytes = load_info
@@ -207,3 +210,215 @@ pub fn transfert_load_script(tracee: &mut Tracee) -> Result<()> {
     tracee.regs.set_restore_original_regs(false);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+    use std::mem::size_of;
+    use std::os::unix::io::RawFd;
+    use std::path::Path;
+    use std::time::Duration;
+
+    use libc::pid_t;
+    use nix::fcntl::{open, OFlag};
+    use nix::sys::signal::{kill, Signal};
+    use nix::sys::stat::Mode;
+    use nix::sys::wait::waitpid;
+    use nix::unistd::{dup2, dup3, execv, fork, ForkResult, Pid};
+
+    use super::*;
+    use crate::filesystem::binding::Side;
+    use crate::register::Original;
+    use crate::utils::tests::{test_with_proot, test_with_proot_and_fs};
+
+    /// Reads `length` bytes at `address` in the process `pid`.
+    fn read_remote(pid: pid_t, address: usize, length: usize) -> Vec<u8> {
+        let mut buffer = vec![0u8; length];
+        let local = libc::iovec {
+            iov_base: buffer.as_mut_ptr() as *mut c_void,
+            iov_len: length,
+        };
+        let remote = libc::iovec {
+            iov_base: address as *mut c_void,
+            iov_len: length,
+        };
+        let count = unsafe { libc::process_vm_readv(pid, &local, 1, &remote, 1, 0) };
+        assert_eq!(count, length as isize);
+        buffer
+    }
+
+    fn read_remote_word(pid: pid_t, address: usize) -> usize {
+        let mut word = [0u8; size_of::<usize>()];
+        word.copy_from_slice(&read_remote(pid, address, size_of::<usize>()));
+        usize::from_ne_bytes(word)
+    }
+
+    /// Runs `sleep 5` from `raw_path` in a new process, and waits until the
+    /// loader has started it.
+    fn spawn_loaded_sleep(raw_path: &str) -> Pid {
+        let child = match unsafe { fork() }.unwrap() {
+            ForkResult::Child => {
+                execv(
+                    &CString::new(raw_path).unwrap(),
+                    &[CString::new("sleep").unwrap(), CString::new("5").unwrap()],
+                )
+                .unwrap();
+                unreachable!()
+            }
+            ForkResult::Parent { child } => child,
+        };
+
+        // The loader names the process after `AT_EXECFN` once the
+        // auxiliary vector is updated.
+        let comm_path = format!("/proc/{}/comm", child);
+        for _ in 0..500 {
+            if std::fs::read_to_string(&comm_path).unwrap() == "sleep\n" {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(std::fs::read_to_string(&comm_path).unwrap(), "sleep\n");
+        child
+    }
+
+    /// Reads the auxiliary vector from the initial stack of `pid`, which
+    /// contains argc, argv[], envp[] and auxv[].
+    fn read_remote_auxv(pid: pid_t) -> Vec<(usize, usize)> {
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap();
+        let fields: Vec<&str> = stat[stat.rfind(')').unwrap() + 2..].split(' ').collect();
+        let start_stack: usize = fields[25].parse().unwrap();
+        let word = size_of::<usize>();
+        let argc = read_remote_word(pid, start_stack);
+        let mut cursor = start_stack + (argc + 2) * word;
+        while read_remote_word(pid, cursor) != 0 {
+            cursor += word;
+        }
+        cursor += word;
+        let mut auxv = vec![];
+        loop {
+            let key = read_remote_word(pid, cursor);
+            if key == libc::AT_NULL as usize {
+                break;
+            }
+            auxv.push((key, read_remote_word(pid, cursor + word)));
+            cursor += 2 * word;
+        }
+        auxv
+    }
+
+    fn get_auxv_entry(auxv: &[(usize, usize)], key: libc::c_ulong) -> Option<usize> {
+        auxv.iter()
+            .find(|(entry_key, _)| *entry_key == key as usize)
+            .map(|(_, value)| *value)
+    }
+
+    /// `AT_EXECFN` of a dynamically linked program (the loader also maps its
+    /// interpreter) is the guest path given to `execve()`.
+    #[test]
+    fn test_execve_at_execfn_is_guest_path() {
+        test_with_proot_and_fs(
+            |fs| fs.add_binding("/proc", "/proc").unwrap(),
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let raw_path = "/bin/../bin/sleep";
+                let child = spawn_loaded_sleep(raw_path);
+                let pid = child.as_raw();
+                let auxv = read_remote_auxv(pid);
+                let at_execfn = get_auxv_entry(&auxv, libc::AT_EXECFN);
+                let execfn = read_remote(pid, at_execfn.unwrap(), raw_path.len() + 1);
+
+                kill(child, Signal::SIGKILL).unwrap();
+                waitpid(child, None).unwrap();
+                assert_eq!(&execfn[..raw_path.len()], raw_path.as_bytes());
+                assert_eq!(execfn[raw_path.len()], 0);
+            },
+        )
+    }
+
+    /// Not defined by the libc crate for all the targets.
+    const AT_SYSINFO_EHDR: libc::c_ulong = 33;
+
+    /// The loader leaves `AT_RANDOM` (the seed of the stack protector of the C
+    /// library) and `AT_SYSINFO_EHDR` (the vDSO, used e.g. by
+    /// `clock_gettime()`) as set by the kernel for the loader: the mappings
+    /// they point to are still there once the program is started.
+    #[test]
+    fn test_execve_auxv_random_and_vdso_preserved() {
+        test_with_proot_and_fs(
+            |fs| fs.add_binding("/proc", "/proc").unwrap(),
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let child = spawn_loaded_sleep("/bin/sleep");
+                let pid = child.as_raw();
+                let auxv = read_remote_auxv(pid);
+                let maps = std::fs::read_to_string(format!("/proc/{}/maps", pid)).unwrap();
+                let mapping_of = |name: &str| {
+                    maps.lines().find(|line| line.ends_with(name)).map(|line| {
+                        let range = line.split(' ').next().unwrap();
+                        let (start, end) = range.split_at(range.find('-').unwrap());
+                        (
+                            usize::from_str_radix(start, 16).unwrap(),
+                            usize::from_str_radix(&end[1..], 16).unwrap(),
+                        )
+                    })
+                };
+                let at_random = get_auxv_entry(&auxv, libc::AT_RANDOM);
+                let at_sysinfo_ehdr = get_auxv_entry(&auxv, AT_SYSINFO_EHDR);
+                let stack = mapping_of("[stack]").unwrap();
+                let vdso = mapping_of("[vdso]");
+                let random_bytes = read_remote(pid, at_random.unwrap(), 16);
+                let vdso_magic = at_sysinfo_ehdr.map(|address| read_remote(pid, address, 4));
+
+                kill(child, Signal::SIGKILL).unwrap();
+                waitpid(child, None).unwrap();
+                let at_random = at_random.unwrap();
+                assert!(stack.0 <= at_random && at_random + 16 <= stack.1);
+                assert_ne!(random_bytes, vec![0u8; 16]);
+                // The vDSO may be disabled on the host (e.g. `vdso=0`).
+                if let Some(vdso) = vdso {
+                    assert_eq!(at_sysinfo_ehdr, Some(vdso.0));
+                    assert_eq!(vdso_magic.unwrap(), b"\x7fELF");
+                }
+            },
+        )
+    }
+
+    /// proot-rs keeps no fd table of its own: the paths of the fds are read
+    /// from `/proc/<pid>/fd` (see `Tracee::get_path_from_fd()`), from which
+    /// the kernel removes the `O_CLOEXEC` fds as soon as `execve()` succeeds.
+    #[test]
+    fn test_execve_closes_cloexec_fds() {
+        const CLOEXEC_FD: RawFd = 100;
+        const INHERITED_FD: RawFd = 101;
+        test_with_proot(
+            |tracee, is_sysenter, before_translation| {
+                if !is_sysenter
+                    && before_translation
+                    && tracee.regs.get_sys_num(Original) == sc::nr::EXECVE
+                    && tracee.regs.get(Current, SysResult) == 0
+                {
+                    assert!(tracee.get_path_from_fd(CLOEXEC_FD, Side::Guest).is_err());
+                    assert_eq!(
+                        tracee.get_path_from_fd(INHERITED_FD, Side::Guest).unwrap(),
+                        Path::new("/etc/passwd")
+                    );
+                }
+            },
+            || {
+                let fd = open("/etc/passwd", OFlag::O_RDONLY, Mode::empty()).unwrap();
+                dup3(fd, CLOEXEC_FD, OFlag::O_CLOEXEC).unwrap();
+                dup2(fd, INHERITED_FD).unwrap();
+                // The new program only has the inherited fd.
+                execv(
+                    &CString::new("/bin/sh").unwrap(),
+                    &[
+                        CString::new("sh").unwrap(),
+                        CString::new("-c").unwrap(),
+                        CString::new("cat <&101 >/dev/null && ! cat <&100 2>/dev/null").unwrap(),
+                    ],
+                )
+                .unwrap();
+            },
+        )
+    }
+}
@@ -4,6 +4,7 @@ use crate::filesystem::FileSystem;
 use crate::filesystem::Translator;
 use crate::kernel::execve::binfmt::elf::{ElfHeader, ExecutableClass, ProgramHeader};
 use crate::kernel::execve::binfmt::elf::{PF_R, PF_W, PF_X, PT_GNU_STACK, PT_INTERP, PT_LOAD};
+use crate::kernel::execve::open_executable;
 use crate::register::Word;
 use nix::sys::mman::MapFlags;
 use nix::sys::mman::ProtFlags;
@@ -26,8 +27,12 @@ pub struct Mapping {
 // TODO: redesign this struct and remove unnecessary `Option`
 #[derive(Debug, PartialEq)]
 pub struct LoadInfo {
+    /// The guest path given to `execve()`, used for `AT_EXECFN`.
     pub raw_path: Option<PathBuf>,
+    /// The canonical guest path of the file, which is opened by the loader
+    /// (its `open()` is translated as any other).
     pub user_path: Option<PathBuf>,
+    /// The host path of the file, only used by proot-rs to parse it.
     pub host_path: Option<PathBuf>,
     pub elf_header: ElfHeader,
     pub mappings: Vec<Mapping>,
@@ -36,11 +41,11 @@ pub struct LoadInfo {
 }
 
 lazy_static! {
-    static ref PAGE_SIZE: Word = match sysconf(SysconfVar::PAGE_SIZE) {
+    pub static ref PAGE_SIZE: Word = match sysconf(SysconfVar::PAGE_SIZE) {
         Ok(Some(value)) => value as Word,
         _ => 0x1000,
     };
-    static ref PAGE_MASK: Word = !(*PAGE_SIZE - 1);
+    pub static ref PAGE_MASK: Word = !(*PAGE_SIZE - 1);
 }
 
 //TODO: move these in arch.rs and do cfg for each env
@@ -87,7 +92,7 @@ impl LoadInfo {
     ///     - mappings
     ///     - interp???
     pub fn from(fs: &FileSystem, host_path: &Path) -> Result<LoadInfo> {
-        let mut file = File::open(host_path)?;
+        let mut file = open_executable(host_path)?;
         let (elf_header, mut file) = ElfHeader::extract_from(&mut file)?;
 
         // Sanity checks.
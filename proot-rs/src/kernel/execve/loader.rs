@@ -1,7 +1,11 @@
 use crate::errors::Result;
 use crate::filesystem::temp::TempFile;
+use crate::filesystem::FileSystem;
+use crate::kernel::execve::load_info::LoadInfo;
+use crate::register::Word;
 use libc::{S_IRUSR, S_IXUSR};
 use std::io::Write;
+use std::ops::Range;
 use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
 
@@ -10,6 +14,7 @@ const LOADER_EXE: &'static [u8] = include_bytes!("loader-shim");
 pub trait LoaderFile {
     fn prepare_loader(&self) -> Result<()>;
     fn get_loader_path(&self) -> &Path;
+    fn get_loader_ranges(&self) -> Result<Vec<Range<Word>>>;
 }
 
 impl LoaderFile for TempFile {
@@ -30,11 +35,29 @@ impl LoaderFile for TempFile {
     fn get_loader_path(&self) -> &Path {
         &self.path
     }
+
+    /// Returns the address ranges where the loader is mapped in a tracee.
+    ///
+    /// The loader is linked at a fixed address (see `loader-shim`), and stays
+    /// mapped in the address space of the tracee after it has started the
+    /// program. The loader must have been prepared before.
+    fn get_loader_ranges(&self) -> Result<Vec<Range<Word>>> {
+        // The loader is a static executable, so the file-system is not used to
+        // find an interpreter.
+        let load_info = LoadInfo::from(&FileSystem::new(), &self.path)?;
+        Ok(load_info
+            .mappings
+            .iter()
+            .map(|mapping| mapping.addr..mapping.addr + mapping.length)
+            .collect())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::{Arc, Barrier};
+    use std::thread;
 
     #[test]
     fn test_loader_is_loaded_and_deleted() {
@@ -56,4 +79,44 @@ mod tests {
         // the loader must have been deleted
         assert!(!loader_path.exists());
     }
+
+    #[test]
+    fn test_loader_ranges() {
+        let loader = TempFile::new("prefix_test_loader_ranges");
+        loader.prepare_loader().unwrap();
+
+        let ranges = loader.get_loader_ranges().unwrap();
+        assert!(!ranges.is_empty());
+        assert!(ranges.iter().all(|range| range.start < range.end));
+    }
+
+    #[test]
+    fn test_concurrent_loaders_are_not_clobbered() {
+        // Same prefix as the loader of `PRoot`, in the same process (i.e. with
+        // the same pid).
+        let barrier = Arc::new(Barrier::new(2));
+        let threads: Vec<_> = (0..2)
+            .map(|_| {
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    let loader = TempFile::new("prooted");
+                    barrier.wait();
+                    loader.prepare_loader().unwrap();
+                    // both loaders exist at the same time
+                    barrier.wait();
+                    assert_eq!(std::fs::read(&loader.path).unwrap(), LOADER_EXE);
+                    let loader_path = loader.path.clone();
+                    barrier.wait();
+                    loader_path
+                })
+            })
+            .collect();
+        let loader_paths: Vec<_> = threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .collect();
+
+        assert_ne!(loader_paths[0], loader_paths[1]);
+        assert!(loader_paths.iter().all(|path| !path.exists()));
+    }
 }
@@ -1,20 +1,50 @@
 #[macro_use]
 mod macros;
 mod binfmt;
+pub mod dump;
 pub mod enter;
 pub mod exit;
 pub mod load_info;
 pub mod loader;
 mod params;
 
-use crate::errors::Result;
-use crate::kernel::execve::loader::LoaderFile;
+pub use self::binfmt::{INTERPRETER_REWRITE_LIMIT, MAX_INTERPRETER_REWRITE_LIMIT};
+
+use std::fs::{File, OpenOptions};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+
+use crate::errors::*;
+use crate::process::proot::InfoBag;
 use crate::process::tracee::Tracee;
 
-pub fn enter(tracee: &mut Tracee, loader: &dyn LoaderFile) -> Result<()> {
-    enter::translate(tracee, loader)
+pub fn enter(tracee: &mut Tracee, info_bag: &InfoBag) -> Result<()> {
+    enter::translate(tracee, &info_bag.loader, info_bag.max_interp_depth)
 }
 
 pub fn exit(tracee: &mut Tracee) -> Result<()> {
     exit::translate(tracee)
 }
+
+/// Opens the host file of an executable or of an interpreter, to read its
+/// headers.
+///
+/// With `O_NONBLOCK`, so that a fifo does not block proot-rs (and thus all the
+/// tracees) until a writer opens it; as the other files which are not regular,
+/// it is then rejected with `EACCES`, as the kernel does.
+pub fn open_executable(host_path: &Path) -> Result<File> {
+    let file = OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(host_path)?;
+    if !file.metadata()?.is_file() {
+        return Err(Error::errno_with_msg(
+            EACCES,
+            format!(
+                "The file to be executed is not a regular file: {:?}",
+                host_path
+            ),
+        ));
+    }
+    Ok(file)
+}
@@ -4,25 +4,36 @@ use crate::kernel::heap::*;
 use crate::kernel::ptrace::*;
 use crate::kernel::socket::*;
 use crate::kernel::standard::*;
+use crate::process::proot::InfoBag;
 use crate::process::tracee::Tracee;
 use crate::register::{Original, SysResult, Word};
 
-pub fn translate(tracee: &mut Tracee) {
+pub fn translate(info_bag: &InfoBag, tracee: &mut Tracee) {
     let syscall_number = tracee.regs.get_sys_num(Original);
     let syscall_group = syscall_group_from_sysnum(syscall_number);
+    if info_bag.chroot_only && syscall_group.translates_paths() {
+        return;
+    }
 
     let result = match syscall_group {
         SyscallGroup::Brk => brk::exit(),
         SyscallGroup::GetCwd => getcwd::exit(tracee),
+        SyscallGroup::GetDents => getdents::exit(tracee, info_bag),
         SyscallGroup::Accept => accept::exit(),
         SyscallGroup::GetSockOrPeerName => get_sockorpeer_name::exit(),
+        SyscallGroup::SendRecvMsg => send_recv_msg::exit(tracee),
         SyscallGroup::SocketCall => socketcall::exit(),
         SyscallGroup::Chdir => chdir::exit(tracee),
+        SyscallGroup::Mount => mount::exit(tracee),
+        SyscallGroup::PivotRoot => pivot_root::exit(tracee),
         SyscallGroup::Rename => link_rename::exit(tracee),
         SyscallGroup::RenameAt => rename_at::exit(tracee),
-        SyscallGroup::ReadLinkAt => readlink_at::exit(),
-        #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
-        SyscallGroup::Uname => uname::exit(),
+        SyscallGroup::ReadLink | SyscallGroup::ReadLinkAt => readlink_at::exit(tracee),
+        SyscallGroup::SetHostname => sethostname::exit(tracee),
+        SyscallGroup::StatAt => stat_at::exit(tracee),
+        SyscallGroup::Sync => sync::exit(tracee, info_bag),
+        SyscallGroup::SysInfoTimes => sysinfo_times::exit(info_bag, tracee),
+        SyscallGroup::Uname => uname::exit(tracee),
         SyscallGroup::Execve => execve::exit(tracee),
         SyscallGroup::Ptrace => ptrace::exit(),
         SyscallGroup::Wait => wait::exit(),
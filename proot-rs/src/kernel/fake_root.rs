@@ -0,0 +1,723 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{self, Metadata};
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::prelude::RawFd;
+use std::path::{Path, PathBuf};
+
+use libc::c_void;
+use nix::fcntl::AtFlags;
+use nix::unistd::Pid;
+
+use crate::errors::*;
+use crate::kernel::standard::chmod_access_mknod_at::FCHMODAT2;
+use crate::process::tracee::Tracee;
+use crate::register::{Current, Original, PtraceReader, PtraceWriter, SysArg, SysResult, Word};
+use crate::register::{SysArg1, SysArg2, SysArg3, SysArg4, SysArg5, SysArgIndex};
+
+/// The `struct stat` filled by the kernel in `stat()`, `lstat()`, `fstat()`
+/// and `newfstatat()`, or by their `64` variants on 32-bit architectures.
+#[cfg(target_pointer_width = "64")]
+type KernelStat = libc::stat;
+#[cfg(target_pointer_width = "32")]
+type KernelStat = libc::stat64;
+
+/// Offsets of `stx_uid`, `stx_gid`, `stx_mode`, `stx_ino`, `stx_dev_major`
+/// and `stx_dev_minor` in `struct statx`.
+const STX_UID_OFFSET: usize = 20;
+const STX_GID_OFFSET: usize = 24;
+const STX_MODE_OFFSET: usize = 28;
+const STX_INO_OFFSET: usize = 32;
+const STX_DEV_MAJOR_OFFSET: usize = 136;
+const STX_DEV_MINOR_OFFSET: usize = 140;
+
+/// The owner of a file, as seen by the guest.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ownership {
+    pub uid: u32,
+    pub gid: u32,
+}
+
+/// A change of a file asked by a tracee, recorded at the enter stage of its
+/// syscall and committed at the exit stage.
+#[derive(Debug, Clone, Copy)]
+enum Change {
+    /// Asked by `chown()` and the like.
+    Ownership(Ownership),
+    /// The permission bits (including setuid, setgid and sticky) asked by
+    /// `chmod()` and the like.
+    Mode(u32),
+    /// The removal of the last link of the file, by `unlink()`, `rmdir()` or
+    /// `rename()` over it, after which its inode may be reused by another file.
+    Removal,
+}
+
+/// The state of the `--fake-root` option, shared by all the tracees.
+///
+/// The tracees see themselves as root: the ids reported by `getuid()` and
+/// the like are 0, and so is the owner of the files of the user of proot-rs.
+/// `chown()` and `chmod()` (and the like) are still tried on the host, but
+/// their `EPERM` is hidden, and the ownership or the mode they asked for is
+/// recorded and reported by the syscalls of the `stat()` family, so that
+/// e.g. `tar`, `cp -p` or packaging tools find back what they set, even the
+/// setuid and setgid bits that the host did not keep.
+///
+/// The changes are recorded by device and inode of the host file, so that
+/// they follow the file through renames and hard links, and also apply to
+/// `fstat()`. They are forgotten when the last link of the file is removed,
+/// so that they do not apply to a new file which reuses its inode (but also
+/// no longer to the fds still open on the removed file).
+///
+/// The ids are translated as in a user namespace: by default the ids of
+/// proot-rs on the host (i.e. the ones of the tracees) are mapped to root,
+/// and `--map-uid` and `--map-gid` add other mappings. The ids which are not
+/// mapped are the same on both sides.
+#[derive(Debug)]
+pub struct FakeRoot {
+    /// The mappings of the uids, as `(host uid, guest uid)`.
+    uid_map: Vec<(u32, u32)>,
+    /// The mappings of the gids, as `(host gid, guest gid)`.
+    gid_map: Vec<(u32, u32)>,
+    /// The ownerships given by the tracees, by device and inode.
+    ownerships: HashMap<(u64, u64), Ownership>,
+    /// The permission bits given by the tracees, by device and inode.
+    modes: HashMap<(u64, u64), u32>,
+    /// The change asked by the current syscall of each tracee.
+    pending_changes: HashMap<Pid, ((u64, u64), Change)>,
+}
+
+impl FakeRoot {
+    pub fn new() -> FakeRoot {
+        FakeRoot {
+            uid_map: vec![(nix::unistd::getuid().as_raw(), 0)],
+            gid_map: vec![(nix::unistd::getgid().as_raw(), 0)],
+            ownerships: HashMap::new(),
+            modes: HashMap::new(),
+            pending_changes: HashMap::new(),
+        }
+    }
+
+    /// Maps the host uid `host` to the guest uid `guest`, replacing the
+    /// previous mappings of either of them.
+    pub fn map_uid(&mut self, host: u32, guest: u32) {
+        add_mapping(&mut self.uid_map, host, guest);
+    }
+
+    /// Maps the host gid `host` to the guest gid `guest`, replacing the
+    /// previous mappings of either of them.
+    pub fn map_gid(&mut self, host: u32, guest: u32) {
+        add_mapping(&mut self.gid_map, host, guest);
+    }
+
+    /// Returns the ownership of the host file `(dev, ino)` seen by the guest,
+    /// whose owner on the host is `host_ownership`.
+    pub fn guest_ownership(&self, dev: u64, ino: u64, host_ownership: Ownership) -> Ownership {
+        if let Some(ownership) = self.ownerships.get(&(dev, ino)) {
+            return *ownership;
+        }
+        Ownership {
+            uid: guest_id(&self.uid_map, host_ownership.uid),
+            gid: guest_id(&self.gid_map, host_ownership.gid),
+        }
+    }
+
+    /// Returns the mode of the host file `(dev, ino)` seen by the guest, whose
+    /// mode on the host is `host_mode`.
+    pub fn guest_mode(&self, dev: u64, ino: u64, host_mode: u32) -> u32 {
+        match self.modes.get(&(dev, ino)) {
+            Some(mode) => (host_mode & libc::S_IFMT as u32) | mode,
+            None => host_mode,
+        }
+    }
+}
+
+/// Parses a mapping of `--map-uid` or `--map-gid`, in the format
+/// `host_id:guest_id`.
+pub fn parse_id_mapping(mapping: &str) -> Result<(u32, u32)> {
+    let invalid = || Error::errno_with_msg(EINVAL, format!("invalid id mapping {:?}", mapping));
+
+    let mut ids = mapping.splitn(2, ':');
+    let host = ids.next().unwrap().trim().parse().map_err(|_| invalid())?;
+    let guest = ids
+        .next()
+        .ok_or_else(invalid)?
+        .trim()
+        .parse()
+        .map_err(|_| invalid())?;
+    Ok((host, guest))
+}
+
+fn add_mapping(map: &mut Vec<(u32, u32)>, host: u32, guest: u32) {
+    map.retain(|&(mapped_host, mapped_guest)| mapped_host != host && mapped_guest != guest);
+    map.push((host, guest));
+}
+
+/// Returns the guest id of the host id `host` in `map`.
+fn guest_id(map: &[(u32, u32)], host: u32) -> u32 {
+    map.iter()
+        .find(|&&(mapped_host, _)| mapped_host == host)
+        .map_or(host, |&(_, guest)| guest)
+}
+
+/// Returns the host id of the guest id `guest` in `map`.
+fn host_id(map: &[(u32, u32)], guest: u32) -> u32 {
+    map.iter()
+        .find(|&&(_, mapped_guest)| mapped_guest == guest)
+        .map_or(guest, |&(host, _)| host)
+}
+
+/// Records the change asked by a syscall of the `chown()` or the `chmod()`
+/// family, once its path was translated, and gives the host ids mapped to
+/// the ones asked by `chown()` to the host file.
+pub fn enter(tracee: &mut Tracee, fake_root: &RefCell<FakeRoot>) -> Result<()> {
+    // Left by a tracee which did not reach the exit stage of its syscall, e.g.
+    // killed in between.
+    fake_root.borrow_mut().pending_changes.remove(&tracee.pid);
+
+    let sys_num = tracee.regs.get_sys_num(Original);
+    let arg = |index| tracee.regs.get(Current, SysArg(index));
+
+    if let Some((dirfd, path)) = removed_path(tracee, sys_num)? {
+        // The link itself is removed, not the file it may point to. An error
+        // is left to the kernel.
+        if let Ok(metadata) = host_metadata(tracee.pid, dirfd, &path, false) {
+            // Otherwise the other links keep the file.
+            if metadata.is_dir() || metadata.nlink() == 1 {
+                fake_root.borrow_mut().pending_changes.insert(
+                    tracee.pid,
+                    ((metadata.dev(), metadata.ino()), Change::Removal),
+                );
+            }
+        }
+        return Ok(());
+    }
+
+    // The arguments of the uid and the gid, or twice the one of the mode.
+    let (dirfd, path, deref_final, is_chown, id_args) = match sys_num {
+        #[cfg(target_arch = "x86_64")]
+        sc::nr::CHOWN | sc::nr::LCHOWN => (
+            libc::AT_FDCWD,
+            tracee.regs.get_sysarg_path(SysArg1)?,
+            sys_num == sc::nr::CHOWN,
+            true,
+            (SysArg2, SysArg3),
+        ),
+        #[cfg(any(target_arch = "x86", target_arch = "arm"))]
+        sc::nr::CHOWN32 | sc::nr::LCHOWN32 => (
+            libc::AT_FDCWD,
+            tracee.regs.get_sysarg_path(SysArg1)?,
+            sys_num == sc::nr::CHOWN32,
+            true,
+            (SysArg2, SysArg3),
+        ),
+        #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+        sc::nr::FCHOWN => (
+            arg(SysArg1) as RawFd,
+            PathBuf::new(),
+            true,
+            true,
+            (SysArg2, SysArg3),
+        ),
+        #[cfg(any(target_arch = "x86", target_arch = "arm"))]
+        sc::nr::FCHOWN32 => (
+            arg(SysArg1) as RawFd,
+            PathBuf::new(),
+            true,
+            true,
+            (SysArg2, SysArg3),
+        ),
+        sc::nr::FCHOWNAT => {
+            let flags = AtFlags::from_bits_truncate(arg(SysArg5) as _);
+            (
+                arg(SysArg1) as RawFd,
+                tracee.regs.get_sysarg_path(SysArg2)?,
+                !flags.contains(AtFlags::AT_SYMLINK_NOFOLLOW),
+                true,
+                (SysArg3, SysArg4),
+            )
+        }
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "arm"))]
+        sc::nr::CHMOD => (
+            libc::AT_FDCWD,
+            tracee.regs.get_sysarg_path(SysArg1)?,
+            true,
+            false,
+            (SysArg2, SysArg2),
+        ),
+        sc::nr::FCHMOD => (
+            arg(SysArg1) as RawFd,
+            PathBuf::new(),
+            true,
+            false,
+            (SysArg2, SysArg2),
+        ),
+        sc::nr::FCHMODAT => (
+            arg(SysArg1) as RawFd,
+            tracee.regs.get_sysarg_path(SysArg2)?,
+            true,
+            false,
+            (SysArg3, SysArg3),
+        ),
+        FCHMODAT2 => {
+            let flags = AtFlags::from_bits_truncate(arg(SysArg4) as _);
+            (
+                arg(SysArg1) as RawFd,
+                tracee.regs.get_sysarg_path(SysArg2)?,
+                !flags.contains(AtFlags::AT_SYMLINK_NOFOLLOW),
+                false,
+                (SysArg3, SysArg3),
+            )
+        }
+        _ => return Ok(()),
+    };
+
+    let ids = (arg(id_args.0) as u32, arg(id_args.1) as u32);
+
+    let metadata = host_metadata(tracee.pid, dirfd, &path, deref_final)?;
+    let key = (metadata.dev(), metadata.ino());
+    let mut fake_root = fake_root.borrow_mut();
+    let change = if is_chown {
+        let current = fake_root.guest_ownership(key.0, key.1, ownership_of(&metadata));
+        // -1 leaves the id unchanged.
+        let id_or = |id, current| if id == u32::MAX { current } else { id };
+        for &(index, id, map) in &[
+            (id_args.0, ids.0, &fake_root.uid_map),
+            (id_args.1, ids.1, &fake_root.gid_map),
+        ] {
+            if id != u32::MAX {
+                tracee.regs.set(
+                    SysArg(index),
+                    host_id(map, id) as Word,
+                    "map the guest id to the host in fake_root::enter()",
+                );
+            }
+        }
+        Change::Ownership(Ownership {
+            uid: id_or(ids.0, current.uid),
+            gid: id_or(ids.1, current.gid),
+        })
+    } else {
+        Change::Mode(ids.0 & 0o7777)
+    };
+    fake_root.pending_changes.insert(tracee.pid, (key, change));
+    Ok(())
+}
+
+/// Returns the directory fd and the host path of the file removed by an
+/// `unlink()`, `rmdir()` or `rename()` syscall (i.e. the new path of the
+/// latter, if it exists), or `None` for the other syscalls.
+fn removed_path(tracee: &Tracee, sys_num: usize) -> Result<Option<(RawFd, PathBuf)>> {
+    let arg = |index| tracee.regs.get(Current, SysArg(index));
+    let removed_path = match sys_num {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "arm"))]
+        sc::nr::UNLINK | sc::nr::RMDIR => (libc::AT_FDCWD, tracee.regs.get_sysarg_path(SysArg1)?),
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "arm"))]
+        sc::nr::RENAME => (libc::AT_FDCWD, tracee.regs.get_sysarg_path(SysArg2)?),
+        sc::nr::UNLINKAT => (arg(SysArg1) as RawFd, tracee.regs.get_sysarg_path(SysArg2)?),
+        // With `RENAME_EXCHANGE`, both files are kept.
+        sc::nr::RENAMEAT2 if arg(SysArg5) & libc::RENAME_EXCHANGE as Word != 0 => return Ok(None),
+        sc::nr::RENAMEAT | sc::nr::RENAMEAT2 => {
+            (arg(SysArg3) as RawFd, tracee.regs.get_sysarg_path(SysArg4)?)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(removed_path))
+}
+
+/// Reports the ids, the ownerships and the modes seen by the guest.
+pub fn exit(tracee: &mut Tracee, fake_root: &RefCell<FakeRoot>) {
+    if let Err(error) = exit_inner(tracee, fake_root) {
+        debug!("fake_root::exit() raised an error: {:?}", error);
+    }
+}
+
+fn exit_inner(tracee: &mut Tracee, fake_root: &RefCell<FakeRoot>) -> Result<()> {
+    let sys_num = tracee.regs.get_sys_num(Original);
+    let result = tracee.regs.get(Current, SysResult) as isize;
+
+    let pending_change = fake_root.borrow_mut().pending_changes.remove(&tracee.pid);
+    if let Some((key, Change::Removal)) = pending_change {
+        if result == 0 {
+            let mut fake_root = fake_root.borrow_mut();
+            fake_root.ownerships.remove(&key);
+            fake_root.modes.remove(&key);
+        }
+        return Ok(());
+    }
+    if let Some((key, change)) = pending_change {
+        // Only root could have made this change on the host.
+        if result == 0 || result == -(EPERM as isize) {
+            let mut fake_root = fake_root.borrow_mut();
+            match change {
+                Change::Ownership(ownership) => {
+                    fake_root.ownerships.insert(key, ownership);
+                }
+                Change::Mode(mode) => {
+                    fake_root.modes.insert(key, mode);
+                }
+                Change::Removal => unreachable!(),
+            }
+            tracee.regs.set(
+                SysResult,
+                0 as Word,
+                "update return value in fake_root::exit()",
+            );
+        }
+        return Ok(());
+    }
+    // Error reported by the kernel.
+    if result < 0 {
+        return Ok(());
+    }
+
+    let fake_root = fake_root.borrow();
+    let set_result_id = |tracee: &mut Tracee, map: &[(u32, u32)]| {
+        tracee.regs.set(
+            SysResult,
+            guest_id(map, result as u32) as Word,
+            "update return value in fake_root::exit()",
+        )
+    };
+    match sys_num {
+        sc::nr::GETUID | sc::nr::GETEUID => set_result_id(tracee, &fake_root.uid_map),
+        sc::nr::GETGID | sc::nr::GETEGID => set_result_id(tracee, &fake_root.gid_map),
+        #[cfg(any(target_arch = "x86", target_arch = "arm"))]
+        sc::nr::GETUID32 | sc::nr::GETEUID32 => set_result_id(tracee, &fake_root.uid_map),
+        #[cfg(any(target_arch = "x86", target_arch = "arm"))]
+        sc::nr::GETGID32 | sc::nr::GETEGID32 => set_result_id(tracee, &fake_root.gid_map),
+        #[cfg(target_pointer_width = "64")]
+        sc::nr::GETRESUID => update_res_ids(tracee, &fake_root.uid_map)?,
+        #[cfg(target_pointer_width = "64")]
+        sc::nr::GETRESGID => update_res_ids(tracee, &fake_root.gid_map)?,
+        #[cfg(any(target_arch = "x86", target_arch = "arm"))]
+        sc::nr::GETRESUID32 => update_res_ids(tracee, &fake_root.uid_map)?,
+        #[cfg(any(target_arch = "x86", target_arch = "arm"))]
+        sc::nr::GETRESGID32 => update_res_ids(tracee, &fake_root.gid_map)?,
+
+        #[cfg(target_arch = "x86_64")]
+        sc::nr::STAT | sc::nr::LSTAT => update_stat(tracee, &fake_root, SysArg2)?,
+        #[cfg(target_pointer_width = "64")]
+        sc::nr::FSTAT => update_stat(tracee, &fake_root, SysArg2)?,
+        #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+        sc::nr::NEWFSTATAT => update_stat(tracee, &fake_root, SysArg3)?,
+        #[cfg(any(target_arch = "x86", target_arch = "arm"))]
+        sc::nr::STAT64 | sc::nr::LSTAT64 | sc::nr::FSTAT64 => {
+            update_stat(tracee, &fake_root, SysArg2)?
+        }
+        #[cfg(any(target_arch = "x86", target_arch = "arm"))]
+        sc::nr::FSTATAT64 => update_stat(tracee, &fake_root, SysArg3)?,
+        sc::nr::STATX => update_statx(tracee, &fake_root)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Returns the metadata of the host file targeted by a syscall, through the
+/// `/proc/<pid>` of the tracee if `path` is relative (e.g. untranslated with
+/// `--chroot-only`) or empty (the file of `dirfd` itself).
+fn host_metadata(pid: Pid, dirfd: RawFd, path: &Path, deref_final: bool) -> Result<Metadata> {
+    if path.is_absolute() {
+        return Ok(if deref_final {
+            fs::metadata(path)?
+        } else {
+            fs::symlink_metadata(path)?
+        });
+    }
+    let base = if dirfd == libc::AT_FDCWD {
+        PathBuf::from(format!("/proc/{}/cwd", pid))
+    } else {
+        PathBuf::from(format!("/proc/{}/fd/{}", pid, dirfd))
+    };
+    if path.as_os_str().is_empty() {
+        // The magic link is always followed to the file of the fd.
+        return Ok(fs::metadata(base)?);
+    }
+    host_metadata(pid, dirfd, &base.join(path), deref_final)
+}
+
+fn ownership_of(metadata: &Metadata) -> Ownership {
+    Ownership {
+        uid: metadata.uid(),
+        gid: metadata.gid(),
+    }
+}
+
+/// Replaces the three ids of `getresuid()` or `getresgid()` with the guest
+/// ones in `map`.
+fn update_res_ids(tracee: &Tracee, map: &[(u32, u32)]) -> Result<()> {
+    for &index in &[SysArg1, SysArg2, SysArg3] {
+        let address = tracee.regs.get(Original, SysArg(index)) as *mut c_void;
+        let bytes = tracee.regs.read_data(address, 4)?;
+        let id = guest_id(
+            map,
+            u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        );
+        tracee.regs.write_data(address, &id.to_ne_bytes(), false)?;
+    }
+    Ok(())
+}
+
+/// Replaces the owner and the mode in the `struct stat` given in the argument
+/// `buf_arg`.
+fn update_stat(tracee: &Tracee, fake_root: &FakeRoot, buf_arg: SysArgIndex) -> Result<()> {
+    let address = tracee.regs.get(Original, SysArg(buf_arg)) as *mut c_void;
+    let bytes = tracee
+        .regs
+        .read_data(address, std::mem::size_of::<KernelStat>())?;
+    let mut stat: KernelStat = unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const _) };
+
+    let ownership = fake_root.guest_ownership(
+        stat.st_dev as u64,
+        stat.st_ino as u64,
+        Ownership {
+            uid: stat.st_uid,
+            gid: stat.st_gid,
+        },
+    );
+    let mode = fake_root.guest_mode(stat.st_dev as u64, stat.st_ino as u64, stat.st_mode);
+    if ownership.uid == stat.st_uid && ownership.gid == stat.st_gid && mode == stat.st_mode {
+        return Ok(());
+    }
+    stat.st_uid = ownership.uid;
+    stat.st_gid = ownership.gid;
+    stat.st_mode = mode;
+
+    let bytes = unsafe {
+        std::slice::from_raw_parts(
+            &stat as *const KernelStat as *const u8,
+            std::mem::size_of::<KernelStat>(),
+        )
+    };
+    tracee.regs.write_data(address, bytes, false)
+}
+
+/// Replaces the owner and the mode in the `struct statx` of `statx()`.
+fn update_statx(tracee: &Tracee, fake_root: &FakeRoot) -> Result<()> {
+    let statxbuf = tracee.regs.get(Original, SysArg(SysArg5)) as usize;
+    let read_u32 = |offset| -> Result<u32> {
+        let bytes = tracee
+            .regs
+            .read_data((statxbuf + offset) as *const c_void, 4)?;
+        Ok(u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+    let ino_bytes = tracee
+        .regs
+        .read_data((statxbuf + STX_INO_OFFSET) as *const c_void, 8)?;
+    let mut ino = [0u8; 8];
+    ino.copy_from_slice(&ino_bytes);
+    let dev = unsafe {
+        libc::makedev(
+            read_u32(STX_DEV_MAJOR_OFFSET)?,
+            read_u32(STX_DEV_MINOR_OFFSET)?,
+        )
+    };
+    let host_ownership = Ownership {
+        uid: read_u32(STX_UID_OFFSET)?,
+        gid: read_u32(STX_GID_OFFSET)?,
+    };
+
+    let mode_bytes = tracee
+        .regs
+        .read_data((statxbuf + STX_MODE_OFFSET) as *const c_void, 2)?;
+    let host_mode = u16::from_ne_bytes([mode_bytes[0], mode_bytes[1]]);
+
+    let (dev, ino) = (dev as u64, u64::from_ne_bytes(ino));
+    let ownership = fake_root.guest_ownership(dev, ino, host_ownership);
+    let mode = fake_root.guest_mode(dev, ino, host_mode as u32) as u16;
+    if ownership != host_ownership {
+        for &(offset, id) in &[
+            (STX_UID_OFFSET, ownership.uid),
+            (STX_GID_OFFSET, ownership.gid),
+        ] {
+            tracee
+                .regs
+                .write_data((statxbuf + offset) as *mut c_void, &id.to_ne_bytes(), false)?;
+        }
+    }
+    if mode != host_mode {
+        tracee.regs.write_data(
+            (statxbuf + STX_MODE_OFFSET) as *mut c_void,
+            &mode.to_ne_bytes(),
+            false,
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::io::AsRawFd;
+
+    use nix::unistd::{chown, Gid, Uid};
+
+    use super::*;
+    use crate::filesystem::temp::{TempDir, TempFile};
+    use crate::utils::tests::test_with_proot_and_info_bag;
+
+    /// Runs `func_tracee` in the tracee of `test_with_proot_and_info_bag()`,
+    /// with `--fake-root`.
+    fn with_fake_root<FuncTracee: FnOnce()>(func_tracee: FuncTracee) {
+        test_with_proot_and_info_bag(
+            |info_bag| info_bag.fake_root = Some(RefCell::new(FakeRoot::new())),
+            |_tracee, _is_sysenter, _before_translation| {},
+            func_tracee,
+        )
+    }
+
+    #[test]
+    fn test_fake_root_chown_then_stat() {
+        with_fake_root(|| {
+            let temp_file = TempFile::new("prefix_test_fake_root_chown_then_stat");
+            let filepath = temp_file.path.to_str().unwrap();
+
+            assert_eq!(Uid::current().as_raw(), 0);
+            assert_eq!(Gid::effective().as_raw(), 0);
+
+            let file = File::create(filepath).unwrap();
+            let metadata = fs::metadata(filepath).unwrap();
+            // Owned by the user of proot-rs, i.e. by root.
+            assert_eq!((metadata.uid(), metadata.gid()), (0, 0));
+
+            chown(filepath, Some(Uid::from_raw(1000)), None).unwrap();
+            let metadata = fs::metadata(filepath).unwrap();
+            assert_eq!((metadata.uid(), metadata.gid()), (1000, 0));
+            // Also through the fd, and with statx().
+            assert_eq!(file.metadata().unwrap().uid(), 1000);
+            let mut statx = nc::statx_t::default();
+            nc::statx(nc::AT_FDCWD, filepath, 0, nc::STATX_BASIC_STATS, &mut statx).unwrap();
+            assert_eq!((statx.stx_uid, statx.stx_gid), (1000, 0));
+
+            // -1 leaves the uid as recorded.
+            nix::unistd::fchown(file.as_raw_fd(), None, Some(Gid::from_raw(1000))).unwrap();
+            let metadata = fs::metadata(filepath).unwrap();
+            assert_eq!((metadata.uid(), metadata.gid()), (1000, 1000));
+
+            // The errors of the kernel are kept.
+            assert_eq!(
+                chown(
+                    "/tmp/nonexistent_file_for_test_fake_root",
+                    Some(Uid::from_raw(1000)),
+                    None
+                ),
+                Err(Errno::ENOENT)
+            );
+        })
+    }
+
+    #[test]
+    fn test_fake_root_chmod_setuid_then_stat() {
+        with_fake_root(|| {
+            let temp_file = TempFile::new("prefix_test_fake_root_chmod_setuid_then_stat");
+            let filepath = temp_file.path.to_str().unwrap();
+
+            let file = File::create(filepath).unwrap();
+
+            fs::set_permissions(filepath, fs::Permissions::from_mode(0o4755)).unwrap();
+            let metadata = fs::metadata(filepath).unwrap();
+            assert!(metadata.is_file());
+            assert_eq!(metadata.mode() & 0o7777, 0o4755);
+            let mut statx = nc::statx_t::default();
+            nc::statx(nc::AT_FDCWD, filepath, 0, nc::STATX_BASIC_STATS, &mut statx).unwrap();
+            assert_eq!(statx.stx_mode as u32 & 0o7777, 0o4755);
+
+            // Also through the fd, with the setgid and sticky bits.
+            nix::sys::stat::fchmod(
+                file.as_raw_fd(),
+                nix::sys::stat::Mode::from_bits_truncate(0o3700),
+            )
+            .unwrap();
+            assert_eq!(file.metadata().unwrap().mode() & 0o7777, 0o3700);
+            assert_eq!(
+                file.metadata().unwrap().mode() & libc::S_IFMT,
+                libc::S_IFREG
+            );
+        })
+    }
+
+    #[test]
+    fn test_fake_root_forget_removed_file() {
+        with_fake_root(|| {
+            let dir = TempDir::new("prefix_test_fake_root_forget_removed_file");
+            dir.create_dir().unwrap();
+            let (first, second) = (dir.path.join("first"), dir.path.join("second"));
+
+            let file = File::create(&first).unwrap();
+            chown(&first, Some(Uid::from_raw(1000)), None).unwrap();
+            fs::set_permissions(&first, fs::Permissions::from_mode(0o4755)).unwrap();
+            let ino = file.metadata().unwrap().ino();
+
+            // Another link keeps the file.
+            fs::hard_link(&first, &second).unwrap();
+            fs::remove_file(&first).unwrap();
+            let metadata = fs::metadata(&second).unwrap();
+            assert_eq!((metadata.uid(), metadata.mode() & 0o7777), (1000, 0o4755));
+
+            // Then the last one, which is replaced by a new file.
+            File::create(&first).unwrap();
+            fs::rename(&first, &second).unwrap();
+            drop(file);
+
+            // A new file which reuses the inode is not given its records,
+            // if the file-system reuses it at all.
+            for index in 0..16 {
+                let path = dir.path.join(index.to_string());
+                File::create(&path).unwrap();
+                let metadata = fs::metadata(&path).unwrap();
+                assert_eq!(metadata.uid(), 0);
+                assert_eq!(metadata.mode() & 0o7000, 0);
+                if metadata.ino() == ino {
+                    break;
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_id_mapping() {
+        assert_eq!(parse_id_mapping("1000:0").unwrap(), (1000, 0));
+        assert_eq!(parse_id_mapping(" 5 : 6 ").unwrap(), (5, 6));
+        assert!(parse_id_mapping("1000").is_err());
+        assert!(parse_id_mapping("1000:").is_err());
+        assert!(parse_id_mapping("a:0").is_err());
+        assert!(parse_id_mapping("-1:0").is_err());
+    }
+
+    #[test]
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    fn test_fake_root_map_uid() {
+        let host_uid = nix::unistd::getuid().as_raw();
+        test_with_proot_and_info_bag(
+            |info_bag| {
+                let mut fake_root = FakeRoot::new();
+                fake_root.map_uid(nix::unistd::getuid().as_raw(), 1234);
+                info_bag.fake_root = Some(RefCell::new(fake_root));
+            },
+            move |tracee, is_sysenter, before_translation| {
+                if is_sysenter
+                    && !before_translation
+                    && tracee.regs.get_sys_num(Original) == sc::nr::FCHOWN
+                {
+                    // The guest uid is given back to the host as the mapped one.
+                    assert_eq!(tracee.regs.get(Current, SysArg(SysArg2)) as u32, host_uid);
+                }
+            },
+            || {
+                let temp_file = TempFile::new("prefix_test_fake_root_map_uid");
+                let filepath = temp_file.path.to_str().unwrap();
+
+                // The mapping replaces the one to root.
+                assert_eq!(Uid::current().as_raw(), 1234);
+                assert_eq!(Gid::current().as_raw(), 0);
+
+                let file = File::create(filepath).unwrap();
+                let metadata = fs::metadata(filepath).unwrap();
+                assert_eq!((metadata.uid(), metadata.gid()), (1234, 0));
+
+                nix::unistd::fchown(file.as_raw_fd(), Some(Uid::from_raw(1234)), None).unwrap();
+                assert_eq!(file.metadata().unwrap().uid(), 1234);
+            },
+        )
+    }
+}
@@ -1,3 +1,9 @@
+use crate::kernel::standard::chmod_access_mknod_at::{FACCESSAT2, FCHMODAT2};
+use crate::kernel::standard::denied::denied_syscall;
+use crate::kernel::standard::unhandled_path::{
+    FSPICK, MOUNT_SETATTR, MOVE_MOUNT, OPENAT2, OPEN_TREE,
+};
+
 /// Used to organise system call numbers into an easily-matchable enumeration.
 /// It's easier and cleaner to use cfg conditions here rather than in the huge
 /// match in `translate_syscall_enter` and `translate_syscall_exit`.
@@ -10,20 +16,28 @@ pub enum SyscallGroup {
     Wait,
     Brk,
     GetCwd,
+    GetDents,
     Chdir,
     BindConnect,
     Accept,
     GetSockOrPeerName,
+    SendRecvMsg,
     #[allow(dead_code)]
     SocketCall,
+    SocketFamily,
     StandardSyscall, // syscalls that only require their path arguments to be translated
     Open,
     StatAt,
     ChmodAccessMkNodAt,
     InotifyAddWatch,
+    Denied,
     DirLinkAttr,
     PivotRoot,
+    ProcessVm,
     LinkAt,
+    LookupDcookie,
+    Memory,
+    MessageQueue,
     Mount,
     OpenAt,
     Link,
@@ -31,18 +45,60 @@ pub enum SyscallGroup {
     ReadLinkAt,
     Rename,
     RenameAt,
+    SchedAffinity,
+    SetHostname,
+    Swap,
+    Sync,
     SymLink,
     SymLinkAt,
+    SysInfoTimes,
     Uname,
     UnlinkMkdirAt,
+    UnhandledPath, // syscalls that take a path which is not translated yet
+}
+
+impl SyscallGroup {
+    /// Whether the syscalls of this group take or return paths, which are
+    /// translated by their handlers. These handlers are skipped with
+    /// `--chroot-only`, whereas the other ones (e.g. `execve()` and the
+    /// protection of the loader) are always run.
+    pub fn translates_paths(&self) -> bool {
+        matches!(
+            self,
+            SyscallGroup::BindConnect
+                | SyscallGroup::Chdir
+                | SyscallGroup::ChmodAccessMkNodAt
+                | SyscallGroup::DirLinkAttr
+                | SyscallGroup::GetCwd
+                | SyscallGroup::GetDents
+                | SyscallGroup::InotifyAddWatch
+                | SyscallGroup::Link
+                | SyscallGroup::LinkAt
+                | SyscallGroup::LookupDcookie
+                | SyscallGroup::Mount
+                | SyscallGroup::Open
+                | SyscallGroup::OpenAt
+                | SyscallGroup::PivotRoot
+                | SyscallGroup::ReadLink
+                | SyscallGroup::ReadLinkAt
+                | SyscallGroup::Rename
+                | SyscallGroup::RenameAt
+                | SyscallGroup::SendRecvMsg
+                | SyscallGroup::StandardSyscall
+                | SyscallGroup::StatAt
+                | SyscallGroup::Swap
+                | SyscallGroup::SymLink
+                | SyscallGroup::SymLinkAt
+                | SyscallGroup::UnhandledPath
+                | SyscallGroup::UnlinkMkdirAt
+        )
+    }
 }
 
 // TODO: We also need to consider the unshare() system call. For example,
 // the `CLONE_FS` flag may cause errors in our simulation of tracee's `cwd`
 // field.
 
-// TODO: modify the result of getdents64() so that we can handle binded entries.
-
 #[cfg(any(target_os = "linux", target_os = "android"))]
 pub fn syscall_group_from_sysnum(sysnum: usize) -> SyscallGroup {
     match sysnum {
@@ -53,14 +109,17 @@ pub fn syscall_group_from_sysnum(sysnum: usize) -> SyscallGroup {
         sc::nr::WAITPID => SyscallGroup::Wait,
         sc::nr::BRK => SyscallGroup::Brk,
         sc::nr::GETCWD => SyscallGroup::GetCwd,
+        sc::nr::GETDENTS64 => SyscallGroup::GetDents,
         sc::nr::FCHDIR | sc::nr::CHDIR => SyscallGroup::Chdir,
         sc::nr::BIND | sc::nr::CONNECT => SyscallGroup::BindConnect,
         #[cfg(any(target_arch = "x86_64", target_arch = "arm", target_arch = "aarch64"))]
         sc::nr::ACCEPT => SyscallGroup::Accept,
         sc::nr::ACCEPT4 => SyscallGroup::Accept,
         sc::nr::GETSOCKNAME | sc::nr::GETPEERNAME => SyscallGroup::GetSockOrPeerName,
+        sc::nr::SENDMSG | sc::nr::RECVMSG => SyscallGroup::SendRecvMsg,
         #[cfg(any(target_arch = "x86"))]
         sc::nr::SOCKETCALL => SyscallGroup::SocketCall,
+        sc::nr::SOCKET | sc::nr::SOCKETPAIR => SyscallGroup::SocketFamily,
 
         // int syscall(const char *pathname, ...) follow symlink
         sc::nr::ACCT
@@ -69,8 +128,7 @@ pub fn syscall_group_from_sysnum(sysnum: usize) -> SyscallGroup {
         | sc::nr::LISTXATTR
         | sc::nr::REMOVEXATTR
         | sc::nr::SETXATTR
-        | sc::nr::SWAPOFF
-        | sc::nr::SWAPON
+        | sc::nr::STATFS
         | sc::nr::TRUNCATE
         | sc::nr::UMOUNT2 => SyscallGroup::StandardSyscall,
         #[cfg(any(target_arch = "x86"))]
@@ -105,7 +163,9 @@ pub fn syscall_group_from_sysnum(sysnum: usize) -> SyscallGroup {
         sc::nr::NEWFSTATAT => SyscallGroup::StatAt,
 
         // int syscall(int dirfd, const char *pathname, ...)
-        sc::nr::FCHMODAT | sc::nr::FACCESSAT | sc::nr::MKNODAT => SyscallGroup::ChmodAccessMkNodAt,
+        sc::nr::FCHMODAT | FCHMODAT2 | sc::nr::FACCESSAT | FACCESSAT2 | sc::nr::MKNODAT => {
+            SyscallGroup::ChmodAccessMkNodAt
+        }
         #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "arm"))]
         sc::nr::FUTIMESAT => SyscallGroup::ChmodAccessMkNodAt,
 
@@ -136,11 +196,46 @@ pub fn syscall_group_from_sysnum(sysnum: usize) -> SyscallGroup {
         sc::nr::LINK => SyscallGroup::Link,
         #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "arm"))]
         sc::nr::RENAME => SyscallGroup::Rename,
-        sc::nr::RENAMEAT => SyscallGroup::RenameAt,
+        sc::nr::RENAMEAT | sc::nr::RENAMEAT2 => SyscallGroup::RenameAt,
         #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "arm"))]
         sc::nr::SYMLINK => SyscallGroup::SymLink,
         sc::nr::SYMLINKAT => SyscallGroup::SymLinkAt,
         sc::nr::UNAME => SyscallGroup::Uname,
+        sc::nr::SETHOSTNAME | sc::nr::SETDOMAINNAME => SyscallGroup::SetHostname,
+        sc::nr::SWAPON | sc::nr::SWAPOFF => SyscallGroup::Swap,
+        sc::nr::SYNC => SyscallGroup::Sync,
+        sc::nr::LOOKUP_DCOOKIE => SyscallGroup::LookupDcookie,
+        sc::nr::SYSINFO | sc::nr::TIMES => SyscallGroup::SysInfoTimes,
+        sc::nr::MQ_OPEN | sc::nr::MQ_UNLINK => SyscallGroup::MessageQueue,
+        sc::nr::GETCPU | sc::nr::SCHED_GETAFFINITY | sc::nr::SCHED_SETAFFINITY => {
+            SyscallGroup::SchedAffinity
+        }
+        sc::nr::PROCESS_VM_READV | sc::nr::PROCESS_VM_WRITEV => SyscallGroup::ProcessVm,
+
+        // int syscall(void *addr, size_t length, ...) no path translation
+        sc::nr::MADVISE
+        | sc::nr::MINCORE
+        | sc::nr::MLOCK
+        | sc::nr::MLOCK2
+        | sc::nr::MUNLOCK
+        | sc::nr::MPROTECT
+        | sc::nr::MUNMAP
+        | sc::nr::MREMAP => SyscallGroup::Memory,
+        #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+        sc::nr::MMAP => SyscallGroup::Memory,
+        #[cfg(any(target_arch = "x86", target_arch = "arm"))]
+        sc::nr::MMAP2 => SyscallGroup::Memory,
+
+        // syscalls that take a path, but are not translated yet
+        sc::nr::EXECVEAT
+        | sc::nr::FANOTIFY_MARK
+        | sc::nr::QUOTACTL
+        | OPEN_TREE
+        | MOVE_MOUNT
+        | FSPICK
+        | OPENAT2
+        | MOUNT_SETATTR => SyscallGroup::UnhandledPath,
+        sys_num if denied_syscall(sys_num).is_some() => SyscallGroup::Denied,
         _ => SyscallGroup::Ignored,
     }
 }
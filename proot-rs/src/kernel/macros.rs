@@ -0,0 +1,143 @@
+/// Generates the `enter()` function of a syscall handler whose only job is to
+/// translate a path argument, and to replace it with the host path.
+///
+/// ```ignore
+/// translate_path_handler!(
+///     dirfd: SysArg1,
+///     path: SysArg2,
+///     deref_final: |tracee, raw_path| raw_path.with_trailing_slash()
+/// );
+/// ```
+///
+/// - `dirfd` is optional, and is the argument holding the directory fd of the
+///   `*at()` syscalls. Without it, relative paths are relative to the cwd.
+/// - `path` is the argument holding the guest path.
+/// - `deref_final` is evaluated with the tracee and the guest path bound to the
+///   given names, and tells whether the final component of the path is
+///   dereferenced.
+///
+/// Handlers which need to do more (e.g. on `exit()`) still write it by hand.
+macro_rules! translate_path_handler {
+    (
+        dirfd: $dirfd:ident,
+        path: $path:ident,
+        deref_final: |$tracee:ident, $raw_path:ident| $deref_final:expr $(,)?
+    ) => {
+        translate_path_handler!(
+            @enter $tracee,
+            $raw_path,
+            $tracee.regs.get(
+                $crate::register::Current,
+                $crate::register::SysArg($crate::register::$dirfd),
+            ) as std::os::unix::io::RawFd,
+            $path,
+            $deref_final
+        );
+    };
+    (
+        path: $path:ident,
+        deref_final: |$tracee:ident, $raw_path:ident| $deref_final:expr $(,)?
+    ) => {
+        translate_path_handler!(@enter $tracee, $raw_path, libc::AT_FDCWD, $path, $deref_final);
+    };
+    (@enter $tracee:ident, $raw_path:ident, $dirfd:expr, $path:ident, $deref_final:expr) => {
+        pub fn enter(
+            $tracee: &mut $crate::process::tracee::Tracee,
+        ) -> $crate::errors::Result<()> {
+            use $crate::register::{PtraceReader, PtraceWriter};
+
+            let dirfd = $dirfd;
+            let $raw_path = $tracee.regs.get_sysarg_path($crate::register::$path)?;
+            let deref_final: bool = $deref_final;
+
+            let host_path = $tracee
+                .translate_path_at(dirfd, $raw_path, deref_final)?
+                .1;
+
+            $tracee.regs.set_sysarg_path(
+                $crate::register::$path,
+                &host_path,
+                concat!("during enter ", module_path!(), " translation, setting host path"),
+            )?;
+
+            Ok(())
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::io::RawFd;
+    use std::path::PathBuf;
+
+    use nix::unistd::{self, UnlinkatFlags};
+
+    use crate::errors::*;
+    use crate::filesystem::ext::PathExt;
+    use crate::kernel::standard::unlink_mkdir_at;
+    use crate::process::tracee::Tracee;
+    use crate::register::{Current, PtraceReader, PtraceWriter, SysArg, SysArg1, SysArg2};
+    use crate::utils::tests::{fork_test, get_test_rootfs_path};
+
+    /// The implementation of `unlink_mkdir_at::enter()` before it was
+    /// generated by `translate_path_handler!`.
+    fn hand_written_enter(tracee: &mut Tracee) -> Result<()> {
+        let sys_num = tracee.regs.get_sys_num(Current);
+        let dirfd = tracee.regs.get(Current, SysArg(SysArg1)) as RawFd;
+        let raw_path = tracee.regs.get_sysarg_path(SysArg2)?;
+
+        let deref_final = match sys_num {
+            sc::nr::UNLINKAT | sc::nr::MKDIRAT => false,
+            _ => raw_path.with_trailing_slash(),
+        };
+
+        let host_path = tracee.translate_path_at(dirfd, raw_path, deref_final)?.1;
+
+        tracee.regs.set_sysarg_path(
+            SysArg2,
+            &host_path,
+            "during enter open translation, setting host path",
+        )?;
+
+        Ok(())
+    }
+
+    /// Runs `enter` at the current syscall stop, and returns its result along
+    /// with the path it left in the tracee.
+    fn run_enter(tracee: &mut Tracee, enter: fn(&mut Tracee) -> Result<()>) -> Result<PathBuf> {
+        // Start from the registers of the tracee, not from the ones modified
+        // by a previous run.
+        tracee.regs.fetch_regs()?;
+        enter(tracee)?;
+        tracee.regs.get_sysarg_path(SysArg2)
+    }
+
+    #[test]
+    fn test_translate_path_handler_same_as_hand_written() {
+        let rootfs_path = get_test_rootfs_path();
+        let test_path = "/tmp/../tmp/dir_for_test_translate_path_handler";
+
+        fork_test(
+            &rootfs_path,
+            0,
+            |tracee, _| {
+                if tracee.regs.get_sys_num(Current) != sc::nr::UNLINKAT {
+                    return false;
+                }
+                let expected = run_enter(tracee, hand_written_enter).unwrap();
+                let generated = run_enter(tracee, unlink_mkdir_at::enter).unwrap();
+
+                assert_eq!(generated, expected);
+                assert_eq!(
+                    generated,
+                    rootfs_path.join("tmp/dir_for_test_translate_path_handler")
+                );
+                // the registers are not pushed, the tracee runs the original syscall
+                true
+            },
+            || {
+                assert!(unistd::unlinkat(None, test_path, UnlinkatFlags::RemoveDir).is_err());
+            },
+        );
+    }
+}
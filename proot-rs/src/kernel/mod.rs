@@ -1,9 +1,13 @@
+#[macro_use]
+mod macros;
 pub mod enter;
 pub mod execve;
 pub mod exit;
+pub mod fake_root;
 mod groups;
 mod heap;
 mod ptrace;
+pub mod seccomp;
 mod socket;
-mod standard;
+pub mod standard;
 pub mod syscall;
@@ -1,5 +1,11 @@
 use crate::errors::Result;
 
+/// `wait4()` is forwarded as is: proot-rs virtualizes neither the pids nor the
+/// uids, so the pid, the status and the `rusage` written by the kernel to the
+/// memory of the tracee are already consistent with what the guest sees.
+///
+/// The exit of a tracee is reported to proot-rs (its tracer) first, and only
+/// then to its parent, once proot-rs has waited for it in its event loop.
 pub fn enter() -> Result<()> {
     Ok(())
 }
@@ -7,3 +13,44 @@ pub fn enter() -> Result<()> {
 pub fn exit() -> Result<()> {
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use nix::unistd::{fork, ForkResult};
+
+    use crate::utils::tests::test_with_proot;
+
+    #[test]
+    fn test_wait4_rusage() {
+        /// A `rusage` followed by a canary, which must not be overwritten.
+        #[repr(C)]
+        struct Guarded {
+            rusage: libc::rusage,
+            canary: u64,
+        }
+        const CANARY: u64 = 0xdead_beef_cafe_f00d;
+
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || match unsafe { fork() }.unwrap() {
+                ForkResult::Child => {
+                    // Some work, so that the usage is not empty.
+                    let _ = std::fs::read_dir("/etc").map(|dir| dir.count());
+                    unsafe { libc::_exit(3) }
+                }
+                ForkResult::Parent { child } => {
+                    let mut guarded: Guarded = unsafe { std::mem::zeroed() };
+                    guarded.canary = CANARY;
+                    let mut status = 0;
+                    let pid =
+                        unsafe { libc::wait4(child.as_raw(), &mut status, 0, &mut guarded.rusage) };
+                    assert_eq!(pid, child.as_raw());
+                    assert!(libc::WIFEXITED(status));
+                    assert_eq!(libc::WEXITSTATUS(status), 3);
+                    assert!(guarded.rusage.ru_maxrss > 0);
+                    assert_eq!(guarded.canary, CANARY);
+                }
+            },
+        )
+    }
+}
@@ -0,0 +1,189 @@
+use std::fmt::Write;
+
+use libc::{sock_filter, SECCOMP_RET_ALLOW, SECCOMP_RET_TRACE};
+use libc::{BPF_ABS, BPF_JEQ, BPF_JMP, BPF_K, BPF_LD, BPF_RET, BPF_W};
+
+use crate::kernel::groups::{syscall_group_from_sysnum, SyscallGroup};
+use crate::kernel::syscall::{known_syscalls, name_of_syscall};
+
+/// Value of `seccomp_data.arch` for the architecture of proot-rs (see
+/// `AUDIT_ARCH_*` in `linux/audit.h`).
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: u32 = 0xc000_003e;
+#[cfg(target_arch = "x86")]
+const AUDIT_ARCH: u32 = 0x4000_0003;
+#[cfg(target_arch = "arm")]
+const AUDIT_ARCH: u32 = 0x4000_0028;
+#[cfg(target_arch = "aarch64")]
+const AUDIT_ARCH: u32 = 0xc000_00b7;
+
+/// Offset of the `nr` field in `struct seccomp_data`.
+const SECCOMP_DATA_NR_OFFSET: u32 = 0;
+/// Offset of the `arch` field in `struct seccomp_data`.
+const SECCOMP_DATA_ARCH_OFFSET: u32 = 4;
+
+/// Returns the syscalls which have to be stopped by the tracer, that is, the
+/// ones which are not `SyscallGroup::Ignored` in the dispatch table, sorted by
+/// number.
+pub fn intercepted_syscalls() -> Vec<usize> {
+    let mut syscalls: Vec<usize> = known_syscalls()
+        .filter(|sys_num| syscall_group_from_sysnum(*sys_num) != SyscallGroup::Ignored)
+        .collect();
+    syscalls.sort_unstable();
+    syscalls
+}
+
+/// Builds the seccomp filter which returns `SECCOMP_RET_TRACE` for the
+/// `syscalls`, and `SECCOMP_RET_ALLOW` for the others. The syscalls of other
+/// architectures (e.g. a 32-bit guest) are always traced.
+///
+/// The filter is not installed yet (see the TODO in `PRoot::launch_process()`),
+/// but can be inspected with the `--seccomp-bpf-dump` option.
+pub fn build_filter(syscalls: &[usize]) -> Vec<sock_filter> {
+    let mut filter = vec![
+        bpf_stmt(BPF_LD | BPF_W | BPF_ABS, SECCOMP_DATA_ARCH_OFFSET),
+        bpf_jump(BPF_JMP | BPF_JEQ | BPF_K, AUDIT_ARCH, 1, 0),
+        bpf_stmt(BPF_RET | BPF_K, SECCOMP_RET_TRACE),
+        bpf_stmt(BPF_LD | BPF_W | BPF_ABS, SECCOMP_DATA_NR_OFFSET),
+    ];
+    // The jumps of BPF are limited to 255 instructions, so each syscall is
+    // followed by its own return instead of jumping to a common one.
+    for sys_num in syscalls {
+        filter.push(bpf_jump(BPF_JMP | BPF_JEQ | BPF_K, *sys_num as u32, 0, 1));
+        filter.push(bpf_stmt(BPF_RET | BPF_K, SECCOMP_RET_TRACE));
+    }
+    filter.push(bpf_stmt(BPF_RET | BPF_K, SECCOMP_RET_ALLOW));
+    filter
+}
+
+/// Returns a human-readable listing of the BPF program `filter`, one
+/// instruction per line.
+pub fn dump_filter(filter: &[sock_filter]) -> String {
+    let mut dump = String::new();
+    // Whether the accumulator holds the syscall number, so that the compared
+    // values can be named.
+    let mut loaded_nr = false;
+    for (pc, instruction) in filter.iter().enumerate() {
+        let code = instruction.code as u32;
+        let k = instruction.k;
+        let _ = write!(dump, "{:04}: ", pc);
+        if code == BPF_LD | BPF_W | BPF_ABS {
+            loaded_nr = k == SECCOMP_DATA_NR_OFFSET;
+            let _ = match k {
+                SECCOMP_DATA_NR_OFFSET => write!(dump, "A = seccomp_data.nr"),
+                SECCOMP_DATA_ARCH_OFFSET => write!(dump, "A = seccomp_data.arch"),
+                _ => write!(dump, "A = seccomp_data[{}]", k),
+            };
+        } else if code == BPF_JMP | BPF_JEQ | BPF_K {
+            let _ = write!(
+                dump,
+                "if (A == {:#x}) goto {:04} else goto {:04}",
+                k,
+                pc + 1 + instruction.jt as usize,
+                pc + 1 + instruction.jf as usize
+            );
+            if let Some(name) = name_of_syscall(k as usize).filter(|_| loaded_nr) {
+                let _ = write!(dump, "  # {}", name);
+            }
+        } else if code == BPF_RET | BPF_K {
+            let _ = match k {
+                SECCOMP_RET_TRACE => write!(dump, "return TRACE"),
+                SECCOMP_RET_ALLOW => write!(dump, "return ALLOW"),
+                _ => write!(dump, "return {:#x}", k),
+            };
+        } else {
+            let _ = write!(dump, "unknown instruction {:#06x} {:#x}", code, k);
+        }
+        dump.push('\n');
+    }
+    dump
+}
+
+fn bpf_stmt(code: u32, k: u32) -> sock_filter {
+    bpf_jump(code, k, 0, 0)
+}
+
+fn bpf_jump(code: u32, k: u32, jt: u8, jf: u8) -> sock_filter {
+    sock_filter {
+        code: code as u16,
+        jt,
+        jf,
+        k,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs `filter` on the syscall `sys_num` of the architecture of
+    /// proot-rs, and returns the action.
+    fn run_filter(filter: &[sock_filter], sys_num: usize) -> u32 {
+        let mut accumulator = 0;
+        let mut pc = 0;
+        loop {
+            let instruction = &filter[pc];
+            let code = instruction.code as u32;
+            if code == BPF_LD | BPF_W | BPF_ABS {
+                accumulator = match instruction.k {
+                    SECCOMP_DATA_NR_OFFSET => sys_num as u32,
+                    SECCOMP_DATA_ARCH_OFFSET => AUDIT_ARCH,
+                    k => panic!("unexpected load of seccomp_data[{}]", k),
+                };
+                pc += 1;
+            } else if code == BPF_JMP | BPF_JEQ | BPF_K {
+                let offset = if accumulator == instruction.k {
+                    instruction.jt
+                } else {
+                    instruction.jf
+                };
+                pc += 1 + offset as usize;
+            } else if code == BPF_RET | BPF_K {
+                return instruction.k;
+            } else {
+                panic!("unexpected instruction {:#x}", code);
+            }
+        }
+    }
+
+    #[test]
+    fn test_seccomp_filter_and_dump() {
+        let syscalls = intercepted_syscalls();
+        for sys_num in &[
+            sc::nr::EXECVE,
+            sc::nr::CHDIR,
+            sc::nr::GETCWD,
+            sc::nr::OPENAT,
+        ] {
+            assert!(syscalls.contains(sys_num));
+        }
+        assert!(!syscalls.contains(&sc::nr::WRITE));
+
+        let filter = build_filter(&syscalls);
+        for sys_num in known_syscalls() {
+            let expected = if syscalls.contains(&sys_num) {
+                SECCOMP_RET_TRACE
+            } else {
+                SECCOMP_RET_ALLOW
+            };
+            assert_eq!(
+                run_filter(&filter, sys_num),
+                expected,
+                "{:?}",
+                name_of_syscall(sys_num)
+            );
+        }
+
+        let dump = dump_filter(&filter);
+        assert_eq!(dump.lines().count(), filter.len());
+        for sys_num in &syscalls {
+            let line = format!("  # {}", name_of_syscall(*sys_num).unwrap());
+            assert!(dump.lines().any(|l| l.ends_with(&line)), "{}", line);
+        }
+        assert!(!dump.contains("  # write\n"));
+        assert_eq!(
+            dump.lines().last(),
+            Some(&*format!("{:04}: return ALLOW", filter.len() - 1))
+        );
+    }
+}
@@ -1,4 +1,6 @@
 pub mod accept;
 pub mod bind_connect;
 pub mod get_sockorpeer_name;
+pub mod send_recv_msg;
+pub mod socket_family;
 pub mod socketcall;
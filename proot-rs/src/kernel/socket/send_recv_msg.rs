@@ -0,0 +1,292 @@
+use std::ffi::OsStr;
+use std::mem::size_of;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+use libc::{c_void, msghdr, sa_family_t, sockaddr_storage, sockaddr_un, socklen_t};
+
+use crate::errors::*;
+use crate::filesystem::Translator;
+use crate::process::tracee::Tracee;
+use crate::register::{Current, Modified, Original, SysArg, SysArg2, SysResult, Word};
+use crate::register::{PtraceMemoryAllocator, PtraceReader, PtraceWriter};
+
+/// Offset of `sun_path` in `struct sockaddr_un`, which is also the size of the
+/// address of an unnamed socket.
+const SUN_PATH_OFFSET: usize = size_of::<sa_family_t>();
+
+/// `sendmsg()` and `recvmsg()` take a `struct msghdr`, whose `msg_name` is the
+/// address of the peer: the destination of the message for `sendmsg()`, and
+/// its sender for `recvmsg()`. On a unix socket, this address is the path of
+/// the socket of the peer (unless it is unnamed or abstract), so the guest path
+/// given to `sendmsg()` is replaced by its host path, and the host path
+/// returned by `recvmsg()` by its guest path.
+///
+/// The `msghdr` of the tracee is left untouched: a copy of it, which refers to
+/// an address allocated on the stack of the tracee, is given to the kernel
+/// instead. For `recvmsg()`, this address is large enough for any host path,
+/// whatever the size of the buffer of the tracee, and the results are copied
+/// back to the tracee in `exit()`.
+///
+/// The fds passed in the `SCM_RIGHTS` messages of `msg_control` need nothing
+/// special: they are installed by the kernel, and proot-rs keeps no fd table
+/// of its own, their paths are read from `/proc/<pid>/fd` like the ones of any
+/// other fd (see `Tracee::get_path_from_fd()`).
+///
+/// `socketcall()` on x86 is not translated, only the direct syscalls are.
+pub fn enter(tracee: &mut Tracee) -> Result<()> {
+    let msghdr_address = tracee.regs.get(Original, SysArg(SysArg2));
+    if msghdr_address == 0 {
+        return Ok(());
+    }
+    let mut header = read_msghdr(tracee, msghdr_address)?;
+    if header.msg_name.is_null() {
+        return Ok(());
+    }
+
+    let address = if tracee.regs.get_sys_num(Original) == sc::nr::SENDMSG {
+        // An invalid size is left to the kernel.
+        let size = header.msg_namelen as usize;
+        if size <= SUN_PATH_OFFSET || size > size_of::<sockaddr_un>() {
+            return Ok(());
+        }
+        let address = tracee.regs.read_data(header.msg_name, size)?;
+        let guest_path = match unix_path(&address) {
+            Some(guest_path) => guest_path,
+            None => return Ok(()),
+        };
+        let host_path = tracee
+            .translate_path_at(libc::AT_FDCWD, guest_path, true)?
+            .1;
+        unix_address(&host_path).ok_or_else(|| {
+            Error::errno_with_msg(
+                EINVAL,
+                format!("host path of the unix socket is too long: {:?}", host_path),
+            )
+        })?
+    } else {
+        // Large enough for the address of any socket.
+        vec![0; size_of::<sockaddr_storage>()]
+    };
+
+    // The copy of the `msghdr` is allocated first, so that it keeps the
+    // alignment of the stack pointer.
+    let copy_address = tracee
+        .regs
+        .alloc_mem_on_stack(size_of::<msghdr>() as isize)?;
+    header.msg_name = tracee.regs.allocate_and_write(&address, false)?;
+    header.msg_namelen = address.len() as socklen_t;
+    write_msghdr(tracee, copy_address, &header)?;
+    tracee.regs.set(
+        SysArg(SysArg2),
+        copy_address,
+        "replace the msghdr of sendmsg() or recvmsg() with a translated copy",
+    );
+
+    Ok(())
+}
+
+pub fn exit(tracee: &mut Tracee) -> Result<()> {
+    if tracee.regs.get_sys_num(Original) != sc::nr::RECVMSG {
+        return Ok(());
+    }
+    let msghdr_address = tracee.regs.get(Original, SysArg(SysArg2));
+    let copy_address = tracee.regs.get(Modified, SysArg(SysArg2));
+    // Nothing was replaced in `enter()`, or the kernel reported an error.
+    if copy_address == msghdr_address || (tracee.regs.get(Current, SysResult) as isize) < 0 {
+        return Ok(());
+    }
+    let copy = read_msghdr(tracee, copy_address)?;
+    let mut header = read_msghdr(tracee, msghdr_address)?;
+
+    let mut address = match copy.msg_namelen {
+        0 => vec![],
+        size => tracee.regs.read_data(copy.msg_name, size as usize)?,
+    };
+    if let Some(host_path) = unix_path(&address) {
+        let guest_address = tracee
+            .fs
+            .borrow()
+            .detranslate_path(host_path, None)?
+            .and_then(|guest_path| unix_address(&guest_path));
+        if let Some(guest_address) = guest_address {
+            address = guest_address;
+        }
+    }
+
+    // As the kernel does, the address is truncated to the size of the buffer
+    // of the tracee, and `msg_namelen` is its actual size.
+    let size = address.len().min(header.msg_namelen as usize);
+    if size > 0 {
+        tracee
+            .regs
+            .write_data(header.msg_name, &address[..size], false)?;
+    }
+    header.msg_namelen = address.len() as socklen_t;
+    header.msg_controllen = copy.msg_controllen;
+    header.msg_flags = copy.msg_flags;
+    write_msghdr(tracee, msghdr_address, &header)
+}
+
+fn read_msghdr(tracee: &Tracee, address: Word) -> Result<msghdr> {
+    let bytes = tracee
+        .regs
+        .read_data(address as *const c_void, size_of::<msghdr>())?;
+    Ok(unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const _) })
+}
+
+fn write_msghdr(tracee: &Tracee, address: Word, header: &msghdr) -> Result<()> {
+    let bytes = unsafe {
+        std::slice::from_raw_parts(header as *const msghdr as *const u8, size_of::<msghdr>())
+    };
+    tracee.regs.write_data(address as *mut c_void, bytes, false)
+}
+
+/// Returns the path of the unix socket `address`, or `None` if it is not a
+/// unix socket or has no path (i.e. if it is unnamed or abstract).
+fn unix_path(address: &[u8]) -> Option<&Path> {
+    if address.len() <= SUN_PATH_OFFSET
+        || sa_family_t::from_ne_bytes([address[0], address[1]]) != libc::AF_UNIX as sa_family_t
+    {
+        return None;
+    }
+    // `split()` yields at least one (possibly empty) slice.
+    let path = address[SUN_PATH_OFFSET..].split(|&byte| byte == 0).next()?;
+    if path.is_empty() {
+        return None;
+    }
+    Some(Path::new(OsStr::from_bytes(path)))
+}
+
+/// Returns the address of the unix socket at `path`, null-terminated, or
+/// `None` if `path` does not fit in `sun_path`.
+fn unix_address(path: &Path) -> Option<Vec<u8>> {
+    let path = path.as_os_str().as_bytes();
+    if path.len() >= size_of::<sockaddr_un>() - SUN_PATH_OFFSET {
+        return None;
+    }
+    let mut address = Vec::with_capacity(SUN_PATH_OFFSET + path.len() + 1);
+    address.extend_from_slice(&(libc::AF_UNIX as sa_family_t).to_ne_bytes());
+    address.extend_from_slice(path);
+    address.push(0);
+    Some(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::{IoSlice, IoSliceMut};
+    use std::path::PathBuf;
+
+    use nix::fcntl::{open, openat, readlink, OFlag};
+    use nix::sys::socket::{bind, recvmsg, sendmsg, socket, AddressFamily, SockFlag, SockType};
+    use nix::sys::socket::{ControlMessage, ControlMessageOwned, MsgFlags, UnixAddr};
+    use nix::sys::stat::Mode;
+    use nix::unistd::{close, read};
+
+    use super::*;
+    use crate::filesystem::temp::TempDir;
+    use crate::utils::tests::test_with_proot_and_fs;
+
+    #[test]
+    fn test_unix_path_and_address() {
+        let address = unix_address(Path::new("/tmp/socket")).unwrap();
+        assert_eq!(address.len(), SUN_PATH_OFFSET + "/tmp/socket".len() + 1);
+        assert_eq!(unix_path(&address), Some(Path::new("/tmp/socket")));
+        // Unnamed and abstract.
+        assert_eq!(unix_path(&address[..SUN_PATH_OFFSET]), None);
+        let mut abstract_address = address.clone();
+        abstract_address[SUN_PATH_OFFSET] = 0;
+        assert_eq!(unix_path(&abstract_address), None);
+        // Too long for `sun_path`.
+        assert_eq!(unix_address(Path::new(&"/".repeat(108))), None);
+    }
+
+    #[test]
+    fn test_send_recv_msg_unix_address_and_fd() {
+        // Created out of the subprocess of the test, so that it is deleted.
+        let dir = TempDir::new("test_send_recv_msg_unix_address_and_fd");
+        dir.create_dir().unwrap();
+        fs::write(dir.path.join("file"), b"passed").unwrap();
+        let host_dir = dir.path.clone();
+        test_with_proot_and_fs(
+            |fs| fs.add_binding(&dir.path, "/home").unwrap(),
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                // The sockets are bound to the host paths of the binding,
+                // which are the same paths in the guest.
+                let receiver = socket(
+                    AddressFamily::Unix,
+                    SockType::Datagram,
+                    SockFlag::empty(),
+                    None,
+                )
+                .unwrap();
+                bind(
+                    receiver,
+                    &UnixAddr::new(&host_dir.join("receiver")).unwrap(),
+                )
+                .unwrap();
+                let sender = socket(
+                    AddressFamily::Unix,
+                    SockType::Datagram,
+                    SockFlag::empty(),
+                    None,
+                )
+                .unwrap();
+                bind(sender, &UnixAddr::new(&host_dir.join("sender")).unwrap()).unwrap();
+
+                // Sent to the guest path of the receiver, with the fd of a
+                // directory of the guest.
+                let dir_fd =
+                    open("/home", OFlag::O_RDONLY | OFlag::O_DIRECTORY, Mode::empty()).unwrap();
+                assert_eq!(
+                    sendmsg(
+                        sender,
+                        &[IoSlice::new(b"hello")],
+                        &[ControlMessage::ScmRights(&[dir_fd])],
+                        MsgFlags::empty(),
+                        Some(&UnixAddr::new("/home/receiver").unwrap()),
+                    ),
+                    Ok(5)
+                );
+                close(dir_fd).unwrap();
+
+                let mut buffer = [0u8; 16];
+                let mut cmsg_buffer = nix::cmsg_space!([std::os::unix::io::RawFd; 1]);
+                let message = recvmsg::<UnixAddr>(
+                    receiver,
+                    &mut [IoSliceMut::new(&mut buffer)],
+                    Some(&mut cmsg_buffer),
+                    MsgFlags::empty(),
+                )
+                .unwrap();
+                assert_eq!(message.bytes, 5);
+                // The sender is reported with its guest path.
+                assert_eq!(
+                    message.address.as_ref().and_then(UnixAddr::path),
+                    Some(Path::new("/home/sender"))
+                );
+                let received_fd = match message.cmsgs().next() {
+                    Some(ControlMessageOwned::ScmRights(fds)) => fds[0],
+                    other => panic!("unexpected control message: {:?}", other),
+                };
+                assert_eq!(&buffer[..5], b"hello");
+
+                // The paths relative to the received fd are translated too.
+                assert_eq!(
+                    readlink(format!("/proc/self/fd/{}", received_fd).as_str()).map(PathBuf::from),
+                    Ok(PathBuf::from("/home"))
+                );
+                let file_fd = openat(received_fd, "file", OFlag::O_RDONLY, Mode::empty()).unwrap();
+                let mut content = [0u8; 6];
+                assert_eq!(read(file_fd, &mut content), Ok(6));
+                assert_eq!(&content, b"passed");
+
+                for fd in &[file_fd, received_fd, sender, receiver] {
+                    close(*fd).unwrap();
+                }
+            },
+        )
+    }
+}
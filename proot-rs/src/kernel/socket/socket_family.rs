@@ -0,0 +1,160 @@
+use libc::c_int;
+
+use crate::errors::*;
+use crate::process::proot::InfoBag;
+use crate::process::tracee::Tracee;
+use crate::register::{Current, SysArg, SysArg1};
+
+/// The names of the address families accepted by `--socket-families`.
+const FAMILY_NAMES: &[(&str, c_int)] = &[
+    ("unix", libc::AF_UNIX),
+    ("inet", libc::AF_INET),
+    ("inet6", libc::AF_INET6),
+    ("netlink", libc::AF_NETLINK),
+    ("packet", libc::AF_PACKET),
+    ("bluetooth", libc::AF_BLUETOOTH),
+    ("can", libc::AF_CAN),
+    ("alg", libc::AF_ALG),
+    ("vsock", libc::AF_VSOCK),
+];
+
+/// `socket()` and `socketpair()` are forwarded as is, unless the
+/// `--socket-families` option is used.
+///
+/// In that case, the sockets of the other address families (e.g. `AF_PACKET`,
+/// which sees all the traffic of the host, or `AF_NETLINK`) are rejected with
+/// `EAFNOSUPPORT`, as if the kernel did not support them.
+///
+/// `socketcall()` on x86 is filtered the same way, see `socketcall::enter()`.
+pub fn enter(tracee: &Tracee, info_bag: &InfoBag) -> Result<()> {
+    let family = tracee.regs.get(Current, SysArg(SysArg1)) as c_int;
+    check_family(info_bag, family)
+}
+
+/// Returns `EAFNOSUPPORT` if the sockets of the address family `family` are
+/// not allowed by `--socket-families`.
+pub fn check_family(info_bag: &InfoBag, family: c_int) -> Result<()> {
+    let families = match info_bag.socket_families {
+        Some(ref families) => families,
+        None => return Ok(()),
+    };
+    if families.contains(&family) {
+        return Ok(());
+    }
+    Err(Error::errno_with_msg(
+        EAFNOSUPPORT,
+        format!(
+            "socket of the address family {} is not allowed by --socket-families",
+            family
+        ),
+    ))
+}
+
+/// Parses a comma-separated list of address families (e.g. `unix,inet`), by
+/// name or by number.
+pub fn parse_socket_families(list: &str) -> Result<Vec<c_int>> {
+    list.split(',')
+        .map(|family| {
+            let family = family.trim();
+            FAMILY_NAMES
+                .iter()
+                .find(|(name, _)| *name == family)
+                .map(|(_, number)| *number)
+                .or_else(|| family.parse::<c_int>().ok().filter(|number| *number >= 0))
+                .ok_or_else(|| {
+                    Error::errno_with_msg(EINVAL, format!("unknown address family {:?}", family))
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use nix::sys::socket::{socket, AddressFamily, SockFlag, SockProtocol, SockType};
+    use nix::unistd::close;
+
+    use super::*;
+    use crate::utils::tests::test_with_proot_and_info_bag;
+
+    #[test]
+    fn test_parse_socket_families() {
+        assert_eq!(
+            parse_socket_families("unix, inet,10"),
+            Ok(vec![libc::AF_UNIX, libc::AF_INET, libc::AF_INET6])
+        );
+        assert!(parse_socket_families("").is_err());
+        assert!(parse_socket_families("unix,ipx").is_err());
+        assert!(parse_socket_families("-1").is_err());
+    }
+
+    #[test]
+    fn test_socket_families_restricted() {
+        test_with_proot_and_info_bag(
+            |info_bag| info_bag.socket_families = Some(vec![libc::AF_UNIX, libc::AF_INET]),
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let fd = socket(
+                    AddressFamily::Unix,
+                    SockType::Stream,
+                    SockFlag::empty(),
+                    None,
+                )
+                .unwrap();
+                close(fd).unwrap();
+
+                assert_eq!(
+                    socket(
+                        AddressFamily::Netlink,
+                        SockType::Raw,
+                        SockFlag::empty(),
+                        SockProtocol::NetlinkRoute,
+                    ),
+                    Err(Errno::EAFNOSUPPORT)
+                );
+                assert_eq!(
+                    nix::sys::socket::socketpair(
+                        AddressFamily::Inet6,
+                        SockType::Stream,
+                        None,
+                        SockFlag::empty(),
+                    ),
+                    Err(Errno::EAFNOSUPPORT)
+                );
+            },
+        )
+    }
+
+    #[cfg(target_arch = "x86")]
+    #[test]
+    fn test_socket_families_restricted_socketcall() {
+        test_with_proot_and_info_bag(
+            |info_bag| info_bag.socket_families = Some(vec![libc::AF_UNIX]),
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let socketcall = |call: libc::c_long, args: &[libc::c_ulong]| unsafe {
+                    libc::syscall(libc::SYS_socketcall, call, args.as_ptr())
+                };
+                // SYS_SOCKET
+                let fd = socketcall(1, &[libc::AF_UNIX as _, libc::SOCK_STREAM as _, 0]);
+                assert!(fd >= 0);
+                close(fd as _).unwrap();
+                assert_eq!(
+                    socketcall(1, &[libc::AF_INET6 as _, libc::SOCK_STREAM as _, 0]),
+                    -1
+                );
+                assert_eq!(Errno::last(), Errno::EAFNOSUPPORT);
+                // SYS_SOCKETPAIR
+                let mut fds = [0 as libc::c_int; 2];
+                let fds_address = fds.as_mut_ptr() as libc::c_ulong;
+                assert_eq!(
+                    socketcall(
+                        8,
+                        &[libc::AF_INET as _, libc::SOCK_STREAM as _, 0, fds_address]
+                    ),
+                    -1
+                );
+                assert_eq!(Errno::last(), Errno::EAFNOSUPPORT);
+            },
+        )
+    }
+}
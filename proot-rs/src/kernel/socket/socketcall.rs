@@ -1,6 +1,32 @@
+use std::convert::TryInto;
+use std::mem::size_of;
+
+use libc::{c_int, c_void};
+
 use crate::errors::Result;
+use crate::kernel::socket::socket_family;
+use crate::process::proot::InfoBag;
+use crate::process::tracee::Tracee;
+use crate::register::{Current, PtraceReader, SysArg, SysArg1, SysArg2, Word};
+
+/// Numbers of the calls of `socketcall()`, see `linux/net.h`.
+const SYS_SOCKET: Word = 1;
+const SYS_SOCKETPAIR: Word = 8;
 
-pub fn enter() -> Result<()> {
+/// `socketcall(call, args)` is the single entry point of the socket syscalls
+/// on x86, whose arguments are in the array `args`. Only the address family of
+/// `SYS_SOCKET` and `SYS_SOCKETPAIR` is checked for now, like the one of
+/// their direct syscalls (see `socket_family`).
+pub fn enter(tracee: &Tracee, info_bag: &InfoBag) -> Result<()> {
+    let call = tracee.regs.get(Current, SysArg(SysArg1));
+    if info_bag.socket_families.is_some() && (call == SYS_SOCKET || call == SYS_SOCKETPAIR) {
+        let args = tracee.regs.get(Current, SysArg(SysArg2));
+        let bytes = tracee
+            .regs
+            .read_data(args as *const c_void, size_of::<Word>())?;
+        let family = Word::from_ne_bytes(bytes[..size_of::<Word>()].try_into().unwrap());
+        socket_family::check_family(info_bag, family as c_int)?;
+    }
     Ok(())
 
     //    word_t args_addr;
@@ -1,26 +1,28 @@
+use std::os::unix::io::RawFd;
 use std::path::PathBuf;
 
+use nix::sys::stat::{stat, SFlag};
+
 use crate::errors::*;
 use crate::filesystem::binding::Side;
+use crate::filesystem::Translator;
 use crate::process::tracee::Tracee;
 use crate::register::{Current, PtraceReader, SysArg, SysArg1, SysResult, Word};
 
 pub fn enter(tracee: &mut Tracee) -> Result<()> {
     let sys_num = tracee.regs.get_sys_num(Current);
-    let absolute_guest_path = if sys_num == sc::nr::CHDIR {
+    // The path is translated once, and both the guest and the host paths are
+    // given to the `FileSystem`.
+    let (canonical_guest_path, host_path) = if sys_num == sc::nr::CHDIR {
         let path = tracee.regs.get_sysarg_path(SysArg1)?;
-        if path.is_relative() {
-            let mut guest_path = PathBuf::from(tracee.fs.borrow().get_cwd());
-            guest_path.push(path);
-            guest_path
-        } else {
-            path
-        }
+        tracee.translate_path_at(libc::AT_FDCWD, path, true)?
     } else if sys_num == sc::nr::FCHDIR {
-        tracee.get_path_from_fd(
-            tracee.regs.get(Current, SysArg(SysArg1)) as i32,
-            Side::Guest,
-        )?
+        let guest_path =
+            get_dir_path_from_fd(tracee, tracee.regs.get(Current, SysArg(SysArg1)) as i32)?;
+        tracee
+            .fs
+            .borrow()
+            .translate_absolute_path(guest_path, true)?
     } else {
         // This check prevents us from incorrectly handling system calls other than
         // `CHDIR` and `FCHDIR`.
@@ -35,7 +37,10 @@ pub fn enter(tracee: &mut Tracee) -> Result<()> {
         ))?
     };
 
-    tracee.fs.borrow_mut().set_cwd(absolute_guest_path)?;
+    tracee
+        .fs
+        .borrow_mut()
+        .set_translated_cwd(canonical_guest_path, &host_path)?;
 
     // Avoid this syscall
     tracee
@@ -45,6 +50,28 @@ pub fn enter(tracee: &mut Tracee) -> Result<()> {
     Ok(())
 }
 
+/// Returns the guest path of the directory referenced by `fd`, with the same
+/// errors as `fchdir()`: `EBADF` if `fd` is not open, and `ENOTDIR` if it does
+/// not refer to a directory (including pipes and sockets).
+fn get_dir_path_from_fd(tracee: &Tracee, fd: RawFd) -> Result<PathBuf> {
+    // `AT_FDCWD` is not a valid fd for `fchdir()`.
+    if fd < 0 {
+        return Err(Error::errno_with_msg(EBADF, format!("invalid fd {}", fd)));
+    }
+    let proc_fd = format!("/proc/{}/fd/{}", tracee.pid, fd);
+    let file_stat = stat(proc_fd.as_str()).map_err(|errno| match errno {
+        ENOENT => Error::errno_with_msg(EBADF, format!("fd {} is not open", fd)),
+        errno => Error::errno(errno),
+    })?;
+    if SFlag::from_bits_truncate(file_stat.st_mode) & SFlag::S_IFMT != SFlag::S_IFDIR {
+        return Err(Error::errno_with_msg(
+            ENOTDIR,
+            format!("fd {} does not refer to a directory", fd),
+        ));
+    }
+    tracee.get_path_from_fd(fd, Side::Guest)
+}
+
 pub fn exit(tracee: &mut Tracee) -> Result<()> {
     // This syscall is fully emulated, see method `enter()` above.
     tracee
@@ -57,6 +84,8 @@ pub fn exit(tracee: &mut Tracee) -> Result<()> {
 mod tests {
     use super::*;
     use crate::utils::tests::test_with_proot;
+    use nix::fcntl::OFlag;
+    use nix::sys::stat::Mode;
 
     #[test]
     fn test_chdir_fchdir_and_getcwd() {
@@ -109,6 +138,8 @@ mod tests {
                 // test chdir with parent dir
                 assert_with_chdir!("../../../../", Ok(()), Ok("/"));
                 assert_with_chdir!("../etc", Ok(()), Ok("/etc"));
+                assert_with_chdir!("/etc/../tmp", Ok(()), Ok("/tmp"));
+                assert_with_chdir!("../etc", Ok(()), Ok("/etc"));
                 // test chdir with not exist
                 assert_with_chdir!(
                     "./impossible_path",
@@ -148,4 +179,37 @@ mod tests {
             },
         )
     }
+
+    #[test]
+    fn test_fchdir_and_relative_path_resolution() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let dir_fd =
+                    nix::fcntl::open("/etc", OFlag::O_RDONLY | OFlag::O_DIRECTORY, Mode::empty())
+                        .unwrap();
+                assert_eq!(nix::unistd::fchdir(dir_fd), Ok(()));
+                assert_eq!(nix::unistd::getcwd(), Ok("/etc".into()));
+                // relative paths are resolved from the new cwd
+                assert_eq!(
+                    std::fs::read("passwd").unwrap(),
+                    std::fs::read("/etc/passwd").unwrap()
+                );
+
+                // fds which do not refer to a directory
+                let file_fd = nix::fcntl::open("passwd", OFlag::O_RDONLY, Mode::empty()).unwrap();
+                assert_eq!(nix::unistd::fchdir(file_fd), Err(Errno::ENOTDIR));
+                let (read_fd, write_fd) = nix::unistd::pipe().unwrap();
+                assert_eq!(nix::unistd::fchdir(read_fd), Err(Errno::ENOTDIR));
+                // fds which are not open
+                nix::unistd::close(write_fd).unwrap();
+                assert_eq!(nix::unistd::fchdir(write_fd), Err(Errno::EBADF));
+                assert_eq!(nix::unistd::fchdir(libc::AT_FDCWD), Err(Errno::EBADF));
+
+                // the cwd is not changed by the failed calls
+                assert_eq!(nix::unistd::getcwd(), Ok("/etc".into()));
+                assert!(std::fs::metadata("passwd").is_ok());
+            },
+        )
+    }
 }
@@ -1,20 +1,44 @@
 use std::os::unix::prelude::RawFd;
 
+use nix::fcntl::AtFlags;
+
 use crate::errors::*;
 use crate::process::tracee::Tracee;
 use crate::register::PtraceWriter;
-use crate::register::{Current, PtraceReader, SysArg, SysArg1, SysArg2};
+use crate::register::{Current, PtraceReader, SysArg, SysArg1, SysArg2, SysArg4};
+
+/// Number of `faccessat2()` (Linux 5.8), the same on all architectures, not
+/// defined by the sc crate yet.
+pub const FACCESSAT2: usize = 439;
+
+/// Number of `fchmodat2()` (Linux 6.6), the same on all architectures, not
+/// defined by the sc crate yet.
+pub const FCHMODAT2: usize = 452;
 
 pub fn enter(tracee: &mut Tracee) -> Result<()> {
     let sys_num = tracee.regs.get_sys_num(Current);
     let dirfd = tracee.regs.get(Current, SysArg(SysArg1)) as RawFd;
     let raw_path = tracee.regs.get_sysarg_path(SysArg2)?;
 
+    // Unlike `faccessat()` and `fchmodat()`, `faccessat2()` and `fchmodat2()`
+    // take flags.
+    let flags = if sys_num == FACCESSAT2 || sys_num == FCHMODAT2 {
+        AtFlags::from_bits_truncate(tracee.regs.get(Current, SysArg(SysArg4)) as _)
+    } else {
+        AtFlags::empty()
+    };
+    // With `AT_EMPTY_PATH`, `faccessat2()` and `fchmodat2()` check `dirfd`
+    // itself.
+    if Tracee::is_empty_path_of_fd(dirfd, &raw_path, flags.contains(AtFlags::AT_EMPTY_PATH)) {
+        return Ok(());
+    }
+
     let deref_final = match sys_num {
         sc::nr::MKNODAT => false, /* By default, mknodat() will not follow a symbolic link. https://man7.org/linux/man-pages/man2/mknod.2.html */
         #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "arm"))]
         sc::nr::FUTIMESAT => true,
         sc::nr::FACCESSAT | sc::nr::FCHMODAT => true,
+        FACCESSAT2 | FCHMODAT2 => !flags.contains(AtFlags::AT_SYMLINK_NOFOLLOW),
         _ => true,
     };
 
@@ -35,6 +59,8 @@ mod tests {
 
     use nix::{fcntl::OFlag, sys::stat::Mode};
 
+    use super::*;
+    use crate::filesystem::temp::TempDir;
     use crate::utils::tests::test_with_proot;
 
     /// Unit test for the following syscalls:
@@ -125,4 +151,92 @@ mod tests {
             },
         )
     }
+
+    #[test]
+    fn test_faccessat2() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let faccessat2 = |dirfd: RawFd, path: &str, mode: i32, flags: i32| {
+                    let path = std::ffi::CString::new(path).unwrap();
+                    let result = unsafe {
+                        libc::syscall(FACCESSAT2 as _, dirfd, path.as_ptr(), mode, flags)
+                    };
+                    Errno::result(result).map(|_| ())
+                };
+                // faccessat2() needs Linux 5.8.
+                if faccessat2(libc::AT_FDCWD, "/", libc::F_OK, 0) == Err(Errno::ENOSYS) {
+                    return;
+                }
+
+                // A path is translated as with faccessat().
+                assert_eq!(
+                    faccessat2(libc::AT_FDCWD, "/etc/passwd", libc::R_OK, 0),
+                    Ok(())
+                );
+                assert_eq!(
+                    faccessat2(libc::AT_FDCWD, "/impossible_path", libc::F_OK, 0),
+                    Err(Errno::ENOENT)
+                );
+
+                // With AT_EMPTY_PATH, the fd itself is checked, even if it has no path.
+                let fd = nix::fcntl::open("/etc/passwd", OFlag::O_RDONLY, Mode::empty()).unwrap();
+                assert_eq!(faccessat2(fd, "", libc::R_OK, libc::AT_EMPTY_PATH), Ok(()));
+                let (read_fd, _write_fd) = nix::unistd::pipe().unwrap();
+                assert_eq!(
+                    faccessat2(read_fd, "", libc::R_OK, libc::AT_EMPTY_PATH),
+                    Ok(())
+                );
+                // Without AT_EMPTY_PATH, an empty path is an error.
+                assert_eq!(faccessat2(fd, "", libc::R_OK, 0), Err(Errno::ENOENT));
+            },
+        )
+    }
+
+    #[test]
+    fn test_fchmodat2() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let fchmodat2 = |path: &str, mode: u32, flags: i32| {
+                    let path = std::ffi::CString::new(path).unwrap();
+                    let result = unsafe {
+                        libc::syscall(FCHMODAT2 as _, libc::AT_FDCWD, path.as_ptr(), mode, flags)
+                    };
+                    Errno::result(result).map(|_| ())
+                };
+                // fchmodat2() needs Linux 6.6. Invalid flags are rejected
+                // before the file is changed.
+                if fchmodat2("/", 0o755, -1) == Err(Errno::ENOSYS) {
+                    return;
+                }
+
+                let dir = TempDir::new("prefix_test_fchmodat2");
+                dir.create_dir().unwrap();
+                let filepath = dir.path.join("file");
+                let filepath = filepath.to_str().unwrap();
+                let linkpath = dir.path.join("link");
+                let linkpath = linkpath.to_str().unwrap();
+                let mode_of = |path: &str| std::fs::metadata(path).unwrap().permissions().mode();
+
+                std::fs::File::create(filepath).unwrap();
+                std::os::unix::fs::symlink(filepath, linkpath).unwrap();
+
+                // Without flags, the target of the symlink is changed.
+                fchmodat2(linkpath, 0o600, 0).unwrap();
+                assert_eq!(mode_of(filepath) & 0o777, 0o600);
+
+                // With AT_SYMLINK_NOFOLLOW, the symlink itself, whose mode
+                // cannot be changed on Linux.
+                assert_eq!(
+                    fchmodat2(linkpath, 0o700, libc::AT_SYMLINK_NOFOLLOW),
+                    Err(Errno::EOPNOTSUPP)
+                );
+                assert_eq!(mode_of(filepath) & 0o777, 0o600);
+                // But a regular file is changed as usual.
+                fchmodat2(filepath, 0o700, libc::AT_SYMLINK_NOFOLLOW).unwrap();
+                assert_eq!(mode_of(filepath) & 0o777, 0o700);
+            },
+        )
+    }
 }
@@ -0,0 +1,259 @@
+use crate::errors::*;
+use crate::kernel::syscall::name_of_syscall;
+use crate::process::proot::InfoBag;
+use crate::process::tracee::Tracee;
+use crate::register::Current;
+
+/// A syscall which is rejected by default, since proot-rs cannot confine it to
+/// the guest, unless it is allowed by an option.
+pub struct DeniedSyscall {
+    pub sys_num: usize,
+    /// The errno returned to the tracee when the syscall is rejected.
+    pub errno: Errno,
+    /// The option which allows the syscall.
+    pub option: &'static str,
+    /// Returns the flag of `InfoBag` which is set by `option`.
+    pub is_allowed: fn(&InfoBag) -> bool,
+}
+
+/// The syscalls of the `Denied` group:
+///
+/// - The operations submitted through an io_uring (e.g. `IORING_OP_OPENAT` or
+///   `IORING_OP_STATX`) are performed by the kernel without any syscall that
+///   proot-rs could intercept, so their paths would not be translated and the
+///   guest rootfs could be escaped. The `io_uring_*()` syscalls fail with
+///   `ENOSYS`, as on kernels without io_uring: programs then fall back to the
+///   regular syscalls.
+/// - `bpf()` and `perf_event_open()` give access to programs and counters which
+///   observe the whole host (e.g. kprobes or tracepoints) rather than the
+///   sandbox, so they fail with `EPERM`, as for an unprivileged user.
+/// - The kernel keyrings are not isolated by proot-rs: the session and user
+///   keyrings of the guest are the ones of the host user, so the keys they hold
+///   (e.g. credentials of network filesystems) could be read or replaced. The
+///   keyring syscalls fail with `EPERM`.
+/// - proot-rs does not manage namespaces: a tracee which joins another
+///   namespace (e.g. the mount namespace of a process of the host, through its
+///   `/proc/<pid>/ns/mnt`) leaves the view of the files that proot-rs
+///   translates, while the paths are still resolved as if it had not moved.
+///   `setns()` fails with `EPERM`.
+pub const DENIED_SYSCALLS: &[DeniedSyscall] = &[
+    DeniedSyscall {
+        sys_num: sc::nr::IO_URING_SETUP,
+        errno: ENOSYS,
+        option: "--allow-io-uring",
+        is_allowed: |info_bag| info_bag.allow_io_uring,
+    },
+    DeniedSyscall {
+        sys_num: sc::nr::IO_URING_ENTER,
+        errno: ENOSYS,
+        option: "--allow-io-uring",
+        is_allowed: |info_bag| info_bag.allow_io_uring,
+    },
+    DeniedSyscall {
+        sys_num: sc::nr::IO_URING_REGISTER,
+        errno: ENOSYS,
+        option: "--allow-io-uring",
+        is_allowed: |info_bag| info_bag.allow_io_uring,
+    },
+    DeniedSyscall {
+        sys_num: sc::nr::BPF,
+        errno: EPERM,
+        option: "--allow-bpf",
+        is_allowed: |info_bag| info_bag.allow_bpf,
+    },
+    DeniedSyscall {
+        sys_num: sc::nr::PERF_EVENT_OPEN,
+        errno: EPERM,
+        option: "--allow-perf",
+        is_allowed: |info_bag| info_bag.allow_perf,
+    },
+    DeniedSyscall {
+        sys_num: sc::nr::ADD_KEY,
+        errno: EPERM,
+        option: "--allow-keyring",
+        is_allowed: |info_bag| info_bag.allow_keyring,
+    },
+    DeniedSyscall {
+        sys_num: sc::nr::REQUEST_KEY,
+        errno: EPERM,
+        option: "--allow-keyring",
+        is_allowed: |info_bag| info_bag.allow_keyring,
+    },
+    DeniedSyscall {
+        sys_num: sc::nr::KEYCTL,
+        errno: EPERM,
+        option: "--allow-keyring",
+        is_allowed: |info_bag| info_bag.allow_keyring,
+    },
+    DeniedSyscall {
+        sys_num: sc::nr::SETNS,
+        errno: EPERM,
+        option: "--allow-setns",
+        is_allowed: |info_bag| info_bag.allow_setns,
+    },
+];
+
+/// Returns the entry of `DENIED_SYSCALLS` for the syscall `sys_num`, if any.
+pub fn denied_syscall(sys_num: usize) -> Option<&'static DeniedSyscall> {
+    DENIED_SYSCALLS
+        .iter()
+        .find(|denied| denied.sys_num == sys_num)
+}
+
+/// The syscalls of `DENIED_SYSCALLS` fail with their errno, unless their
+/// option is used, in which case they are forwarded as is.
+pub fn enter(tracee: &Tracee, info_bag: &InfoBag) -> Result<()> {
+    let sys_num = tracee.regs.get_sys_num(Current);
+    let denied = match denied_syscall(sys_num) {
+        Some(denied) => denied,
+        None => return Ok(()),
+    };
+    if (denied.is_allowed)(info_bag) {
+        return Ok(());
+    }
+    Err(Error::errno_with_msg(
+        denied.errno,
+        format!(
+            "{}() is disabled in the sandbox, see {}",
+            name_of_syscall(sys_num).unwrap_or("unknown"),
+            denied.option
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use nix::fcntl::{self, OFlag};
+    use nix::sys::stat::Mode;
+    use nix::unistd;
+
+    use super::*;
+    use crate::utils::tests::test_with_proot;
+
+    /// `KEYCTL_GET_KEYRING_ID` in `linux/keyctl.h`.
+    const KEYCTL_GET_KEYRING_ID: libc::c_long = 0;
+    /// `KEY_SPEC_SESSION_KEYRING` in `linux/keyctl.h`.
+    const KEY_SPEC_SESSION_KEYRING: libc::c_long = -3;
+
+    #[test]
+    fn test_denied_io_uring_setup() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                // `struct io_uring_params` is 120 bytes long, and must be zeroed.
+                let mut params = [0u8; 120];
+                assert_eq!(
+                    unsafe { libc::syscall(libc::SYS_io_uring_setup, 1, params.as_mut_ptr()) },
+                    -1
+                );
+                assert_eq!(Errno::last(), ENOSYS);
+            },
+        )
+    }
+
+    #[test]
+    fn test_denied_bpf_and_perf_event_open() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                // `BPF_PROG_LOAD` with an empty `union bpf_attr`
+                let mut attr = [0u8; 128];
+                assert_eq!(
+                    unsafe { libc::syscall(libc::SYS_bpf, 5, attr.as_mut_ptr(), attr.len()) },
+                    -1
+                );
+                assert_eq!(Errno::last(), EPERM);
+
+                // a software counter of the current process, on any cpu
+                let mut perf_event_attr = [0u8; 128];
+                perf_event_attr[4..8]
+                    .copy_from_slice(&(perf_event_attr.len() as u32).to_ne_bytes());
+                assert_eq!(
+                    unsafe {
+                        libc::syscall(
+                            libc::SYS_perf_event_open,
+                            perf_event_attr.as_mut_ptr(),
+                            0,
+                            -1,
+                            -1,
+                            0,
+                        )
+                    },
+                    -1
+                );
+                assert_eq!(Errno::last(), EPERM);
+            },
+        )
+    }
+
+    #[test]
+    fn test_denied_keyring() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let key_type = CString::new("user").unwrap();
+                let description = CString::new("key_for_test_keyring").unwrap();
+                let payload = b"secret";
+
+                assert_eq!(
+                    unsafe {
+                        libc::syscall(
+                            libc::SYS_add_key,
+                            key_type.as_ptr(),
+                            description.as_ptr(),
+                            payload.as_ptr(),
+                            payload.len(),
+                            KEY_SPEC_SESSION_KEYRING,
+                        )
+                    },
+                    -1
+                );
+                assert_eq!(Errno::last(), EPERM);
+
+                assert_eq!(
+                    unsafe {
+                        libc::syscall(
+                            libc::SYS_request_key,
+                            key_type.as_ptr(),
+                            description.as_ptr(),
+                            std::ptr::null::<libc::c_char>(),
+                            0,
+                        )
+                    },
+                    -1
+                );
+                assert_eq!(Errno::last(), EPERM);
+
+                assert_eq!(
+                    unsafe {
+                        libc::syscall(
+                            libc::SYS_keyctl,
+                            KEYCTL_GET_KEYRING_ID,
+                            KEY_SPEC_SESSION_KEYRING,
+                            0,
+                        )
+                    },
+                    -1
+                );
+                assert_eq!(Errno::last(), EPERM);
+            },
+        )
+    }
+
+    #[test]
+    fn test_denied_setns() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                // Joining its own user namespace would fail with `EINVAL` if
+                // the syscall was forwarded.
+                let fd = fcntl::open("/proc/self/ns/user", OFlag::O_RDONLY, Mode::empty()).unwrap();
+                assert_eq!(unsafe { libc::setns(fd, 0) }, -1);
+                assert_eq!(Errno::last(), EPERM);
+                unistd::close(fd).unwrap();
+            },
+        )
+    }
+}
@@ -1,7 +1,6 @@
 use crate::errors::*;
 
 use crate::filesystem::ext::PathExt;
-use crate::filesystem::Translator;
 use crate::process::tracee::Tracee;
 use crate::register::PtraceWriter;
 use crate::register::{Current, PtraceReader, SysArg1};
@@ -25,7 +24,9 @@ pub fn enter(tracee: &mut Tracee) -> Result<()> {
             }
         }
     };
-    let host_path = tracee.fs.borrow().translate_path(raw_path, deref_final)?.1;
+    let host_path = tracee
+        .translate_path_at(libc::AT_FDCWD, raw_path, deref_final)?
+        .1;
 
     tracee.regs.set_sysarg_path(
         SysArg1,
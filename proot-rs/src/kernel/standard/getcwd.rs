@@ -4,7 +4,8 @@ use libc::c_void;
 
 use crate::errors::*;
 
-use crate::filesystem::Translator;
+use crate::filesystem::binding::Side;
+use crate::filesystem::Substitutor;
 use crate::process::tracee::Tracee;
 use crate::register::{Original, SysArg, SysArg1, SysArg2, SysResult};
 use crate::register::{PtraceWriter, Word};
@@ -25,13 +26,9 @@ pub fn exit(tracee: &mut Tracee) -> Result<()> {
 
     let fs_r = tracee.fs.borrow();
     let guest_path = fs_r.get_cwd();
-    // we need to ensure cwd still exists
-    tracee
-        .fs
-        .borrow()
-        .translate_absolute_path(&guest_path, true)?
-        .1
-        .metadata()?;
+    // we need to ensure cwd still exists, the cwd is already a canonical path, so
+    // we can just substitute it.
+    fs_r.substitute(guest_path, Side::Guest)?.metadata()?;
 
     let bytes = guest_path.as_os_str().as_bytes();
     let real_size = bytes.len() + 1;
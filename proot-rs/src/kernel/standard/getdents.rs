@@ -0,0 +1,353 @@
+use std::collections::HashMap;
+use std::fs::FileType;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::io::RawFd;
+use std::path::Path;
+
+use libc::c_void;
+
+use crate::errors::*;
+use crate::filesystem::binding::Side;
+use crate::process::proot::InfoBag;
+use crate::process::tracee::Tracee;
+use crate::register::{Current, Original, PtraceReader, PtraceWriter, SysArg, SysArg1};
+use crate::register::{SysArg2, SysResult, Word};
+
+/// Offset of the `d_reclen` field in `struct linux_dirent64`.
+const D_RECLEN_OFFSET: usize = 16;
+/// Offset of the `d_type` field in `struct linux_dirent64`.
+const D_TYPE_OFFSET: usize = 18;
+/// Offset of the `d_name` field in `struct linux_dirent64`.
+const D_NAME_OFFSET: usize = 19;
+
+/// `getdents64()` is run by the kernel on the directory of the rootfs, so the
+/// `d_type` of an entry which is the guest path of a binding is the one of the
+/// file it hides (e.g. a directory of the rootfs on which a file is bound).
+///
+/// The `d_type` of these entries is replaced with the type of the host path
+/// of the binding, as it would be with a bind mount. The other fields (such as
+/// `d_ino`) are left as is.
+pub fn exit(tracee: &mut Tracee, info_bag: &InfoBag) -> Result<()> {
+    // Error reported by the kernel, or end of the directory.
+    if tracee.regs.get(Current, SysResult) as isize <= 0 {
+        return Ok(());
+    }
+
+    let fd = tracee.regs.get(Original, SysArg(SysArg1)) as RawFd;
+    if info_bag.hide_host_pids && hide_host_pids(tracee, fd)? {
+        return Ok(());
+    }
+
+    let guest_dir = match tracee.get_path_from_fd(fd, Side::Guest) {
+        Ok(guest_dir) => guest_dir,
+        // The entries were listed anyway, do not fail the syscall.
+        Err(error) => {
+            debug!(
+                "-- {}, getdents64() on fd {} not checked for bindings: {}",
+                tracee.pid, fd, error
+            );
+            return Ok(());
+        }
+    };
+
+    // The types of the bindings in the directory, by entry name.
+    let mut binding_types: HashMap<Vec<u8>, u8> = HashMap::new();
+    for binding in tracee.fs.borrow().get_bindings_in_dir(&guest_dir) {
+        let name = match binding.get_path(Side::Guest).file_name() {
+            Some(name) => name.as_bytes().to_vec(),
+            None => continue,
+        };
+        // The most recent binding is the one which is visible.
+        if binding_types.contains_key(&name) {
+            continue;
+        }
+        if let Ok(metadata) = binding.get_path(Side::Host).metadata() {
+            binding_types.insert(name, d_type_of(metadata.file_type()));
+        }
+    }
+    if binding_types.is_empty() {
+        return Ok(());
+    }
+
+    // The size may have been reduced by `hide_host_pids()`.
+    let size = tracee.regs.get(Current, SysResult) as usize;
+    let address = tracee.regs.get(Original, SysArg(SysArg2)) as *mut c_void;
+    let mut entries = tracee.regs.read_data(address, size)?;
+    if fix_d_types(&mut entries, &binding_types) {
+        trace!(
+            "-- {}, d_type of bindings corrected in {:?}",
+            tracee.pid,
+            guest_dir
+        );
+        tracee.regs.write_data(address, &entries, false)?;
+    }
+
+    Ok(())
+}
+
+/// With `--hide-host-pids`, the entries of the host `/proc` which are the pids
+/// of processes not traced by this proot-rs (the other processes of the host,
+/// including the ones of other sandboxes) are removed from the listing. The
+/// pids are not virtualized: the hidden ones are still accessible by path.
+///
+/// An empty result would mean the end of the directory, so when all the
+/// entries of a chunk are hidden (with a small buffer, or a lot of processes),
+/// the syscall is restarted to read the next chunk instead, until an entry is
+/// left or the end of the directory is reached. Returns `true` in this case.
+fn hide_host_pids(tracee: &mut Tracee, fd: RawFd) -> Result<bool> {
+    match tracee.get_path_from_fd(fd, Side::Host) {
+        Ok(ref host_dir) if host_dir == Path::new("/proc") => {}
+        _ => return Ok(false),
+    }
+
+    let size = tracee.regs.get(Current, SysResult) as usize;
+    let address = tracee.regs.get(Original, SysArg(SysArg2)) as *mut c_void;
+    let entries = tracee.regs.read_data(address, size)?;
+    let tracer_pid = std::process::id();
+    let visible_entries = retain_entries(&entries, |name| !is_host_pid(name, tracer_pid));
+    if visible_entries.len() == entries.len() {
+        return Ok(false);
+    }
+    if visible_entries.is_empty() {
+        tracee
+            .regs
+            .restart_syscall("read the next entries in getdents::hide_host_pids()");
+        return Ok(true);
+    }
+
+    tracee.regs.write_data(address, &visible_entries, false)?;
+    tracee.regs.set(
+        SysResult,
+        visible_entries.len() as Word,
+        "update return value in getdents::hide_host_pids()",
+    );
+    Ok(false)
+}
+
+/// Whether the entry `name` of `/proc` is the pid of a process which is not
+/// traced by `tracer_pid`. The processes which are already gone are hidden
+/// too.
+fn is_host_pid(name: &[u8], tracer_pid: u32) -> bool {
+    if name.is_empty() || !name.iter().all(u8::is_ascii_digit) {
+        return false;
+    }
+    let status_path = Path::new("/proc")
+        .join(std::ffi::OsStr::from_bytes(name))
+        .join("status");
+    let status = match std::fs::read_to_string(status_path) {
+        Ok(status) => status,
+        Err(_) => return true,
+    };
+    status
+        .lines()
+        .find(|line| line.starts_with("TracerPid:"))
+        .and_then(|line| line["TracerPid:".len()..].trim().parse::<u32>().ok())
+        != Some(tracer_pid)
+}
+
+/// Returns the entries of the `struct linux_dirent64` array `entries` whose
+/// name satisfies `keep`, in the same order.
+fn retain_entries<F: Fn(&[u8]) -> bool>(entries: &[u8], keep: F) -> Vec<u8> {
+    let mut retained = Vec::with_capacity(entries.len());
+    let mut offset = 0;
+    while offset + D_NAME_OFFSET <= entries.len() {
+        let reclen = entry_reclen(entries, offset);
+        if reclen == 0 || offset + reclen > entries.len() {
+            break;
+        }
+        let entry = &entries[offset..offset + reclen];
+        if keep(entry_name(entry)) {
+            retained.extend_from_slice(entry);
+        }
+        offset += reclen;
+    }
+    retained
+}
+
+/// Returns the `d_reclen` of the entry at `offset` in `entries`.
+fn entry_reclen(entries: &[u8], offset: usize) -> usize {
+    let mut reclen = [0u8; 2];
+    reclen.copy_from_slice(&entries[offset + D_RECLEN_OFFSET..offset + D_TYPE_OFFSET]);
+    u16::from_ne_bytes(reclen) as usize
+}
+
+/// Returns the `d_name` of `entry`, without its null bytes.
+fn entry_name(entry: &[u8]) -> &[u8] {
+    let name = &entry[D_NAME_OFFSET..];
+    &name[..name
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(name.len())]
+}
+
+/// Replaces the `d_type` of the entries of the `struct linux_dirent64` array
+/// `entries` whose name is in `binding_types`. Returns `true` if an entry was
+/// modified.
+fn fix_d_types(entries: &mut [u8], binding_types: &HashMap<Vec<u8>, u8>) -> bool {
+    let mut modified = false;
+    let mut offset = 0;
+    while offset + D_NAME_OFFSET <= entries.len() {
+        let reclen = entry_reclen(entries, offset);
+        if reclen == 0 || offset + reclen > entries.len() {
+            break;
+        }
+
+        let name = entry_name(&entries[offset..offset + reclen]);
+        if let Some(d_type) = binding_types.get(name) {
+            entries[offset + D_TYPE_OFFSET] = *d_type;
+            modified = true;
+        }
+
+        offset += reclen;
+    }
+    modified
+}
+
+/// Returns the `d_type` (`DT_*`) corresponding to `file_type`.
+fn d_type_of(file_type: FileType) -> u8 {
+    if file_type.is_dir() {
+        libc::DT_DIR
+    } else if file_type.is_file() {
+        libc::DT_REG
+    } else if file_type.is_symlink() {
+        libc::DT_LNK
+    } else if file_type.is_block_device() {
+        libc::DT_BLK
+    } else if file_type.is_char_device() {
+        libc::DT_CHR
+    } else if file_type.is_fifo() {
+        libc::DT_FIFO
+    } else if file_type.is_socket() {
+        libc::DT_SOCK
+    } else {
+        libc::DT_UNKNOWN
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+    use std::path::PathBuf;
+
+    use nix::dir::{Dir, Type};
+    use nix::fcntl::OFlag;
+    use nix::sys::stat::Mode;
+
+    use super::*;
+    use crate::utils::tests::{test_with_proot_and_fs, test_with_proot_and_info_bag};
+
+    /// Returns a `struct linux_dirent64` of `name`, padded to 8 bytes.
+    fn dirent(name: &str) -> Vec<u8> {
+        let reclen = (D_NAME_OFFSET + name.len() + 1 + 7) / 8 * 8;
+        let mut entry = vec![0u8; reclen];
+        entry[D_RECLEN_OFFSET..D_TYPE_OFFSET].copy_from_slice(&(reclen as u16).to_ne_bytes());
+        entry[D_TYPE_OFFSET] = libc::DT_DIR;
+        entry[D_NAME_OFFSET..D_NAME_OFFSET + name.len()].copy_from_slice(name.as_bytes());
+        entry
+    }
+
+    #[test]
+    fn test_retain_entries() {
+        let entries = [dirent("."), dirent("1"), dirent("self"), dirent("42")].concat();
+        assert_eq!(
+            retain_entries(&entries, |name| name != b"1" && name != b"42"),
+            [dirent("."), dirent("self")].concat()
+        );
+        assert_eq!(retain_entries(&entries, |_| true), entries);
+        assert!(retain_entries(&entries, |_| false).is_empty());
+    }
+
+    #[test]
+    fn test_getdents64_hide_host_pids() {
+        test_with_proot_and_info_bag(
+            |info_bag| info_bag.hide_host_pids = true,
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let mut dir =
+                    Dir::open("/proc", OFlag::O_RDONLY | OFlag::O_DIRECTORY, Mode::empty())
+                        .unwrap();
+                let names: Vec<String> = dir
+                    .iter()
+                    .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+                    .collect();
+
+                assert!(names.contains(&nix::unistd::getpid().to_string()));
+                // Neither proot-rs (the parent of the tracee) nor the init
+                // process of the host.
+                assert!(!names.contains(&nix::unistd::getppid().to_string()));
+                assert!(!names.contains(&"1".to_string()));
+                // The other entries are still listed.
+                assert!(names.contains(&"self".to_string()));
+            },
+        )
+    }
+
+    #[test]
+    fn test_getdents64_hide_host_pids_small_buffer() {
+        test_with_proot_and_info_bag(
+            |info_bag| info_bag.hide_host_pids = true,
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let fd =
+                    nix::fcntl::open("/proc", OFlag::O_RDONLY | OFlag::O_DIRECTORY, Mode::empty())
+                        .unwrap();
+                // Room for one or two entries, so that most chunks are only
+                // made of host pids.
+                let mut buffer = [0u8; 64];
+                let mut names = vec![];
+                loop {
+                    let size = unsafe {
+                        libc::syscall(libc::SYS_getdents64, fd, buffer.as_mut_ptr(), buffer.len())
+                    };
+                    assert!(size >= 0);
+                    if size == 0 {
+                        break;
+                    }
+                    let mut offset = 0;
+                    while offset < size as usize {
+                        let reclen = entry_reclen(&buffer, offset);
+                        let name = entry_name(&buffer[offset..offset + reclen]);
+                        names.push(String::from_utf8_lossy(name).into_owned());
+                        offset += reclen;
+                    }
+                }
+                nix::unistd::close(fd).unwrap();
+
+                assert!(names.contains(&nix::unistd::getpid().to_string()));
+                assert!(!names.contains(&nix::unistd::getppid().to_string()));
+                assert!(!names.contains(&"1".to_string()));
+                assert!(names.contains(&"self".to_string()));
+            },
+        )
+    }
+
+    #[test]
+    fn test_getdents64_d_type_of_binding() {
+        test_with_proot_and_fs(
+            // `/home` is a directory of the rootfs.
+            |fs| fs.add_binding("/etc/passwd", "/home").unwrap(),
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let mut dir =
+                    Dir::open("/", OFlag::O_RDONLY | OFlag::O_DIRECTORY, Mode::empty()).unwrap();
+                let types: HashMap<PathBuf, Option<Type>> = dir
+                    .iter()
+                    .map(|entry| entry.unwrap())
+                    .map(|entry| {
+                        (
+                            PathBuf::from(OsStr::from_bytes(entry.file_name().to_bytes())),
+                            entry.file_type(),
+                        )
+                    })
+                    .collect();
+
+                assert_eq!(types.get(&PathBuf::from("home")), Some(&Some(Type::File)));
+                assert_eq!(
+                    types.get(&PathBuf::from("etc")),
+                    Some(&Some(Type::Directory))
+                );
+            },
+        )
+    }
+}
@@ -8,6 +8,11 @@ use crate::process::tracee::Tracee;
 use crate::register::PtraceWriter;
 use crate::register::{Current, PtraceReader, SysArg, SysArg1, SysArg2, SysArg3, SysArg4, SysArg5};
 
+/// With `AT_SYMLINK_FOLLOW` (or a trailing slash), a final symlink of the old
+/// path is dereferenced by the translation, since its target is a guest path:
+/// the kernel then creates a hard link to the host path of that target, and
+/// the flag has no effect anymore. Without it, the hard link is to the symlink
+/// itself.
 pub fn enter(tracee: &mut Tracee) -> Result<()> {
     let olddirfd = tracee.regs.get(Current, SysArg(SysArg1)) as RawFd;
     let newdirfd = tracee.regs.get(Current, SysArg(SysArg3)) as RawFd;
@@ -17,17 +22,31 @@ pub fn enter(tracee: &mut Tracee) -> Result<()> {
     let flags = AtFlags::from_bits_truncate(tracee.regs.get(Current, SysArg(SysArg5)) as _);
     let deref_final = flags.contains(AtFlags::AT_SYMLINK_FOLLOW) || old_path.with_trailing_slash();
 
-    let old_host_path = tracee.translate_path_at(olddirfd, old_path, deref_final)?.1;
-    let new_host_path = tracee.translate_path_at(newdirfd, new_path, false)?.1;
+    // With `AT_EMPTY_PATH`, the hard link is to the file of `olddirfd`.
+    if Tracee::is_empty_path_of_fd(olddirfd, &old_path, flags.contains(AtFlags::AT_EMPTY_PATH)) {
+        let new_host_path = tracee.translate_path_at(newdirfd, new_path, false)?.1;
+        tracee.regs.set_sysarg_path(
+            SysArg4,
+            &new_host_path,
+            "during enter linkat translation with AT_EMPTY_PATH, setting new host path",
+        )?;
+        return Ok(());
+    }
+
+    let host_paths = tracee.translate_paths_at(&[
+        (olddirfd, old_path, deref_final),
+        (newdirfd, new_path, false),
+    ])?;
+    let (old_host_path, new_host_path) = (&host_paths[0].1, &host_paths[1].1);
 
     tracee.regs.set_sysarg_path(
         SysArg2,
-        &old_host_path,
+        old_host_path,
         "during enter open translation, setting host path",
     )?;
     tracee.regs.set_sysarg_path(
         SysArg4,
-        &new_host_path,
+        new_host_path,
         "during enter open translation, setting host path",
     )?;
 
@@ -36,11 +55,14 @@ pub fn enter(tracee: &mut Tracee) -> Result<()> {
 
 #[cfg(test)]
 mod tests {
-    use std::fs::File;
+    use std::fs::{self, File};
+    use std::os::unix::fs::MetadataExt;
 
+    use nix::unistd::{linkat, LinkatFlags};
     use nix::{fcntl::OFlag, sys::stat::Mode};
 
-    use crate::utils::tests::test_with_proot;
+    use crate::filesystem::temp::TempDir;
+    use crate::utils::tests::{test_with_proot, test_with_proot_and_fs};
 
     /// Unit test for the following syscalls:
     /// - linkat
@@ -107,4 +129,48 @@ mod tests {
             },
         )
     }
+
+    #[test]
+    fn test_link_at_symlink_follow_guest_target() {
+        // Created out of the subprocess of the test, so that it is deleted.
+        let dir = TempDir::new("prefix_test_link_at_symlink_follow_guest_target");
+        dir.create_dir().unwrap();
+        test_with_proot_and_fs(
+            |fs| fs.add_binding(&dir.path, "/mnt").unwrap(),
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                File::create("/mnt/file").unwrap();
+                // The target is a guest path, which does not exist on the host.
+                std::os::unix::fs::symlink("/mnt/file", "/mnt/link").unwrap();
+                let ino_of = |path: &str| fs::symlink_metadata(path).unwrap().ino();
+
+                // Without `AT_SYMLINK_FOLLOW`, a hard link to the symlink.
+                linkat(
+                    None,
+                    "/mnt/link",
+                    None,
+                    "/mnt/link_to_link",
+                    LinkatFlags::NoSymlinkFollow,
+                )
+                .unwrap();
+                assert!(fs::symlink_metadata("/mnt/link_to_link")
+                    .unwrap()
+                    .file_type()
+                    .is_symlink());
+                assert_eq!(ino_of("/mnt/link_to_link"), ino_of("/mnt/link"));
+
+                // With it, a hard link to the file, found in the guest.
+                linkat(
+                    None,
+                    "/mnt/link",
+                    None,
+                    "/mnt/link_to_file",
+                    LinkatFlags::SymlinkFollow,
+                )
+                .unwrap();
+                assert!(fs::symlink_metadata("/mnt/link_to_file").unwrap().is_file());
+                assert_eq!(ino_of("/mnt/link_to_file"), ino_of("/mnt/file"));
+            },
+        )
+    }
 }
@@ -11,17 +11,20 @@ pub fn enter(tracee: &mut Tracee) -> Result<()> {
     let new_path = tracee.regs.get_sysarg_path(SysArg2)?;
     let deref_final = old_path.with_trailing_slash();
 
-    let old_host_path = tracee.fs.borrow().translate_path(old_path, deref_final)?.1;
-    let new_host_path = tracee.fs.borrow().translate_path(new_path, false)?.1;
+    let host_paths = tracee
+        .fs
+        .borrow()
+        .translate_paths(&[(old_path, deref_final), (new_path, false)])?;
+    let (old_host_path, new_host_path) = (&host_paths[0].1, &host_paths[1].1);
 
     tracee.regs.set_sysarg_path(
         SysArg1,
-        &old_host_path,
+        old_host_path,
         "during enter open translation, setting host path",
     )?;
     tracee.regs.set_sysarg_path(
         SysArg2,
-        &new_host_path,
+        new_host_path,
         "during enter open translation, setting host path",
     )?;
 
@@ -0,0 +1,44 @@
+use crate::errors::*;
+use crate::process::tracee::Tracee;
+use crate::register::{Current, SysArg, SysArg1};
+
+/// The errno returned to the tracee by `lookup_dcookie()`.
+pub const LOOKUP_DCOOKIE_ERRNO: Errno = ENOSYS;
+
+/// `lookup_dcookie()` returns the host path associated with a directory entry
+/// cookie, which cannot be translated since the cookie is opaque, and could
+/// leak paths outside of the guest rootfs. It is rejected with
+/// `LOOKUP_DCOOKIE_ERRNO` instead, as on kernels where it is not available.
+pub fn enter(tracee: &mut Tracee) -> Result<()> {
+    let cookie = tracee.regs.get(Current, SysArg(SysArg1));
+    Err(Error::errno_with_msg(
+        LOOKUP_DCOOKIE_ERRNO,
+        format!(
+            "lookup_dcookie({:#x}) is not allowed in the sandbox",
+            cookie
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tests::test_with_proot;
+
+    #[test]
+    fn test_lookup_dcookie_rejected() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let mut buf = [0u8; libc::PATH_MAX as usize];
+                assert_eq!(
+                    unsafe {
+                        libc::syscall(libc::SYS_lookup_dcookie, 0, buf.as_mut_ptr(), buf.len())
+                    },
+                    -1
+                );
+                assert_eq!(Errno::last(), LOOKUP_DCOOKIE_ERRNO);
+            },
+        )
+    }
+}
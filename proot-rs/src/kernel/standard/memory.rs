@@ -0,0 +1,338 @@
+use std::ops::Range;
+
+use crate::errors::*;
+use crate::kernel::execve::load_info::{PAGE_MASK, PAGE_SIZE};
+use crate::process::proot::InfoBag;
+use crate::process::tracee::Tracee;
+use crate::register::{Current, SysArg, SysArg1, SysArg2, SysArg3, SysArg4, SysArg5, Word};
+
+/// `madvise()` advice which zeroes the pages in the child after a `fork()`
+/// (Linux 4.14), not defined by the libc crate yet.
+const MADV_WIPEONFORK: libc::c_int = 18;
+
+/// Syscalls of the `Memory` group operate on a range of the address space of
+/// the tracee, and are forwarded to the kernel as is.
+///
+/// The only exception is when the range overlaps the mappings of the loader
+/// (see `LoaderFile::get_loader_ranges()`), which belong to proot-rs rather
+/// than to the program: operations which could modify or discard them are
+/// rejected with `EPERM`, and the harmless ones (e.g. `mlock()`) are only
+/// logged.
+///
+/// `mremap()` is also checked at its destination when `MREMAP_FIXED` is
+/// used: like `mmap(MAP_FIXED)`, it replaces whatever is mapped at the new
+/// address, so a mapping of the program cannot be moved over the loader. The
+/// other moves are done to addresses chosen by the kernel, which are free.
+///
+/// `mincore()` only queries its range, but the kernel writes its result to
+/// the vector of the tracee (one byte per page), which must not be in the
+/// loader either.
+///
+/// Advice which only changes what the children get on `fork()` (e.g.
+/// `MADV_DONTFORK`) is forwarded too for the memory of the program: proot-rs
+/// does not copy the memory of the tracees, the children created with
+/// `fork()`/`clone()` are only attached to, so the kernel applies it as usual.
+pub fn enter(tracee: &mut Tracee, info_bag: &InfoBag) -> Result<()> {
+    let sys_num = tracee.regs.get_sys_num(Current);
+    let addr = tracee.regs.get(Current, SysArg(SysArg1));
+    let length = tracee.regs.get(Current, SysArg(SysArg2));
+
+    let reject = match sys_num {
+        sc::nr::MADVISE => {
+            is_destructive_advice(tracee.regs.get(Current, SysArg(SysArg3)) as libc::c_int)
+        }
+        sc::nr::MPROTECT | sc::nr::MUNMAP | sc::nr::MREMAP => true,
+        #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+        sc::nr::MMAP => is_fixed_mapping(tracee.regs.get(Current, SysArg(SysArg4))),
+        #[cfg(any(target_arch = "x86", target_arch = "arm"))]
+        sc::nr::MMAP2 => is_fixed_mapping(tracee.regs.get(Current, SysArg(SysArg4))),
+        _ => false,
+    };
+
+    if sys_num == sc::nr::MREMAP && is_fixed_remap(tracee.regs.get(Current, SysArg(SysArg4))) {
+        let new_addr = tracee.regs.get(Current, SysArg(SysArg5));
+        let new_length = tracee.regs.get(Current, SysArg(SysArg3));
+        if let Some(target) = page_range(new_addr, new_length) {
+            if overlaps_any(&target, &info_bag.loader_ranges) {
+                return Err(Error::errno_with_msg(
+                    EPERM,
+                    format!(
+                        "mremap() to {:#x}..{:#x} would replace the loader of proot-rs",
+                        target.start, target.end
+                    ),
+                ));
+            }
+        }
+    }
+
+    if sys_num == sc::nr::MINCORE {
+        let vec = tracee.regs.get(Current, SysArg(SysArg3));
+        let pages = length.saturating_add(*PAGE_SIZE - 1) / *PAGE_SIZE;
+        if let Some(target) = page_range(vec, pages) {
+            if overlaps_any(&target, &info_bag.loader_ranges) {
+                return Err(Error::errno_with_msg(
+                    EPERM,
+                    format!(
+                        "mincore() to {:#x}..{:#x} would modify the loader of proot-rs",
+                        target.start, target.end
+                    ),
+                ));
+            }
+        }
+    }
+
+    let range = match page_range(addr, length) {
+        Some(range) => range,
+        // Invalid ranges are reported by the kernel.
+        None => return Ok(()),
+    };
+    if !overlaps_any(&range, &info_bag.loader_ranges) {
+        return Ok(());
+    }
+    if !reject {
+        warn!(
+            "-- {}, syscall {} on {:#x}..{:#x} overlaps the loader of proot-rs",
+            tracee.pid, sys_num, range.start, range.end
+        );
+        return Ok(());
+    }
+    Err(Error::errno_with_msg(
+        EPERM,
+        format!(
+            "syscall {} on {:#x}..{:#x} would modify the loader of proot-rs",
+            sys_num, range.start, range.end
+        ),
+    ))
+}
+
+/// Returns whether the `madvise()` advice may discard or alter the content of
+/// the pages, including in the children of the tracee.
+fn is_destructive_advice(advice: libc::c_int) -> bool {
+    matches!(
+        advice,
+        libc::MADV_DONTNEED
+            | libc::MADV_FREE
+            | libc::MADV_REMOVE
+            | libc::MADV_DONTFORK
+            | MADV_WIPEONFORK
+            | libc::MADV_HWPOISON
+    )
+}
+
+/// Returns whether the `mmap()` flags would replace an existing mapping.
+fn is_fixed_mapping(flags: Word) -> bool {
+    (flags as libc::c_int) & libc::MAP_FIXED != 0
+}
+
+/// Returns whether the `mremap()` flags move the mapping to a given address.
+fn is_fixed_remap(flags: Word) -> bool {
+    (flags as libc::c_int) & libc::MREMAP_FIXED != 0
+}
+
+/// Returns the range of pages covered by `length` bytes at `addr`, or `None`
+/// if it is empty or overflows.
+fn page_range(addr: Word, length: Word) -> Option<Range<Word>> {
+    let start = addr & *PAGE_MASK;
+    let end = addr.checked_add(length)?.checked_add(*PAGE_SIZE - 1)? & *PAGE_MASK;
+    if start < end {
+        Some(start..end)
+    } else {
+        None
+    }
+}
+
+fn overlaps_any(range: &Range<Word>, ranges: &[Range<Word>]) -> bool {
+    ranges
+        .iter()
+        .any(|other| range.start < other.end && other.start < range.end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filesystem::temp::TempFile;
+    use crate::kernel::execve::loader::LoaderFile;
+    use crate::utils::tests::test_with_proot;
+    use nix::sys::mman::{madvise, mlock, mmap, munlock, MapFlags, MmapAdvise, ProtFlags};
+    use nix::sys::resource::{setrlimit, Resource};
+    use nix::sys::signal::Signal;
+    use nix::sys::wait::{waitpid, WaitStatus};
+    use nix::unistd::{fork, ForkResult};
+
+    #[test]
+    fn test_page_range_and_overlaps() {
+        assert_eq!(page_range(0x1000, 0), None);
+        assert_eq!(page_range(Word::MAX, 2), None);
+        assert_eq!(page_range(0x1001, 0x1000), Some(0x1000..0x3000));
+
+        let ranges = vec![0x10000..0x12000, 0x20000..0x21000];
+        assert!(overlaps_any(&(0x11000..0x13000), &ranges));
+        assert!(overlaps_any(&(0x0..0x30000), &ranges));
+        assert!(!overlaps_any(&(0x12000..0x20000), &ranges));
+    }
+
+    #[test]
+    fn test_madvise_over_loader_is_rejected() {
+        let loader = TempFile::new("prefix_test_madvise_over_loader");
+        loader.prepare_loader().unwrap();
+        let range = loader.get_loader_ranges().unwrap()[0].clone();
+        let addr = range.start as *mut libc::c_void;
+        let length = (range.end - range.start) as usize;
+
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            move || {
+                assert_eq!(
+                    unsafe { madvise(addr, length, MmapAdvise::MADV_DONTNEED) },
+                    Err(Errno::EPERM)
+                );
+                assert_eq!(unsafe { libc::munmap(addr, length) }, -1);
+                assert_eq!(Errno::last(), Errno::EPERM);
+
+                // `mlock()` is only logged, the range is not mapped in this
+                // process so the kernel reports `ENOMEM`.
+                assert_eq!(unsafe { mlock(addr, length) }, Err(Errno::ENOMEM));
+
+                // Ranges which do not overlap the loader are forwarded.
+                let mut page = vec![0u8; 0x4000];
+                let ptr = page.as_mut_ptr() as *const libc::c_void;
+                assert_eq!(unsafe { mlock(ptr, page.len()) }, Ok(()));
+                assert_eq!(unsafe { munlock(ptr, page.len()) }, Ok(()));
+            },
+        )
+    }
+
+    #[test]
+    fn test_mremap_fixed_onto_loader_is_rejected() {
+        let loader = TempFile::new("prefix_test_mremap_fixed_onto_loader");
+        loader.prepare_loader().unwrap();
+        let range = loader.get_loader_ranges().unwrap()[0].clone();
+        let loader_addr = range.start as *mut libc::c_void;
+
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            move || {
+                let length = *PAGE_SIZE as usize;
+                let map = || unsafe {
+                    mmap(
+                        std::ptr::null_mut(),
+                        length,
+                        ProtFlags::PROT_READ | ProtFlags::PROT_WRITE,
+                        MapFlags::MAP_PRIVATE | MapFlags::MAP_ANONYMOUS,
+                        -1,
+                        0,
+                    )
+                    .unwrap()
+                };
+                let remap = |from: *mut libc::c_void, to: *mut libc::c_void| unsafe {
+                    libc::mremap(
+                        from,
+                        length,
+                        length,
+                        libc::MREMAP_MAYMOVE | libc::MREMAP_FIXED,
+                        to,
+                    )
+                };
+                let source = map();
+                unsafe { *(source as *mut u8) = 42 };
+
+                assert_eq!(remap(source, loader_addr), libc::MAP_FAILED);
+                assert_eq!(Errno::last(), Errno::EPERM);
+                // The mapping was not moved.
+                assert_eq!(unsafe { *(source as *mut u8) }, 42);
+
+                // Moving it over another mapping of the program is allowed.
+                let target = map();
+                assert_eq!(remap(source, target), target);
+                assert_eq!(unsafe { *(target as *mut u8) }, 42);
+            },
+        )
+    }
+
+    #[test]
+    fn test_madvise_fork_advice_is_applied() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let length = *PAGE_SIZE as usize;
+                let map = || unsafe {
+                    let addr = mmap(
+                        std::ptr::null_mut(),
+                        length,
+                        ProtFlags::PROT_READ | ProtFlags::PROT_WRITE,
+                        MapFlags::MAP_PRIVATE | MapFlags::MAP_ANONYMOUS,
+                        -1,
+                        0,
+                    )
+                    .unwrap() as *mut u8;
+                    *addr = 42;
+                    addr
+                };
+                let dont_fork = map();
+                let wipe_on_fork = map();
+                unsafe {
+                    madvise(dont_fork as _, length, MmapAdvise::MADV_DONTFORK).unwrap();
+                    assert_eq!(libc::madvise(wipe_on_fork as _, length, MADV_WIPEONFORK), 0);
+                }
+
+                match unsafe { fork() }.unwrap() {
+                    ForkResult::Child => {
+                        // The wiped region is still mapped, but zeroed.
+                        if unsafe { *wipe_on_fork } != 0 {
+                            std::process::exit(1);
+                        }
+                        // The other one is not mapped in the child at all.
+                        setrlimit(Resource::RLIMIT_CORE, 0, 0).unwrap();
+                        unsafe { std::ptr::write_volatile(dont_fork, 0) };
+                        std::process::exit(0);
+                    }
+                    ForkResult::Parent { child } => {
+                        assert_eq!(
+                            waitpid(child, None),
+                            Ok(WaitStatus::Signaled(child, Signal::SIGSEGV, false))
+                        );
+                    }
+                }
+
+                // The parent keeps both regions.
+                assert_eq!(unsafe { (*dont_fork, *wipe_on_fork) }, (42, 42));
+            },
+        )
+    }
+
+    #[test]
+    fn test_mincore_guest_mapping() {
+        let loader = TempFile::new("prefix_test_mincore_guest_mapping");
+        loader.prepare_loader().unwrap();
+        let range = loader.get_loader_ranges().unwrap()[0].clone();
+        let loader_addr = range.start as *mut u8;
+
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            move || {
+                let length = 2 * *PAGE_SIZE as usize;
+                let addr = unsafe {
+                    mmap(
+                        std::ptr::null_mut(),
+                        length,
+                        ProtFlags::PROT_READ | ProtFlags::PROT_WRITE,
+                        MapFlags::MAP_PRIVATE | MapFlags::MAP_ANONYMOUS,
+                        -1,
+                        0,
+                    )
+                    .unwrap()
+                };
+                // Only the first page is populated.
+                unsafe { *(addr as *mut u8) = 42 };
+
+                let mut vec = [0xffu8; 2];
+                assert_eq!(unsafe { libc::mincore(addr, length, vec.as_mut_ptr()) }, 0);
+                assert_eq!((vec[0] & 1, vec[1] & 1), (1, 0));
+
+                // The result cannot be written over the loader.
+                assert_eq!(unsafe { libc::mincore(addr, length, loader_addr) }, -1);
+                assert_eq!(Errno::last(), Errno::EPERM);
+            },
+        )
+    }
+}
@@ -1,19 +1,32 @@
 pub mod chdir;
 pub mod chmod_access_mknod_at;
+pub mod denied;
 pub mod dir_link_attr;
 pub mod getcwd;
+pub mod getdents;
 pub mod inotify_add_watch;
 pub mod link_at;
 pub mod link_rename;
+pub mod lookup_dcookie;
+pub mod memory;
 pub mod mount;
+pub mod mq;
 pub mod open;
 pub mod open_at;
+pub mod passthrough;
 pub mod pivot_root;
+pub mod process_vm;
 pub mod readlink_at;
 pub mod rename_at;
+pub mod sched_affinity;
+pub mod sethostname;
 pub mod standard_syscall;
 pub mod stat_at;
+pub mod swap;
 pub mod sym_link;
 pub mod sym_link_at;
+pub mod sync;
+pub mod sysinfo_times;
 pub mod uname;
+pub mod unhandled_path;
 pub mod unlink_mkdir_at;
@@ -1,18 +1,177 @@
-use crate::errors::Result;
+use std::path::PathBuf;
+
+use nix::mount::MsFlags;
+
+use crate::errors::*;
+use crate::filesystem::binding::Side;
+use crate::process::tracee::Tracee;
+use crate::register::{Current, Original, PtraceReader, SysArg, SysArg1, SysArg2, SysArg4};
+use crate::register::{SysResult, Word};
+
+/// Bind mounts (`MS_BIND`) are emulated with a new binding from the source to
+/// the target, shared by the tracees which share their `FileSystem`, as if
+/// they were in their own mount namespace. The other mounts (including the
+/// remount of a bind mount) are forwarded as is.
+///
+/// The source and the target are checked like the kernel does, so that a
+/// missing target or a file bound over a directory fails instead of adding a
+/// binding to nowhere.
+///
+/// As with the kernel, the bindings nested in the source are only replicated
+/// under the target with `MS_REC`: otherwise the target shows the directories
+/// which they hide.
+pub fn enter(tracee: &mut Tracee) -> Result<()> {
+    let flags = MsFlags::from_bits_truncate(tracee.regs.get(Current, SysArg(SysArg4)) as _);
+    if !is_bind_mount(flags) {
+        return Ok(());
+    }
+    let source = tracee.regs.get_sysarg_path(SysArg1)?;
+    let target = tracee.regs.get_sysarg_path(SysArg2)?;
+    let (guest_source, host_source) = tracee.translate_path_at(libc::AT_FDCWD, source, true)?;
+    let (guest_target, host_target) = tracee.translate_path_at(libc::AT_FDCWD, target, true)?;
+
+    // Same checks as the kernel: both must exist (`ENOENT`), and a directory
+    // can only be mounted on a directory, a file on a file (`ENOTDIR`).
+    if host_source.metadata()?.is_dir() != host_target.metadata()?.is_dir() {
+        return Err(Error::errno_with_msg(
+            ENOTDIR,
+            format!(
+                "cannot bind {:?} on {:?}, one is not a directory",
+                guest_source, guest_target
+            ),
+        ));
+    }
+
+    let mut fs = tracee.fs.borrow_mut();
+    // The nested bindings, the most recent first.
+    let nested_bindings: Vec<(PathBuf, PathBuf)> = if flags.contains(MsFlags::MS_REC) {
+        fs.get_bindings()
+            .filter_map(|binding| {
+                let suffix = binding
+                    .get_path(Side::Guest)
+                    .strip_prefix(&guest_source)
+                    .ok()?;
+                if suffix.as_os_str().is_empty() {
+                    return None;
+                }
+                Some((
+                    binding.get_path(Side::Host).clone(),
+                    guest_target.join(suffix),
+                ))
+            })
+            .collect()
+    } else {
+        vec![]
+    };
+    fs.add_binding(&host_source, &guest_target)?;
+    for (host_path, guest_path) in nested_bindings.iter().rev() {
+        fs.add_binding(host_path, guest_path)?;
+    }
+    drop(fs);
+
+    tracee
+        .regs
+        .cancel_syscall("Cancel mount since the bind mount is emulated");
 
-pub fn enter() -> Result<()> {
     Ok(())
+}
+
+pub fn exit(tracee: &mut Tracee) -> Result<()> {
+    let flags = MsFlags::from_bits_truncate(tracee.regs.get(Original, SysArg(SysArg4)) as _);
+    if !is_bind_mount(flags) {
+        return Ok(());
+    }
+    // The bind mount is emulated, see method `enter()` above.
+    tracee
+        .regs
+        .set(SysResult, 0 as Word, "update return value in mount::exit()");
+    Ok(())
+}
+
+fn is_bind_mount(flags: MsFlags) -> bool {
+    flags.contains(MsFlags::MS_BIND) && !flags.contains(MsFlags::MS_REMOUNT)
+}
+
+#[cfg(test)]
+mod tests {
+    use nix::mount::mount;
+    use nix::sys::stat::stat;
+
+    use super::*;
+    use crate::utils::tests::test_with_proot_and_fs;
+
+    #[test]
+    fn test_mount_bind_recursive() {
+        test_with_proot_and_fs(
+            |fs| fs.add_binding("/etc", "/home").unwrap(),
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let stat_of = |path: &str| stat(path).ok().map(|stat| (stat.st_dev, stat.st_ino));
+
+                mount(
+                    Some("/"),
+                    "/mnt",
+                    None::<&str>,
+                    MsFlags::MS_BIND | MsFlags::MS_REC,
+                    None::<&str>,
+                )
+                .unwrap();
+                assert_eq!(stat_of("/mnt/etc/passwd"), stat_of("/etc/passwd"));
+                // The nested binding is replicated.
+                assert_eq!(stat_of("/mnt/home/passwd"), stat_of("/home/passwd"));
+
+                mount(
+                    Some("/"),
+                    "/opt",
+                    None::<&str>,
+                    MsFlags::MS_BIND,
+                    None::<&str>,
+                )
+                .unwrap();
+                assert_eq!(stat_of("/opt/etc/passwd"), stat_of("/etc/passwd"));
+                // Without `MS_REC`, the directory hidden by the nested binding
+                // shows through.
+                assert_ne!(stat_of("/opt/home/passwd"), stat_of("/home/passwd"));
+
+                assert_eq!(
+                    mount(
+                        Some("/nonexistent"),
+                        "/mnt",
+                        None::<&str>,
+                        MsFlags::MS_BIND,
+                        None::<&str>
+                    ),
+                    Err(Errno::ENOENT)
+                );
+            },
+        )
+    }
+
+    #[test]
+    fn test_mount_bind_checks_target() {
+        test_with_proot_and_fs(
+            |_fs| {},
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let bind = |source: &str, target: &str| {
+                    mount(
+                        Some(source),
+                        target,
+                        None::<&str>,
+                        MsFlags::MS_BIND,
+                        None::<&str>,
+                    )
+                };
 
-    //                status = get_sysarg_path(tracee, path, SYSARG_1);
-    //                if (status < 0)
-    //                break;
-    //
-    //                /* The following check covers only 90% of the cases. */
-    //                if (path[0] == '/' || path[0] == '.') {
-    //                    status = translate_path2(tracee, AT_FDCWD, path,
-    // SYSARG_1, REGULAR);                    if (status < 0)
-    //                    break;
-    //                }
-    //
-    //                status = translate_sysarg(tracee, SYSARG_2, REGULAR);
+                // A missing target.
+                assert_eq!(bind("/etc", "/nonexistent"), Err(Errno::ENOENT));
+                // A file over a directory, and the other way around.
+                assert_eq!(bind("/etc/passwd", "/mnt"), Err(Errno::ENOTDIR));
+                assert_eq!(bind("/etc", "/etc/passwd"), Err(Errno::ENOTDIR));
+                // Nothing was bound.
+                assert!(stat("/mnt/passwd").is_err());
+                assert!(stat("/etc/passwd/passwd").is_err());
+            },
+        )
+    }
 }
@@ -0,0 +1,111 @@
+use std::ffi::OsString;
+use std::os::unix::ffi::OsStringExt;
+use std::path::PathBuf;
+
+use crate::errors::*;
+use crate::process::proot::InfoBag;
+use crate::process::tracee::Tracee;
+use crate::register::{Current, PtraceReader, PtraceWriter, SysArg1};
+
+/// `mq_open()` and `mq_unlink()` take the name of a message queue, which looks
+/// like a path but lives in a namespace of the kernel (the C library removes
+/// the leading `/` before calling the syscall).
+///
+/// By default the name is forwarded as is (and logged), so that the guest
+/// shares its message queues with the host. With the `--mq-namespace` option,
+/// the name is prefixed with `InfoBag::mq_prefix`, so that each sandbox gets
+/// its own queues.
+pub fn enter(tracee: &mut Tracee, info_bag: &InfoBag) -> Result<()> {
+    let name = tracee.regs.get_sysarg_path(SysArg1)?;
+    let prefix = match info_bag.mq_prefix {
+        Some(ref prefix) => prefix,
+        None => {
+            debug!(
+                "-- {}, syscall {} on message queue {:?} passed through",
+                tracee.pid,
+                tracee.regs.get_sys_num(Current),
+                name
+            );
+            return Ok(());
+        }
+    };
+
+    let namespaced_name = namespace_name(prefix, name.into_os_string());
+    debug!(
+        "-- {}, syscall {} on message queue {:?}",
+        tracee.pid,
+        tracee.regs.get_sys_num(Current),
+        namespaced_name
+    );
+    tracee.regs.set_sysarg_path(
+        SysArg1,
+        &namespaced_name,
+        "during enter mq translation, setting namespaced name",
+    )
+}
+
+/// Returns the name of the message queue `name` in the namespace of the
+/// sandbox. Names which are too long are reported by the kernel.
+fn namespace_name(prefix: &str, name: OsString) -> PathBuf {
+    let mut bytes = prefix.as_bytes().to_vec();
+    bytes.extend(name.into_vec());
+    PathBuf::from(OsString::from_vec(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+    use std::path::Path;
+
+    use super::*;
+    use crate::utils::tests::test_with_proot_and_info_bag;
+
+    const PREFIX: &str = "proot-rs-test.";
+
+    #[test]
+    fn test_mq_open_is_namespaced() {
+        test_with_proot_and_info_bag(
+            |info_bag| info_bag.mq_prefix = Some(PREFIX.into()),
+            |tracee, is_sysenter, before_translation| {
+                let sys_num = tracee.regs.get_sys_num(Current);
+                if is_sysenter
+                    && !before_translation
+                    && (sys_num == sc::nr::MQ_OPEN || sys_num == sc::nr::MQ_UNLINK)
+                {
+                    assert_eq!(
+                        tracee.regs.get_sysarg_path(SysArg1).unwrap(),
+                        Path::new("proot-rs-test.queue_for_test_mq_open")
+                    );
+                }
+            },
+            || {
+                // The syscalls are used directly, since the C library would
+                // remove the leading `/` anyway.
+                let name = CString::new("queue_for_test_mq_open").unwrap();
+                let mqd = unsafe {
+                    libc::syscall(
+                        libc::SYS_mq_open,
+                        name.as_ptr(),
+                        libc::O_CREAT | libc::O_RDWR,
+                        0o600,
+                        std::ptr::null::<libc::mq_attr>(),
+                    )
+                };
+                assert!(mqd >= 0, "mq_open() failed: {}", Errno::last());
+                unsafe { libc::close(mqd as libc::c_int) };
+                assert_eq!(
+                    unsafe { libc::syscall(libc::SYS_mq_unlink, name.as_ptr()) },
+                    0
+                );
+            },
+        )
+    }
+
+    #[test]
+    fn test_namespace_name() {
+        assert_eq!(
+            namespace_name(PREFIX, "queue".into()),
+            Path::new("proot-rs-test.queue")
+        );
+    }
+}
@@ -1,20 +1,29 @@
+use std::path::Path;
+
 use nix::fcntl::OFlag;
 
 use crate::errors::*;
-
-use crate::filesystem::Translator;
+use crate::filesystem::ext::PathExt;
 use crate::process::tracee::Tracee;
 use crate::register::PtraceWriter;
 use crate::register::{Current, PtraceReader, SysArg, SysArg1, SysArg2};
 
+/// The path is only translated (without opening it), and the open itself is
+/// left to the kernel: the open of a fifo blocks the tracee until the other
+/// end is opened (unless `O_NONBLOCK`), while proot-rs keeps handling the
+/// other tracees, e.g. the one which opens the other end.
 pub fn enter(tracee: &mut Tracee) -> Result<()> {
     let raw_path = tracee.regs.get_sysarg_path(SysArg1)?;
 
     let flags = OFlag::from_bits_truncate(tracee.regs.get(Current, SysArg(SysArg2)) as _);
 
-    let deref_final = !(flags.contains(OFlag::O_NOFOLLOW)
-        || (flags.contains(OFlag::O_EXCL) && flags.contains(OFlag::O_CREAT)));
-    let host_path = tracee.fs.borrow().translate_path(raw_path, deref_final)?.1;
+    let deref_final = open_deref_final(flags, &raw_path);
+    // `open()` behaves like `openat(AT_FDCWD, ...)`, which also takes care of
+    // the magic paths such as `/proc/self/fd/N`.
+    let host_path = tracee
+        .translate_path_at(libc::AT_FDCWD, raw_path, deref_final)?
+        .1;
+    check_tmpfile_dir(flags, &host_path)?;
 
     tracee.regs.set_sysarg_path(
         SysArg1,
@@ -25,11 +34,57 @@ pub fn enter(tracee: &mut Tracee) -> Result<()> {
     Ok(())
 }
 
+/// Returns whether the final component of the path given to `open()` or
+/// `openat()` with `flags` should be dereferenced.
+///
+/// As for the kernel, a trailing slash makes the final symlink be followed
+/// even with `O_NOFOLLOW`, except when creating the file exclusively.
+///
+/// `O_DIRECTORY` does not change it: the final component is translated as
+/// for any other open, and the kernel fails with `ENOTDIR` if the host path
+/// (whose type is the one of the guest path) is not a directory.
+///
+/// A trailing slash also means that the final component must be a directory:
+/// when it is followed, the canonicalization fails with `ENOTDIR` on a file
+/// (e.g. a binding of a file), and otherwise the trailing slash is kept on the
+/// host path, so that the kernel does the same.
+pub fn open_deref_final(flags: OFlag, raw_path: &Path) -> bool {
+    if flags.contains(OFlag::O_EXCL) && flags.contains(OFlag::O_CREAT) {
+        return false;
+    }
+    !flags.contains(OFlag::O_NOFOLLOW) || raw_path.with_trailing_slash()
+}
+
+/// With `O_TMPFILE`, the path is not the file to open but the directory in
+/// which an unnamed file is created, so it must be an existing directory.
+///
+/// Such a file can later be given a name with `linkat()` on
+/// `/proc/self/fd/N`, see `Tracee::translate_magic_fd_path()`.
+pub fn check_tmpfile_dir(flags: OFlag, host_path: &Path) -> Result<()> {
+    if flags.contains(OFlag::O_TMPFILE) && !host_path.metadata()?.is_dir() {
+        return Err(Error::errno_with_msg(
+            ENOTDIR,
+            format!(
+                "O_TMPFILE on a path which is not a directory: {:?}",
+                host_path
+            ),
+        ));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
+    use std::ffi::CString;
+
+    use nix::errno::Errno;
     use nix::fcntl::OFlag;
+    use nix::sys::stat::{lstat, stat, Mode};
+    use nix::sys::wait::{waitpid, WaitStatus};
+    use nix::unistd::{fork, ForkResult};
 
-    use crate::utils::tests::test_with_proot;
+    use crate::filesystem::temp::TempFile;
+    use crate::utils::tests::{test_with_proot, test_with_proot_and_fs};
 
     /// Unit test for the following syscalls:
     /// - linkat
@@ -113,4 +168,91 @@ mod tests {
             },
         )
     }
+
+    #[test]
+    fn test_open_file_with_trailing_slash() {
+        test_with_proot_and_fs(
+            |fs| fs.add_binding("/etc/passwd", "/home").unwrap(),
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let open = |path: &str, flags: OFlag| {
+                    nix::fcntl::open(path, flags, Mode::empty()).and_then(nix::unistd::close)
+                };
+
+                // A file of the rootfs, and a file bound on a directory.
+                for path in &["/etc/passwd/", "/etc/passwd/.", "/home/"] {
+                    assert_eq!(open(path, OFlag::O_RDONLY), Err(Errno::ENOTDIR), "{}", path);
+                    assert_eq!(
+                        open(path, OFlag::O_RDONLY | OFlag::O_NOFOLLOW),
+                        Err(Errno::ENOTDIR),
+                        "{}",
+                        path
+                    );
+                    assert_eq!(stat(*path).err(), Some(Errno::ENOTDIR), "{}", path);
+                    assert_eq!(lstat(*path).err(), Some(Errno::ENOTDIR), "{}", path);
+                }
+                let fd = nix::fcntl::open("/etc", OFlag::O_RDONLY, Mode::empty()).unwrap();
+                assert_eq!(
+                    nix::fcntl::openat(fd, "passwd/", OFlag::O_RDONLY, Mode::empty()),
+                    Err(Errno::ENOTDIR)
+                );
+                nix::unistd::close(fd).unwrap();
+
+                // Without the trailing slash, they are files.
+                assert_eq!(open("/home", OFlag::O_RDONLY), Ok(()));
+                assert_eq!(open("/etc/passwd", OFlag::O_RDONLY), Ok(()));
+            },
+        )
+    }
+
+    /// The open of a fifo blocks until its other end is opened, by another
+    /// tracee.
+    #[test]
+    fn test_open_fifo() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let fifo = TempFile::new("prefix_test_open_fifo");
+                let fifopath = fifo.path.to_str().unwrap();
+
+                nix::unistd::mkfifo(fifopath, Mode::from_bits_truncate(0o755)).unwrap();
+
+                // Without a reader, as the kernel does.
+                assert_eq!(
+                    nix::fcntl::open(fifopath, OFlag::O_WRONLY | OFlag::O_NONBLOCK, Mode::empty()),
+                    Err(Errno::ENXIO)
+                );
+                let fd =
+                    nix::fcntl::open(fifopath, OFlag::O_RDONLY | OFlag::O_NONBLOCK, Mode::empty())
+                        .unwrap();
+                nix::unistd::close(fd).unwrap();
+
+                match unsafe { fork() }.unwrap() {
+                    ForkResult::Child => {
+                        let written = nix::fcntl::open(fifopath, OFlag::O_WRONLY, Mode::empty())
+                            .and_then(|fd| nix::unistd::write(fd, b"fifo"));
+                        unsafe { libc::_exit(if written == Ok(4) { 0 } else { 1 }) }
+                    }
+                    ForkResult::Parent { child } => {
+                        // Blocks until the child opens the fifo for writing.
+                        let fd =
+                            nix::fcntl::open(fifopath, OFlag::O_RDONLY, Mode::empty()).unwrap();
+                        let mut buf = [0u8; 4];
+                        assert_eq!(nix::unistd::read(fd, &mut buf), Ok(4));
+                        assert_eq!(&buf, b"fifo");
+                        nix::unistd::close(fd).unwrap();
+                        assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+                    }
+                }
+
+                // Nor does its execution block proot-rs, which reads the
+                // headers of the executables.
+                let path = CString::new(fifopath).unwrap();
+                assert_eq!(
+                    nix::unistd::execv(&path, &[&path]).unwrap_err(),
+                    Errno::EACCES
+                );
+            },
+        )
+    }
 }
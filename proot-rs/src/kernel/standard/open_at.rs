@@ -3,6 +3,7 @@ use std::os::unix::prelude::RawFd;
 use nix::fcntl::OFlag;
 
 use crate::errors::*;
+use crate::kernel::standard::open;
 use crate::process::tracee::Tracee;
 use crate::register::PtraceWriter;
 use crate::register::{Current, PtraceReader, SysArg, SysArg1, SysArg2, SysArg3};
@@ -12,10 +13,10 @@ pub fn enter(tracee: &mut Tracee) -> Result<()> {
     let raw_path = tracee.regs.get_sysarg_path(SysArg2)?;
     let flags = OFlag::from_bits_truncate(tracee.regs.get(Current, SysArg(SysArg3)) as _);
 
-    let deref_final = !(flags.contains(OFlag::O_NOFOLLOW)
-        || (flags.contains(OFlag::O_EXCL) && flags.contains(OFlag::O_CREAT)));
+    let deref_final = open::open_deref_final(flags, &raw_path);
 
     let host_path = tracee.translate_path_at(dirfd, raw_path, deref_final)?.1;
+    open::check_tmpfile_dir(flags, &host_path)?;
 
     tracee.regs.set_sysarg_path(
         SysArg2,
@@ -29,9 +30,12 @@ pub fn enter(tracee: &mut Tracee) -> Result<()> {
 
 #[cfg(test)]
 mod tests {
+    use std::path::PathBuf;
+
     use nix::{fcntl::OFlag, sys::stat::Mode};
 
-    use crate::utils::tests::test_with_proot;
+    use crate::filesystem::temp::{TempDir, TempFile};
+    use crate::utils::tests::{get_test_rootfs_path, test_with_proot, test_with_proot_and_fs};
 
     /// Unit test for the following syscalls:
     /// - openat
@@ -91,4 +95,270 @@ mod tests {
             },
         )
     }
+
+    /// Opening `/proc/self/fd/N` or `/dev/fd/N` should give the same guest file
+    /// as the fd `N`.
+    #[test]
+    fn test_open_magic_fd_path() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let fd = nix::fcntl::open("/etc/passwd", OFlag::O_RDONLY, Mode::empty()).unwrap();
+                let stat = nix::sys::stat::fstat(fd).unwrap();
+
+                for magic_path in &[
+                    format!("/proc/self/fd/{}", fd),
+                    format!("/proc/{}/fd/{}", nix::unistd::getpid(), fd),
+                    format!("/dev/fd/{}", fd),
+                ] {
+                    let magic_fd =
+                        nix::fcntl::open(magic_path.as_str(), OFlag::O_RDONLY, Mode::empty())
+                            .unwrap();
+                    let magic_stat = nix::sys::stat::fstat(magic_fd).unwrap();
+                    assert_eq!(
+                        (magic_stat.st_dev, magic_stat.st_ino),
+                        (stat.st_dev, stat.st_ino)
+                    );
+                    nix::unistd::close(magic_fd).unwrap();
+
+                    // same with openat() and a relative path
+                    let root_fd = nix::fcntl::open("/", OFlag::O_RDONLY, Mode::empty()).unwrap();
+                    let magic_fd = nix::fcntl::openat(
+                        root_fd,
+                        &magic_path[1..],
+                        OFlag::O_RDONLY,
+                        Mode::empty(),
+                    )
+                    .unwrap();
+                    let magic_stat = nix::sys::stat::fstat(magic_fd).unwrap();
+                    assert_eq!(
+                        (magic_stat.st_dev, magic_stat.st_ino),
+                        (stat.st_dev, stat.st_ino)
+                    );
+                    nix::unistd::close(magic_fd).unwrap();
+                    nix::unistd::close(root_fd).unwrap();
+                }
+
+                // fds which are not associated with a path are also supported.
+                let (read_fd, write_fd) = nix::unistd::pipe().unwrap();
+                let magic_fd = nix::fcntl::open(
+                    format!("/proc/self/fd/{}", write_fd).as_str(),
+                    OFlag::O_WRONLY,
+                    Mode::empty(),
+                )
+                .unwrap();
+                assert_eq!(nix::unistd::write(magic_fd, b"pipe"), Ok(4));
+                let mut buf = [0u8; 4];
+                assert_eq!(nix::unistd::read(read_fd, &mut buf), Ok(4));
+                assert_eq!(&buf, b"pipe");
+            },
+        )
+    }
+
+    /// The paths under the magic link of a fd whose guest path is unknown
+    /// should not be looked up in the host.
+    #[test]
+    fn test_open_under_magic_fd_path_out_of_guest() {
+        // Inherited by the tracee, and out of the guest.
+        let rootfs_path = get_test_rootfs_path();
+        let host_dir_fd = nix::fcntl::open(
+            rootfs_path.parent().unwrap(),
+            OFlag::O_RDONLY | OFlag::O_DIRECTORY,
+            Mode::empty(),
+        )
+        .unwrap();
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            move || {
+                for magic_path in &[
+                    format!("/proc/self/fd/{}/..", host_dir_fd),
+                    format!("/dev/fd/{}/..", host_dir_fd),
+                ] {
+                    assert_eq!(
+                        nix::fcntl::open(magic_path.as_str(), OFlag::O_RDONLY, Mode::empty()),
+                        Err(nix::errno::Errno::EACCES)
+                    );
+                }
+
+                let (read_fd, _write_fd) = nix::unistd::pipe().unwrap();
+                assert_eq!(
+                    nix::fcntl::open(
+                        format!("/proc/self/fd/{}/..", read_fd).as_str(),
+                        OFlag::O_RDONLY,
+                        Mode::empty()
+                    ),
+                    Err(nix::errno::Errno::ENOTDIR)
+                );
+            },
+        )
+    }
+
+    /// `/dev/stdout` should give the stdout of the tracee, rather than the one
+    /// of proot-rs, when it is a symlink to `/proc/self/fd/1` in the guest (and
+    /// the same for `/dev/stderr`).
+    #[test]
+    fn test_open_dev_stdout() {
+        // Created out of the subprocess of the test, so that it is deleted.
+        let dev = TempDir::new("test_open_dev_stdout");
+        dev.create_dir().unwrap();
+        std::os::unix::fs::symlink("/proc/self/fd/1", dev.path.join("stdout")).unwrap();
+        std::os::unix::fs::symlink("../proc/self/fd/2", dev.path.join("stderr")).unwrap();
+        test_with_proot_and_fs(
+            |fs| fs.add_binding(&dev.path, "/dev").unwrap(),
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let (read_fd, write_fd) = nix::unistd::pipe().unwrap();
+                let saved_stdout = nix::unistd::dup(1).unwrap();
+                nix::unistd::dup2(write_fd, 1).unwrap();
+
+                let result = nix::fcntl::open("/dev/stdout", OFlag::O_WRONLY, Mode::empty())
+                    .and_then(|stdout| nix::unistd::write(stdout, b"stdout"));
+                nix::unistd::dup2(saved_stdout, 1).unwrap();
+                assert_eq!(result, Ok(6));
+                let mut buf = [0u8; 6];
+                assert_eq!(nix::unistd::read(read_fd, &mut buf), Ok(6));
+                assert_eq!(&buf, b"stdout");
+
+                // Same with a relative target.
+                let saved_stderr = nix::unistd::dup(2).unwrap();
+                nix::unistd::dup2(write_fd, 2).unwrap();
+                let result = nix::fcntl::open("/dev/stderr", OFlag::O_WRONLY, Mode::empty())
+                    .and_then(|stderr| nix::unistd::write(stderr, b"stderr"));
+                nix::unistd::dup2(saved_stderr, 2).unwrap();
+                assert_eq!(result, Ok(6));
+                assert_eq!(nix::unistd::read(read_fd, &mut buf), Ok(6));
+                assert_eq!(&buf, b"stderr");
+
+                // The symlink itself is the one of the guest.
+                assert_eq!(
+                    nix::fcntl::readlink("/dev/stdout").map(PathBuf::from),
+                    Ok(PathBuf::from("/proc/self/fd/1"))
+                );
+                // The names of `/dev` are not hardcoded: there is no
+                // `/dev/stdin` in the guest.
+                assert_eq!(
+                    nix::fcntl::open("/dev/stdin", OFlag::O_RDONLY, Mode::empty()),
+                    Err(nix::errno::Errno::ENOENT)
+                );
+            },
+        )
+    }
+
+    /// A file created with `O_TMPFILE` should be created in the guest
+    /// directory, and should be linkable into the guest through
+    /// `/proc/self/fd/N`.
+    #[test]
+    fn test_open_tmpfile_and_linkat() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let temp_file = TempFile::new("prefix_test_open_tmpfile_and_linkat");
+                let filepath = temp_file.path.to_str().unwrap();
+
+                // `O_TMPFILE` needs a directory.
+                assert_eq!(
+                    nix::fcntl::open(
+                        "/etc/passwd",
+                        OFlag::O_TMPFILE | OFlag::O_RDWR,
+                        Mode::from_bits_truncate(0o600)
+                    ),
+                    Err(nix::errno::Errno::ENOTDIR)
+                );
+
+                let fd = match nix::fcntl::open(
+                    "/tmp",
+                    OFlag::O_TMPFILE | OFlag::O_RDWR,
+                    Mode::from_bits_truncate(0o600),
+                ) {
+                    // The file-system of the rootfs does not support `O_TMPFILE`.
+                    Err(nix::errno::Errno::EOPNOTSUPP) => return,
+                    result => result.unwrap(),
+                };
+                assert_eq!(nix::unistd::write(fd, b"tmpfile"), Ok(7));
+                nix::unistd::linkat(
+                    None,
+                    format!("/proc/self/fd/{}", fd).as_str(),
+                    None,
+                    filepath,
+                    nix::unistd::LinkatFlags::SymlinkFollow,
+                )
+                .unwrap();
+                assert_eq!(std::fs::read(filepath).unwrap(), b"tmpfile");
+            },
+        )
+    }
+
+    /// `O_DIRECTORY` is enforced by the kernel on the translated path.
+    #[test]
+    fn test_open_directory() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let link = TempFile::new("prefix_test_open_directory");
+                let linkpath = link.path.to_str().unwrap();
+                let flags = OFlag::O_RDONLY | OFlag::O_DIRECTORY;
+
+                assert_eq!(
+                    nix::fcntl::open("/etc/passwd", flags, Mode::empty()),
+                    Err(nix::errno::Errno::ENOTDIR)
+                );
+                assert_eq!(
+                    nix::fcntl::open("/etc/passwd/", OFlag::O_RDONLY, Mode::empty()),
+                    Err(nix::errno::Errno::ENOTDIR)
+                );
+                let fd = nix::fcntl::open("/etc", flags, Mode::empty()).unwrap();
+                nix::unistd::close(fd).unwrap();
+
+                // The absolute target of the symlink is a guest path, which is
+                // followed because of the trailing slash, despite `O_NOFOLLOW`.
+                std::os::unix::fs::symlink("/etc", linkpath).unwrap();
+                let fd = nix::fcntl::open(
+                    format!("{}/", linkpath).as_str(),
+                    flags | OFlag::O_NOFOLLOW,
+                    Mode::empty(),
+                )
+                .unwrap();
+                let stat = nix::sys::stat::fstat(fd).unwrap();
+                let etc_stat = nix::sys::stat::stat("/etc").unwrap();
+                assert_eq!(
+                    (stat.st_dev, stat.st_ino),
+                    (etc_stat.st_dev, etc_stat.st_ino)
+                );
+                nix::unistd::close(fd).unwrap();
+            },
+        )
+    }
+
+    /// A path relative to a `dirfd` inside a binding is resolved on the guest
+    /// side: `..` leaves the binding as it would leave a mount.
+    #[test]
+    fn test_open_at_relative_to_binding() {
+        test_with_proot_and_fs(
+            |fs| fs.add_binding("/etc", "/home").unwrap(),
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let stat_of = |path: &str| {
+                    let stat = nix::sys::stat::stat(path).unwrap();
+                    (stat.st_dev, stat.st_ino)
+                };
+                let stat_at = |dirfd, path: &str| {
+                    let fd =
+                        nix::fcntl::openat(dirfd, path, OFlag::O_RDONLY, Mode::empty()).unwrap();
+                    let stat = nix::sys::stat::fstat(fd).unwrap();
+                    nix::unistd::close(fd).unwrap();
+                    (stat.st_dev, stat.st_ino)
+                };
+                // The rootfs and the host have their own `/etc/passwd`.
+                assert_ne!(stat_of("/home/passwd"), stat_of("/etc/passwd"));
+
+                let dirfd = nix::fcntl::open("/home", OFlag::O_RDONLY, Mode::empty()).unwrap();
+                assert_eq!(stat_at(dirfd, "passwd"), stat_of("/home/passwd"));
+                assert_eq!(stat_at(dirfd, "./passwd"), stat_of("/home/passwd"));
+                // Not the host `/etc/../etc/passwd`, which is in the binding.
+                assert_eq!(stat_at(dirfd, "../etc/passwd"), stat_of("/etc/passwd"));
+                assert_eq!(stat_at(dirfd, "../home/passwd"), stat_of("/home/passwd"));
+                nix::unistd::close(dirfd).unwrap();
+            },
+        )
+    }
 }
@@ -0,0 +1,206 @@
+//! The offset-based IO (`pread64()` and `pwrite64()`), `fallocate()` and the
+//! hints on the page cache (`readahead()` and `fadvise64()`, with its variants
+//! of 32-bit architectures) are intentionally forwarded to the kernel without
+//! any translation: they operate on a file descriptor which was already
+//! obtained through a translated path.
+//!
+//! Like `read()` and `write()`, and the vectored IO (`readv()`, `writev()` and
+//! their `p*v()` and `p*v2()` variants), they are not even traced. Checking
+//! their fd against the files used internally by proot-rs (e.g. the loader)
+//! would not protect more than `read()` and `write()` do.
+//!
+//! `fallocate()` is forwarded with all its modes, including
+//! `FALLOC_FL_PUNCH_HOLE`: the kernel already rejects them on a fd which was
+//! not opened for writing (`EBADF`) or which is on a read-only mount
+//! (`EROFS`). proot-rs has no read-only bindings of its own to enforce.
+//!
+//! The tests below check that they keep working on the files of the guest.
+
+#[cfg(test)]
+mod tests {
+    use crate::filesystem::temp::TempFile;
+    use crate::utils::tests::test_with_proot;
+    use nix::fcntl::{self, OFlag};
+    use nix::sys::stat::Mode;
+    use nix::sys::uio::{pread, preadv, pwrite, pwritev, readv};
+    use nix::unistd;
+    use std::io::{IoSlice, IoSliceMut};
+
+    #[test]
+    fn test_passthrough_pread64_pwrite64() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let content = std::fs::read("/etc/passwd").unwrap();
+                assert!(content.len() > 4);
+
+                let fd = fcntl::open("/etc/passwd", OFlag::O_RDONLY, Mode::empty()).unwrap();
+                let mut buf = vec![0u8; content.len() - 4];
+                assert_eq!(pread(fd, &mut buf, 4), Ok(content.len() - 4));
+                assert_eq!(&buf[..], &content[4..]);
+                unistd::close(fd).unwrap();
+
+                let temp_file = TempFile::new("prefix_test_passthrough_pread64_pwrite64");
+                let file_path = temp_file.path.to_str().unwrap();
+                let fd = fcntl::open(
+                    file_path,
+                    OFlag::O_RDWR | OFlag::O_CREAT | OFlag::O_TRUNC,
+                    Mode::from_bits_truncate(0o644),
+                )
+                .unwrap();
+                assert_eq!(pwrite(fd, b"world", 6), Ok(5));
+                assert_eq!(pwrite(fd, b"hello", 0), Ok(5));
+                let mut buf = [0u8; 5];
+                assert_eq!(pread(fd, &mut buf, 6), Ok(5));
+                assert_eq!(&buf, b"world");
+                unistd::close(fd).unwrap();
+            },
+        )
+    }
+
+    #[test]
+    fn test_passthrough_fadvise_readahead() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let fd = fcntl::open("/etc/passwd", OFlag::O_RDONLY, Mode::empty()).unwrap();
+                assert_eq!(
+                    fcntl::posix_fadvise(fd, 0, 0, fcntl::PosixFadviseAdvice::POSIX_FADV_WILLNEED),
+                    Ok(())
+                );
+                assert_eq!(unsafe { libc::readahead(fd, 0, 4096) }, 0);
+                unistd::close(fd).unwrap();
+
+                // The errors are the ones of the kernel.
+                assert_eq!(
+                    fcntl::posix_fadvise(-1, 0, 0, fcntl::PosixFadviseAdvice::POSIX_FADV_NORMAL),
+                    Err(nix::errno::Errno::EBADF)
+                );
+            },
+        )
+    }
+
+    #[test]
+    fn test_passthrough_vectored_io() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let content = std::fs::read("/etc/passwd").unwrap();
+                assert!(content.len() > 8);
+
+                let fd = fcntl::open("/etc/passwd", OFlag::O_RDONLY, Mode::empty()).unwrap();
+                let (mut head, mut tail) = (vec![0u8; 4], vec![0u8; content.len() - 4]);
+                assert_eq!(
+                    readv(
+                        fd,
+                        &mut [IoSliceMut::new(&mut head), IoSliceMut::new(&mut tail)]
+                    ),
+                    Ok(content.len())
+                );
+                assert_eq!(&head[..], &content[..4]);
+                assert_eq!(&tail[..], &content[4..]);
+
+                let mut buf = [0u8; 4];
+                assert_eq!(preadv(fd, &mut [IoSliceMut::new(&mut buf)], 4), Ok(4));
+                assert_eq!(&buf[..], &content[4..8]);
+                unistd::close(fd).unwrap();
+
+                let temp_file = TempFile::new("prefix_test_passthrough_vectored_io");
+                let file_path = temp_file.path.to_str().unwrap();
+                let fd = fcntl::open(
+                    file_path,
+                    OFlag::O_RDWR | OFlag::O_CREAT | OFlag::O_TRUNC,
+                    Mode::from_bits_truncate(0o644),
+                )
+                .unwrap();
+                assert_eq!(
+                    pwritev(fd, &[IoSlice::new(b"hello "), IoSlice::new(b"world")], 0),
+                    Ok(11)
+                );
+                unistd::close(fd).unwrap();
+                assert_eq!(std::fs::read(file_path).unwrap(), b"hello world");
+            },
+        )
+    }
+
+    #[test]
+    fn test_passthrough_preadv2_flags() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                // Not defined by the libc crate for musl.
+                const RWF_NOWAIT: i32 = 0x8;
+                let preadv2 = |fd: i32, buf: &mut [u8], offset: i64, flags: i32| {
+                    let iov = libc::iovec {
+                        iov_base: buf.as_mut_ptr() as _,
+                        iov_len: buf.len(),
+                    };
+                    let result = unsafe {
+                        libc::syscall(
+                            sc::nr::PREADV2 as _,
+                            fd,
+                            &iov as *const libc::iovec,
+                            1,
+                            offset as libc::c_long,
+                            (offset >> 32) as libc::c_long,
+                            flags,
+                        )
+                    };
+                    nix::errno::Errno::result(result).map(|n| n as usize)
+                };
+                // Read once, so that the content is in the page cache and
+                // `RWF_NOWAIT` does not fail with `EAGAIN`.
+                let content = std::fs::read("/etc/passwd").unwrap();
+                assert!(content.len() > 8);
+
+                let fd = fcntl::open("/etc/passwd", OFlag::O_RDONLY, Mode::empty()).unwrap();
+                let mut buf = [0u8; 4];
+                assert_eq!(preadv2(fd, &mut buf, 4, RWF_NOWAIT), Ok(4));
+                assert_eq!(&buf[..], &content[4..8]);
+                // The unknown flags are rejected by the kernel itself.
+                assert_eq!(
+                    preadv2(fd, &mut buf, 0, 0x4000_0000),
+                    Err(nix::errno::Errno::EOPNOTSUPP)
+                );
+                unistd::close(fd).unwrap();
+            },
+        )
+    }
+
+    #[test]
+    fn test_passthrough_fallocate() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let temp_file = TempFile::new("prefix_test_passthrough_fallocate");
+                let file_path = temp_file.path.to_str().unwrap();
+                let fd = fcntl::open(
+                    file_path,
+                    OFlag::O_RDWR | OFlag::O_CREAT | OFlag::O_TRUNC,
+                    Mode::from_bits_truncate(0o644),
+                )
+                .unwrap();
+                assert_eq!(
+                    fcntl::fallocate(fd, fcntl::FallocateFlags::empty(), 0, 8192),
+                    Ok(())
+                );
+                unistd::close(fd).unwrap();
+                assert_eq!(std::fs::metadata(file_path).unwrap().len(), 8192);
+
+                // The mode is checked by the kernel against the fd.
+                let fd = fcntl::open(file_path, OFlag::O_RDONLY, Mode::empty()).unwrap();
+                assert_eq!(
+                    fcntl::fallocate(
+                        fd,
+                        fcntl::FallocateFlags::FALLOC_FL_PUNCH_HOLE
+                            | fcntl::FallocateFlags::FALLOC_FL_KEEP_SIZE,
+                        0,
+                        4096
+                    ),
+                    Err(nix::errno::Errno::EBADF)
+                );
+                unistd::close(fd).unwrap();
+            },
+        )
+    }
+}
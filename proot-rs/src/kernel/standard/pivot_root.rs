@@ -1,11 +1,99 @@
-use crate::errors::Result;
+use crate::errors::*;
+use crate::process::tracee::Tracee;
+use crate::register::{PtraceReader, SysArg1, SysArg2, SysResult, Word};
+
+/// `pivot_root(new_root, put_old)` is emulated by changing the root binding of
+/// the tracee (see `FileSystem::pivot_root()`), so that a container init can
+/// run in the sandbox without changing the mounts of the host.
+pub fn enter(tracee: &mut Tracee) -> Result<()> {
+    let raw_new_root = tracee.regs.get_sysarg_path(SysArg1)?;
+    let raw_put_old = tracee.regs.get_sysarg_path(SysArg2)?;
+
+    let (new_root, new_root_host) = tracee.translate_path_at(libc::AT_FDCWD, raw_new_root, true)?;
+    let (put_old, put_old_host) = tracee.translate_path_at(libc::AT_FDCWD, raw_put_old, true)?;
+
+    // Same checks as the kernel, except for the ones about mount points.
+    if !new_root_host.metadata()?.is_dir() || !put_old_host.metadata()?.is_dir() {
+        return Err(Error::errno(ENOTDIR));
+    }
+
+    tracee.fs.borrow_mut().pivot_root(new_root, put_old)?;
+
+    // Avoid this syscall
+    tracee
+        .regs
+        .cancel_syscall("Cancel pivot_root since it is fully emulated");
 
-pub fn enter() -> Result<()> {
     Ok(())
+}
+
+pub fn exit(tracee: &mut Tracee) -> Result<()> {
+    // This syscall is fully emulated, see method `enter()` above.
+    tracee.regs.set(
+        SysResult,
+        0 as Word,
+        "update return value in pivot_root::exit()",
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use nix::sys::wait::{self, WaitStatus};
+    use nix::unistd::{self, ForkResult};
+
+    use crate::filesystem::temp::TempDir;
+    use crate::utils::tests::test_with_proot;
+
+    #[test]
+    fn test_pivot_root_to_subdir() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let dir = TempDir::new("prefix_test_pivot_root_to_subdir");
+                dir.create_dir().unwrap();
+                let dir_path = dir.path.to_str().unwrap();
+                std::fs::create_dir(format!("{}/put_old", dir_path)).unwrap();
+                std::fs::write(format!("{}/file", dir_path), b"new root").unwrap();
 
-    //                 status = translate_sysarg(tracee, SYSARG_1, REGULAR);
-    //                 if (status < 0)
-    //                     break;
-    //
-    //                 status = translate_sysarg(tracee, SYSARG_2, REGULAR);
+                // The root is changed in a child process, which does not share its
+                // `FileSystem` with this one, so that the cleanup can still be done here.
+                match unsafe { unistd::fork() }.unwrap() {
+                    ForkResult::Child => {
+                        let result = std::panic::catch_unwind(|| {
+                            let new_root = CString::new(dir_path).unwrap();
+                            let put_old = CString::new(format!("{}/put_old", dir_path)).unwrap();
+                            assert_eq!(
+                                unsafe {
+                                    libc::syscall(
+                                        libc::SYS_pivot_root,
+                                        new_root.as_ptr(),
+                                        put_old.as_ptr(),
+                                    )
+                                },
+                                0
+                            );
+                            // `/` now resolves to the new root.
+                            assert_eq!(std::fs::read("/file").unwrap(), b"new root");
+                            // The old root is available under `put_old`.
+                            assert!(std::path::Path::new("/put_old/bin/sh").exists());
+                            assert_eq!(
+                                std::fs::read(format!("/put_old{}/file", dir_path)).unwrap(),
+                                b"new root"
+                            );
+                            assert_eq!(unistd::getcwd().unwrap(), std::path::Path::new("/"));
+                        });
+                        unsafe { libc::_exit(if result.is_ok() { 0 } else { 1 }) };
+                    }
+                    ForkResult::Parent { child } => {
+                        assert_eq!(wait::waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+                    }
+                }
+                // The root of this process is not changed.
+                assert!(std::path::Path::new("/tmp").exists());
+            },
+        )
+    }
 }
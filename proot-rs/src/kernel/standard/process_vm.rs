@@ -0,0 +1,88 @@
+use libc::pid_t;
+
+use crate::errors::*;
+use crate::process::tracee::Tracee;
+use crate::register::{Current, SysArg, SysArg1};
+
+/// `process_vm_readv()` and `process_vm_writev()` take the pid of the process
+/// whose memory is accessed. The pids are not virtualized by proot-rs (the
+/// guest sees the pids of the host), so this pid is forwarded as is, and the
+/// kernel checks the permissions as usual.
+///
+/// The only exception is proot-rs itself: it is not a process of the guest,
+/// so accessing its memory is rejected with `EPERM`, as if it was not allowed
+/// by the kernel.
+pub fn enter(tracee: &Tracee) -> Result<()> {
+    let pid = tracee.regs.get(Current, SysArg(SysArg1)) as pid_t;
+    if pid == std::process::id() as pid_t {
+        return Err(Error::errno_with_msg(
+            EPERM,
+            format!(
+                "syscall {} on the memory of proot-rs",
+                tracee.regs.get_sys_num(Current)
+            ),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use nix::sys::signal::{kill, Signal};
+    use nix::sys::wait::waitpid;
+    use nix::unistd::{fork, getppid, pipe, read, write, ForkResult};
+
+    use super::*;
+    use crate::utils::tests::test_with_proot;
+
+    /// Reads `buffer.len()` bytes at `address` in the process `pid`.
+    fn read_remote(pid: pid_t, address: *const u8, buffer: &mut [u8]) -> isize {
+        let local = libc::iovec {
+            iov_base: buffer.as_mut_ptr() as *mut libc::c_void,
+            iov_len: buffer.len(),
+        };
+        let remote = libc::iovec {
+            iov_base: address as *mut libc::c_void,
+            iov_len: buffer.len(),
+        };
+        unsafe { libc::process_vm_readv(pid, &local, 1, &remote, 1, 0) }
+    }
+
+    #[test]
+    fn test_process_vm_readv_on_another_tracee() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let mut data = *b"parent";
+                let address = data.as_ptr();
+                let (read_end, write_end) = pipe().unwrap();
+
+                match unsafe { fork() }.unwrap() {
+                    ForkResult::Child => {
+                        // The child has its own copy at the same address.
+                        data.copy_from_slice(b"child!");
+                        write(write_end, b"x").unwrap();
+                        loop {
+                            std::thread::park();
+                        }
+                    }
+                    ForkResult::Parent { child } => {
+                        read(read_end, &mut [0]).unwrap();
+
+                        let mut buffer = [0u8; 6];
+                        assert_eq!(read_remote(child.as_raw(), address, &mut buffer), 6);
+                        assert_eq!(&buffer, b"child!");
+                        assert_eq!(&data, b"parent");
+
+                        kill(child, Signal::SIGKILL).unwrap();
+                        waitpid(child, None).unwrap();
+
+                        // The parent of the init process is proot-rs.
+                        assert_eq!(read_remote(getppid().as_raw(), address, &mut buffer), -1);
+                        assert_eq!(Errno::last(), Errno::EPERM);
+                    }
+                }
+            },
+        )
+    }
+}
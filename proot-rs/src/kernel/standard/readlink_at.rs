@@ -1,84 +1,247 @@
-use crate::errors::Result;
-
-pub fn exit() -> Result<()> {
-    // TODO: Find out why we need to call detranslate_path() on the result of
-    // readlinkat() here.
-
-    //    char referee[PATH_MAX];
-    //    char referer[PATH_MAX];
-    //    size_t old_size;
-    //    size_t new_size;
-    //    size_t max_size;
-    //    word_t input;
-    //    word_t output;
-    //
-    //    /* Error reported by the kernel.  */
-    //    if ((int) syscall_result < 0)
-    //        return SyscallExitResult::None;
-    //
-    //    old_size = syscall_result;
-    //
-    //    if (syscall_number == PR_readlink) {
-    //        output   = peek_reg(tracee, ORIGINAL, SYSARG_2);
-    //        max_size = peek_reg(tracee, ORIGINAL, SYSARG_3);
-    //        input    = peek_reg(tracee, MODIFIED, SYSARG_1);
-    //    }
-    //    else {
-    //        output   = peek_reg(tracee, ORIGINAL,  SYSARG_3);
-    //        max_size = peek_reg(tracee, ORIGINAL, SYSARG_4);
-    //        input    = peek_reg(tracee, MODIFIED, SYSARG_2);
-    //    }
-    //
-    //    if (max_size > PATH_MAX)
-    //        max_size = PATH_MAX;
-    //
-    //    if (max_size == 0) {
-    //        return SyscallExitResult::Value(-EINVAL);
-    //    }
-    //
-    //    /* The kernel does NOT put the NULL terminating byte for
-    //     * readlink(2).  */
-    //    status = read_data(tracee, referee, output, old_size);
-    //    if (status < 0)
-    //        break;
-    //    referee[old_size] = '\0';
-    //
-    //    /* Not optimal but safe (path is fully translated).  */
-    //    status = read_path(tracee, referer, input);
-    //    if (status < 0)
-    //        return SyscallExitResult::Value(status);
-    //
-    //    if (status >= PATH_MAX) {
-    //        return SyscallExitResult::Value(-ENAMETOOLONG);
-    //    }
-    //
-    //    status = detranslate_path(tracee, referee, referer);
-    //    if (status < 0)
-    //        return SyscallExitResult::Value(status);
-    //
-    //    /* The original path doesn't require any transformation, i.e
-    //     * it is a symetric binding.  */
-    //    if (status == 0)
-    //        return SyscallExitResult::None;
-    //
-    //    /* Overwrite the path.  Note: the output buffer might be
-    //     * initialized with zeros but it was updated with the kernel
-    //     * result, and then with the detranslated result.  This later
-    //     * might be shorter than the former, so it's safier to add a
-    //     * NULL terminating byte when possible.  This problem was
-    //     * exposed by IDA Demo 6.3.  */
-    //    if ((size_t) status < max_size) {
-    //        new_size = status - 1;
-    //        status = write_data(tracee, output, referee, status);
-    //    }
-    //    else {
-    //        new_size = max_size;
-    //        status = write_data(tracee, output, referee, max_size);
-    //    }
-    //    if (status < 0)
-    //        return SyscallExitResult::Value(status);
-    //
-    // The value of "status" is used to update the returned value in
-    // translate_syscall_exit().
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::prelude::RawFd;
+use std::path::{Path, PathBuf};
+
+use libc::{c_void, PATH_MAX};
+
+use crate::errors::*;
+use crate::filesystem::binding::Side;
+use crate::filesystem::ext::PathExt;
+use crate::filesystem::Translator;
+use crate::process::tracee::Tracee;
+use crate::register::{Current, Original, PtraceReader, PtraceWriter, SysArg, SysResult, Word};
+use crate::register::{SysArg1, SysArg2, SysArg3, SysArg4};
+
+/// Unlike the other syscalls of the `*at()` family, `readlinkat()` always
+/// accepts an empty path, which refers to the symlink `dirfd` itself (opened
+/// with `O_PATH | O_NOFOLLOW`): it is left to the kernel, as an empty path
+/// with `AT_EMPTY_PATH` is (see `Tracee::is_empty_path_of_fd()`). This includes
+/// `AT_FDCWD`, as the cwd is never a symlink, for which the kernel fails with
+/// `ENOENT`.
+pub fn enter(tracee: &mut Tracee) -> Result<()> {
+    let dirfd = tracee.regs.get(Current, SysArg(SysArg1)) as RawFd;
+    let raw_path = tracee.regs.get_sysarg_path(SysArg2)?;
+    if raw_path.as_os_str().is_empty() {
+        return Ok(());
+    }
+    let deref_final = raw_path.with_trailing_slash();
+
+    let host_path = tracee.translate_path_at(dirfd, raw_path, deref_final)?.1;
+
+    tracee.regs.set_sysarg_path(
+        SysArg2,
+        &host_path,
+        "during enter readlink_at translation, setting host path",
+    )?;
+
+    Ok(())
+}
+
+/// The target of the symlink read by the kernel is a host path (e.g. the path
+/// of the file of `/proc/self/fd/N`, or an absolute symlink inside a binding),
+/// so it is detranslated before being returned to the tracee.
+pub fn exit(tracee: &mut Tracee) -> Result<()> {
+    // Error reported by the kernel.
+    let old_size = tracee.regs.get(Current, SysResult) as isize;
+    if old_size < 0 {
+        return Ok(());
+    }
+
+    let (output, max_size, input) = if tracee.regs.get_sys_num(Original) == sc::nr::READLINKAT {
+        (
+            tracee.regs.get(Original, SysArg(SysArg3)),
+            tracee.regs.get(Original, SysArg(SysArg4)) as usize,
+            SysArg2,
+        )
+    } else {
+        (
+            tracee.regs.get(Original, SysArg(SysArg2)),
+            tracee.regs.get(Original, SysArg(SysArg3)) as usize,
+            SysArg1,
+        )
+    };
+    let max_size = max_size.min(PATH_MAX as usize);
+    if max_size == 0 {
+        return Err(Error::errno(EINVAL));
+    }
+
+    // The kernel does not put a null byte at the end of the target.
+    let referee = tracee
+        .regs
+        .read_data(output as *const c_void, old_size as usize)?;
+    // The path given to the kernel, i.e. the host path of the symlink, or
+    // the one of `dirfd` if it is the symlink itself.
+    let mut referrer = tracee.regs.get_sysarg_path(input)?;
+    if referrer.as_os_str().is_empty() && tracee.regs.get_sys_num(Original) == sc::nr::READLINKAT {
+        let dirfd = tracee.regs.get(Original, SysArg(SysArg1)) as RawFd;
+        referrer = match tracee.get_path_from_fd(dirfd, Side::Host) {
+            Ok(host_path) => host_path,
+            Err(_) => return Ok(()),
+        };
+    }
+
+    let guest_referee = match emulated_proc_link_target(tracee, &referrer) {
+        Some(guest_referee) => guest_referee,
+        None => match tracee
+            .fs
+            .borrow()
+            .detranslate_path(Path::new(OsStr::from_bytes(&referee)), Some(&referrer))?
+        {
+            Some(guest_referee) => guest_referee,
+            // The target does not need any transformation.
+            None => return Ok(()),
+        },
+    };
+    let guest_referee = guest_referee.as_os_str().as_bytes();
+    if guest_referee == &referee[..] {
+        return Ok(());
+    }
+
+    // As the kernel does, the target is truncated to the size of the buffer,
+    // without null byte.
+    let new_size = guest_referee.len().min(max_size);
+    tracee
+        .regs
+        .write_data(output as *mut c_void, &guest_referee[..new_size], false)?;
+    tracee.regs.set(
+        SysResult,
+        new_size as Word,
+        "update return value in readlink_at::exit()",
+    );
     Ok(())
 }
+
+/// Returns the guest target of `referrer` if it is one of the links of
+/// `/proc/<pid>` emulated for the tracee, whose target on the host is wrong
+/// (e.g. `cwd`, since the cwd of the guest is never changed on the host).
+fn emulated_proc_link_target(tracee: &Tracee, referrer: &Path) -> Option<PathBuf> {
+    if !tracee.proc_emulation {
+        return None;
+    }
+    let name = referrer
+        .strip_prefix(Path::new("/proc").join(tracee.pid.to_string()))
+        .ok()?;
+    tracee.get_proc_link_target(name.as_os_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    use nix::errno::Errno;
+    use nix::fcntl::{open, readlink, readlinkat, OFlag};
+    use nix::sys::stat::Mode;
+
+    use crate::filesystem::synthetic_proc::create_synthetic_proc;
+    use crate::filesystem::temp::TempDir;
+    use crate::filesystem::FileSystem;
+    use crate::utils::tests::{get_test_rootfs_path, test_with_proot_and_fs};
+
+    #[test]
+    fn test_readlink_proc_self_fd_in_binding() {
+        test_with_proot_and_fs(
+            |fs| fs.add_binding("/etc", "/home").unwrap(),
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let fd = open("/home/passwd", OFlag::O_RDONLY, Mode::empty()).unwrap();
+                for link in &[
+                    format!("/proc/self/fd/{}", fd),
+                    format!("/dev/fd/{}", fd),
+                    format!("/proc/{}/fd/{}", std::process::id(), fd),
+                ] {
+                    assert_eq!(
+                        readlink(link.as_str()).map(PathBuf::from),
+                        Ok(PathBuf::from("/home/passwd")),
+                        "{}",
+                        link
+                    );
+                }
+
+                // The fds without path are left as the kernel reports them.
+                let (read_fd, _write_fd) = nix::unistd::pipe().unwrap();
+                let target = readlink(format!("/proc/self/fd/{}", read_fd).as_str()).unwrap();
+                assert!(target.to_str().unwrap().starts_with("pipe:["));
+            },
+        )
+    }
+
+    #[test]
+    fn test_readlink_synthetic_proc_self() {
+        // Created out of the subprocess of the test, so that it is deleted.
+        let fs = FileSystem::with_root(get_test_rootfs_path()).unwrap();
+        let proc = create_synthetic_proc(&fs).unwrap();
+        test_with_proot_and_fs(
+            |fs| fs.add_binding(&proc.path, "/proc").unwrap(),
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                // The links of `/proc/self` are emulated, even though they do
+                // not exist in the synthetic /proc.
+                nix::unistd::chdir("/etc").unwrap();
+                assert_eq!(
+                    readlink("/proc/self/cwd").map(PathBuf::from),
+                    Ok(PathBuf::from("/etc"))
+                );
+                assert!(std::fs::metadata("/proc/self/cwd/passwd").is_ok());
+
+                // The exe is the one of the guest, as executed by the kernel:
+                // busybox, or one of its hard links.
+                let output = Command::new("/bin/readlink")
+                    .arg("/proc/self/exe")
+                    .output()
+                    .unwrap();
+                assert!(output.status.success());
+                let exe = String::from_utf8(output.stdout).unwrap();
+                assert!(
+                    exe == "/bin/readlink\n" || exe == "/bin/busybox\n",
+                    "{}",
+                    exe
+                );
+
+                let mounts = std::fs::read_to_string("/proc/mounts").unwrap();
+                assert!(mounts.starts_with("rootfs / rootfs"));
+                assert!(std::fs::metadata("/proc/cpuinfo").is_ok());
+            },
+        )
+    }
+
+    #[test]
+    fn test_readlinkat_empty_path_of_symlink_fd() {
+        // Created out of the subprocess of the test, so that it is deleted.
+        let dir = TempDir::new("test_readlinkat_empty_path_of_symlink_fd");
+        dir.create_dir().unwrap();
+        // An absolute symlink of the host, inside the binding.
+        std::os::unix::fs::symlink(dir.path.join("target"), dir.path.join("link")).unwrap();
+        test_with_proot_and_fs(
+            |fs| fs.add_binding(&dir.path, "/home").unwrap(),
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                assert_eq!(
+                    readlink("/home/link").map(PathBuf::from),
+                    Ok(PathBuf::from("/home/target"))
+                );
+
+                // The fd of the symlink itself.
+                let fd = open(
+                    "/home/link",
+                    OFlag::O_PATH | OFlag::O_NOFOLLOW,
+                    Mode::empty(),
+                )
+                .unwrap();
+                assert_eq!(
+                    readlinkat(fd, "").map(PathBuf::from),
+                    Ok(PathBuf::from("/home/target"))
+                );
+                // Same through the magic link of the fd, which is the symlink.
+                assert_eq!(
+                    readlink(format!("/proc/self/fd/{}", fd).as_str()).map(PathBuf::from),
+                    Ok(PathBuf::from("/home/link"))
+                );
+
+                // Not a symlink.
+                assert_eq!(readlinkat(libc::AT_FDCWD, ""), Err(Errno::ENOENT));
+            },
+        )
+    }
+}
@@ -5,27 +5,49 @@ use crate::filesystem::ext::PathExt;
 use crate::kernel::standard::link_rename;
 use crate::process::tracee::Tracee;
 use crate::register::PtraceWriter;
-use crate::register::{Current, PtraceReader, SysArg, SysArg1, SysArg2, SysArg3, SysArg4};
+use crate::register::{
+    Current, PtraceReader, SysArg, SysArg1, SysArg2, SysArg3, SysArg4, SysArg5, Word,
+};
 
+/// Handles `renameat()` and `renameat2()`.
+///
+/// The flags of `renameat2()` (i.e. `RENAME_NOREPLACE`, `RENAME_EXCHANGE` and
+/// `RENAME_WHITEOUT`) are passed through, and so are the errors of the kernel:
+/// `EEXIST` when the new path exists with `RENAME_NOREPLACE`, `EINVAL` when the
+/// file-system does not support a flag, and `EXDEV` when the paths are on
+/// different file-systems (e.g. in different bindings).
 pub fn enter(tracee: &mut Tracee) -> Result<()> {
+    let sys_num = tracee.regs.get_sys_num(Current);
     let olddirfd = tracee.regs.get(Current, SysArg(SysArg1)) as RawFd;
     let newdirfd = tracee.regs.get(Current, SysArg(SysArg3)) as RawFd;
     let old_path = tracee.regs.get_sysarg_path(SysArg2)?;
     let new_path = tracee.regs.get_sysarg_path(SysArg4)?;
+    let flags = if sys_num == sc::nr::RENAMEAT2 {
+        tracee.regs.get(Current, SysArg(SysArg5))
+    } else {
+        0
+    };
 
-    let deref_final = old_path.with_trailing_slash();
+    let old_deref_final = old_path.with_trailing_slash();
+    // With `RENAME_EXCHANGE`, the new path is also an existing file which is
+    // moved, so it is handled like the old path.
+    let new_deref_final =
+        flags & libc::RENAME_EXCHANGE as Word != 0 && new_path.with_trailing_slash();
 
-    let old_host_path = tracee.translate_path_at(olddirfd, old_path, deref_final)?.1;
-    let new_host_path = tracee.translate_path_at(newdirfd, new_path, false)?.1;
+    let host_paths = tracee.translate_paths_at(&[
+        (olddirfd, old_path, old_deref_final),
+        (newdirfd, new_path, new_deref_final),
+    ])?;
+    let (old_host_path, new_host_path) = (&host_paths[0].1, &host_paths[1].1);
 
     tracee.regs.set_sysarg_path(
         SysArg2,
-        &old_host_path,
+        old_host_path,
         "during enter open translation, setting host path",
     )?;
     tracee.regs.set_sysarg_path(
         SysArg4,
-        &new_host_path,
+        new_host_path,
         "during enter open translation, setting host path",
     )?;
 
@@ -35,3 +57,77 @@ pub fn enter(tracee: &mut Tracee) -> Result<()> {
 pub fn exit(tracee: &mut Tracee) -> Result<()> {
     link_rename::exit(tracee)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use crate::errors::*;
+    use crate::filesystem::temp::TempDir;
+    use crate::utils::tests::test_with_proot;
+
+    fn renameat2(old_path: &str, new_path: &str, flags: u32) -> Result<()> {
+        let old_path = CString::new(old_path).unwrap();
+        let new_path = CString::new(new_path).unwrap();
+        let result = unsafe {
+            libc::syscall(
+                libc::SYS_renameat2,
+                libc::AT_FDCWD,
+                old_path.as_ptr(),
+                libc::AT_FDCWD,
+                new_path.as_ptr(),
+                flags,
+            )
+        };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(Error::errno(Errno::last()))
+        }
+    }
+
+    #[test]
+    fn test_renameat2_noreplace_and_exchange() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let dir = TempDir::new("prefix_test_renameat2_noreplace_and_exchange");
+                dir.create_dir().unwrap();
+                let dir_path = dir.path.to_str().unwrap();
+                let path_a = &format!("{}/a", dir_path);
+                let path_b = &format!("{}/b", dir_path);
+                let path_c = &format!("{}/c", dir_path);
+
+                std::fs::write(path_a, b"a").unwrap();
+                std::fs::write(path_b, b"b").unwrap();
+
+                // RENAME_NOREPLACE
+                assert_eq!(
+                    renameat2(path_a, path_b, libc::RENAME_NOREPLACE as u32),
+                    Err(Error::errno(Errno::EEXIST))
+                );
+                assert_eq!(std::fs::read(path_b).unwrap(), b"b");
+                assert_eq!(
+                    renameat2(path_a, path_c, libc::RENAME_NOREPLACE as u32),
+                    Ok(())
+                );
+                assert!(!std::path::Path::new(path_a).exists());
+                assert_eq!(std::fs::read(path_c).unwrap(), b"a");
+
+                // RENAME_EXCHANGE, which needs the support of the file-system
+                match renameat2(path_b, path_c, libc::RENAME_EXCHANGE as u32) {
+                    Err(error) if error.get_errno() == Errno::EINVAL => {}
+                    result => {
+                        assert_eq!(result, Ok(()));
+                        assert_eq!(std::fs::read(path_b).unwrap(), b"a");
+                        assert_eq!(std::fs::read(path_c).unwrap(), b"b");
+                    }
+                }
+                assert_eq!(
+                    renameat2(path_a, path_c, libc::RENAME_EXCHANGE as u32),
+                    Err(Error::errno(Errno::ENOENT))
+                );
+            },
+        )
+    }
+}
@@ -0,0 +1,148 @@
+use std::mem::size_of;
+
+use libc::c_void;
+use nix::sched::CpuSet;
+
+use crate::errors::*;
+use crate::process::proot::InfoBag;
+use crate::process::tracee::Tracee;
+use crate::register::{Current, PtraceReader, PtraceWriter, SysArg, SysArg2, SysArg3, Word};
+
+/// `getcpu()`, `sched_getaffinity()` and `sched_setaffinity()` are forwarded
+/// as is, unless the `--cpus` option is used.
+///
+/// In that case, the mask given to `sched_setaffinity()` is clamped to the
+/// configured cpus, so that the guest cannot escape from them (the init
+/// process already inherits the restricted affinity of proot-rs). As the
+/// kernel does, a mask without any of the allowed cpus is rejected with
+/// `EINVAL`.
+pub fn enter(tracee: &mut Tracee, info_bag: &InfoBag) -> Result<()> {
+    let cpus = match info_bag.cpus {
+        Some(ref cpus) => cpus,
+        None => return Ok(()),
+    };
+    if tracee.regs.get_sys_num(Current) != sc::nr::SCHED_SETAFFINITY {
+        return Ok(());
+    }
+
+    // Bits beyond `CpuSet::count()` cannot be allowed anyway.
+    let length = (tracee.regs.get(Current, SysArg(SysArg2)) as usize).min(size_of::<CpuSet>());
+    let address = tracee.regs.get(Current, SysArg(SysArg3)) as *const c_void;
+    let mask = tracee.regs.read_data(address, length)?;
+
+    let clamped_mask = clamp_mask(&mask, cpus);
+    if clamped_mask.iter().all(|byte| *byte == 0) {
+        return Err(Error::errno_with_msg(
+            EINVAL,
+            "sched_setaffinity() with none of the cpus allowed by --cpus",
+        ));
+    }
+
+    tracee.regs.set_sysarg_data(
+        SysArg3,
+        &clamped_mask,
+        "clamping the cpu affinity mask",
+        false,
+    )?;
+    tracee.regs.set(
+        SysArg(SysArg2),
+        clamped_mask.len() as Word,
+        "clamping the cpu affinity mask",
+    );
+
+    Ok(())
+}
+
+/// Parses a cpu list as in `/sys/devices/system/cpu/online` (e.g. `0-3,6`).
+pub fn parse_cpu_list(list: &str) -> Result<CpuSet> {
+    let invalid = || Error::errno_with_msg(EINVAL, format!("invalid cpu list {:?}", list));
+
+    let mut cpus = CpuSet::new();
+    for part in list.split(',') {
+        let mut bounds = part.splitn(2, '-');
+        let first: usize = bounds
+            .next()
+            .unwrap()
+            .trim()
+            .parse()
+            .map_err(|_| invalid())?;
+        let last: usize = match bounds.next() {
+            Some(last) => last.trim().parse().map_err(|_| invalid())?,
+            None => first,
+        };
+        if first > last {
+            return Err(invalid());
+        }
+        for cpu in first..=last {
+            cpus.set(cpu).map_err(|_| invalid())?;
+        }
+    }
+    Ok(cpus)
+}
+
+/// Returns the bytes of the affinity `mask`, without the cpus which are not in
+/// `cpus`.
+fn clamp_mask(mask: &[u8], cpus: &CpuSet) -> Vec<u8> {
+    mask.iter()
+        .enumerate()
+        .map(|(index, byte)| {
+            (0..8)
+                .filter(|bit| cpus.is_set(index * 8 + bit).unwrap_or(false))
+                .fold(0u8, |clamped, bit| clamped | (byte & (1 << bit)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tests::test_with_proot_and_info_bag;
+    use nix::sched::{sched_getaffinity, sched_setaffinity};
+    use nix::unistd::Pid;
+
+    #[test]
+    fn test_parse_cpu_list_and_clamp_mask() {
+        let cpus = parse_cpu_list("1-3,9").unwrap();
+        assert!(!cpus.is_set(0).unwrap());
+        assert!(cpus.is_set(1).unwrap());
+        assert!(cpus.is_set(3).unwrap());
+        assert!(!cpus.is_set(4).unwrap());
+        assert!(cpus.is_set(9).unwrap());
+
+        assert!(parse_cpu_list("").is_err());
+        assert!(parse_cpu_list("3-1").is_err());
+        assert!(parse_cpu_list("0,a").is_err());
+        assert!(parse_cpu_list(&CpuSet::count().to_string()).is_err());
+
+        assert_eq!(clamp_mask(&[0xff, 0xff], &cpus), vec![0b1110, 0b10]);
+        assert_eq!(clamp_mask(&[0b0001, 0b01], &cpus), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_sched_setaffinity_is_clamped() {
+        let initial = sched_getaffinity(Pid::from_raw(0)).unwrap();
+        let allowed_cpu = (0..CpuSet::count())
+            .find(|cpu| initial.is_set(*cpu).unwrap())
+            .unwrap();
+        let mut allowed = CpuSet::new();
+        allowed.set(allowed_cpu).unwrap();
+
+        test_with_proot_and_info_bag(
+            move |info_bag| info_bag.cpus = Some(allowed),
+            |_tracee, _is_sysenter, _before_translation| {},
+            move || {
+                // Asking for all the cpus only gives the allowed one.
+                assert_eq!(sched_setaffinity(Pid::from_raw(0), &initial), Ok(()));
+                assert_eq!(sched_getaffinity(Pid::from_raw(0)), Ok(allowed));
+
+                // Asking for none of the allowed cpus is an error.
+                let mut others = initial;
+                others.unset(allowed_cpu).unwrap();
+                assert_eq!(
+                    sched_setaffinity(Pid::from_raw(0), &others),
+                    Err(Errno::EINVAL)
+                );
+            },
+        )
+    }
+}
@@ -0,0 +1,131 @@
+use libc::c_void;
+
+use crate::errors::*;
+use crate::process::tracee::Tracee;
+use crate::register::{Current, PtraceReader, SysArg, SysArg1, SysArg2, SysResult, Word};
+
+/// Maximum length of the `nodename` and `domainname` fields of `struct
+/// utsname`, not including the terminating null byte.
+/// https://elixir.bootlin.com/linux/v5.10.43/source/include/uapi/linux/utsname.h#L24
+pub const NEW_UTS_LEN: usize = 64;
+
+/// The hostname and domainname of the sandbox.
+///
+/// Since proot-rs does not create a new UTS namespace, a real
+/// `sethostname()`/`setdomainname()` would either fail or modify the names of
+/// the host (if proot-rs was started by a privileged user). Instead, the
+/// requested names are recorded here, and are reported back to tracees by
+/// `uname()`. A `None` value means that the name has not been changed, so the
+/// one of the host is reported.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct VirtualUts {
+    pub hostname: Option<Vec<u8>>,
+    pub domainname: Option<Vec<u8>>,
+}
+
+pub fn enter(tracee: &mut Tracee) -> Result<()> {
+    let sys_num = tracee.regs.get_sys_num(Current);
+    let name_addr = tracee.regs.get(Current, SysArg(SysArg1));
+    // The `len` argument is an `int` in the kernel.
+    let len = tracee.regs.get(Current, SysArg(SysArg2)) as i32;
+
+    // Same checks as the kernel.
+    // https://elixir.bootlin.com/linux/v5.10.43/source/kernel/sys.c#L1321
+    if len < 0 || len as usize > NEW_UTS_LEN {
+        return Err(Error::errno(EINVAL));
+    }
+    let name = tracee
+        .regs
+        .read_data(name_addr as *const c_void, len as usize)?;
+
+    let mut uts = tracee.uts.borrow_mut();
+    if sys_num == sc::nr::SETHOSTNAME {
+        uts.hostname = Some(name);
+    } else if sys_num == sc::nr::SETDOMAINNAME {
+        uts.domainname = Some(name);
+    } else {
+        return Err(Error::errno_with_msg(
+            ENOSYS,
+            format!(
+                "sysno should be SETHOSTNAME({}) or SETDOMAINNAME({}), but got {}",
+                sc::nr::SETHOSTNAME,
+                sc::nr::SETDOMAINNAME,
+                sys_num
+            ),
+        ));
+    }
+
+    // Avoid this syscall
+    tracee
+        .regs
+        .cancel_syscall("Cancel sethostname since it is fully emulated");
+
+    Ok(())
+}
+
+pub fn exit(tracee: &mut Tracee) -> Result<()> {
+    // This syscall is fully emulated, see method `enter()` above.
+    tracee.regs.set(
+        SysResult,
+        0 as Word,
+        "update return value in sethostname::exit()",
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tests::test_with_proot;
+    use nix::sys::utsname::uname;
+    use nix::unistd::{gethostname, sethostname};
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    #[test]
+    fn test_sethostname_setdomainname_and_uname() {
+        let host_uname = uname().unwrap();
+
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                // Before any change, the names of the host are reported.
+                let mut buf = [0u8; NEW_UTS_LEN + 1];
+                assert_eq!(
+                    gethostname(&mut buf).unwrap().to_bytes(),
+                    host_uname.nodename().as_bytes()
+                );
+
+                assert_eq!(sethostname("guest-hostname"), Ok(()));
+                assert_eq!(gethostname(&mut buf).unwrap().to_bytes(), b"guest-hostname");
+                assert_eq!(uname().unwrap().nodename(), OsStr::new("guest-hostname"));
+
+                let domainname = "guest-domainname";
+                assert_eq!(
+                    unsafe {
+                        libc::setdomainname(domainname.as_ptr() as *const _, domainname.len())
+                    },
+                    0
+                );
+                let mut utsname: libc::utsname = unsafe { std::mem::zeroed() };
+                assert_eq!(unsafe { libc::uname(&mut utsname) }, 0);
+                let reported = unsafe { std::ffi::CStr::from_ptr(utsname.domainname.as_ptr()) };
+                assert_eq!(reported.to_bytes(), domainname.as_bytes());
+
+                // Other fields are not modified.
+                assert_eq!(uname().unwrap().sysname(), host_uname.sysname());
+                assert_eq!(uname().unwrap().release(), host_uname.release());
+
+                // Names which are too long are rejected, like the kernel does.
+                assert_eq!(
+                    sethostname(String::from_utf8(vec![b'a'; NEW_UTS_LEN + 1]).unwrap()),
+                    Err(nix::errno::Errno::EINVAL)
+                );
+                assert_eq!(uname().unwrap().nodename(), OsStr::new("guest-hostname"));
+            },
+        );
+
+        // The host is not affected.
+        assert_eq!(uname().unwrap().nodename(), host_uname.nodename());
+    }
+}
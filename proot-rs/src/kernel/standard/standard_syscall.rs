@@ -1,34 +1,22 @@
-use crate::errors::*;
-
-use crate::filesystem::Translator;
-use crate::process::tracee::Tracee;
-use crate::register::PtraceWriter;
-use crate::register::{PtraceReader, SysArg1};
-
-pub fn enter(tracee: &mut Tracee) -> Result<()> {
-    let raw_path = tracee.regs.get_sysarg_path(SysArg1)?;
-
-    let host_path = tracee.fs.borrow().translate_path(raw_path, true)?.1;
-
-    tracee.regs.set_sysarg_path(
-        SysArg1,
-        &host_path,
-        "during enter open translation, setting host path",
-    )?;
-
-    Ok(())
-}
+translate_path_handler!(
+    path: SysArg1,
+    deref_final: |_tracee, _raw_path| true,
+);
 
 #[cfg(test)]
 mod tests {
+    use std::ffi::CString;
     use std::fs::File;
+    use std::path::PathBuf;
 
-    use crate::utils::tests::test_with_proot;
+    use crate::filesystem::temp::TempFile;
+    use crate::register::{Current, PtraceReader, SysArg1};
+    use crate::utils::tests::{get_test_rootfs_path, test_with_proot};
 
     /// Unit test for all the standard syscalls:
     /// access, acct, chmod, chown, chroot, getxattr, listxattr, mknod, creat,
-    /// removexattr, setxattr, stat, swapoff, swapon, truncate, umount2, uselib,
-    /// utime, utimes
+    /// removexattr, setxattr, stat, statfs, truncate, umount2, uselib, utime,
+    /// utimes
     ///
     /// Since the arguments of those syscalls follow a certain pattern, only the
     /// stat() call is tested in our unit tests.
@@ -63,4 +51,79 @@ mod tests {
             },
         )
     }
+
+    /// The syscalls whose first argument is a path, with the path given by
+    /// `test_first_arg_path_syscalls()` and the host path it is translated
+    /// to, relative to the rootfs.
+    ///
+    /// `chdir()` and `swapon()` are not in the list: they have their own
+    /// handlers, and do not forward the translated path to the kernel.
+    fn first_arg_path_matrix() -> Vec<(usize, &'static str, &'static str)> {
+        vec![
+            // The paths of acct() and chroot() do not exist, so that they fail
+            // even with privileges.
+            (
+                sc::nr::ACCT,
+                "/etc/../tmp/acct_for_test_first_arg_path",
+                "tmp/acct_for_test_first_arg_path",
+            ),
+            (
+                sc::nr::CHROOT,
+                "/etc/../tmp/chroot_for_test_first_arg_path",
+                "tmp/chroot_for_test_first_arg_path",
+            ),
+            (
+                sc::nr::TRUNCATE,
+                "/etc/../tmp/file_for_test_first_arg_path",
+                "tmp/file_for_test_first_arg_path",
+            ),
+            (sc::nr::STATFS, "/tmp/./../etc", "etc"),
+        ]
+    }
+
+    #[test]
+    fn test_first_arg_path_syscalls() {
+        test_with_proot(
+            |tracee, is_sysenter, before_translation| {
+                if !is_sysenter || before_translation {
+                    return;
+                }
+                let sys_num = tracee.regs.get_sys_num(Current);
+                if let Some((_, _, expected)) = first_arg_path_matrix()
+                    .into_iter()
+                    .find(|(matrix_sys_num, _, _)| *matrix_sys_num == sys_num)
+                {
+                    assert_eq!(
+                        tracee.regs.get_sysarg_path(SysArg1).unwrap(),
+                        get_test_rootfs_path().join(expected)
+                    );
+                }
+            },
+            || {
+                let matrix = first_arg_path_matrix();
+                let path = |sys_num: usize| {
+                    let (_, guest_path, _) = matrix
+                        .iter()
+                        .find(|(matrix_sys_num, _, _)| *matrix_sys_num == sys_num)
+                        .unwrap();
+                    CString::new(*guest_path).unwrap()
+                };
+                // The name is the one of the matrix. It is deleted even if an
+                // assertion fails.
+                let file = TempFile {
+                    path: PathBuf::from("/tmp/file_for_test_first_arg_path"),
+                };
+                std::fs::write(&file.path, b"content").unwrap();
+
+                unsafe {
+                    assert_eq!(libc::acct(path(sc::nr::ACCT).as_ptr()), -1);
+                    assert_eq!(libc::chroot(path(sc::nr::CHROOT).as_ptr()), -1);
+                    assert_eq!(libc::truncate(path(sc::nr::TRUNCATE).as_ptr(), 2), 0);
+                    assert_eq!(std::fs::read(&file.path).unwrap(), b"co");
+                    let mut statfs = std::mem::zeroed::<libc::statfs>();
+                    assert_eq!(libc::statfs(path(sc::nr::STATFS).as_ptr(), &mut statfs), 0);
+                }
+            },
+        )
+    }
 }
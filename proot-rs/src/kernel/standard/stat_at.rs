@@ -1,14 +1,24 @@
 use std::os::unix::prelude::RawFd;
 
+use libc::c_void;
 use nix::fcntl::AtFlags;
 
 use crate::errors::*;
+use crate::filesystem::binding::Side;
 use crate::filesystem::ext::PathExt;
 use crate::kernel::syscall;
 use crate::register::PtraceWriter;
-use crate::register::{Current, PtraceReader, SysArg, SysArg1, SysArg2, SysArg3, SysArg4, SysArg5};
+use crate::register::{Current, Original, PtraceReader, SysArg, SysResult};
+use crate::register::{SysArg1, SysArg2, SysArg3, SysArg4, SysArg5};
 use crate::{errors::Result, process::tracee::Tracee};
 
+/// `STATX_ATTR_MOUNT_ROOT` in `linux/stat.h` (Linux 5.8), not defined by the
+/// libc crate yet.
+const STATX_ATTR_MOUNT_ROOT: u64 = 0x2000;
+/// Offsets of `stx_attributes` and `stx_attributes_mask` in `struct statx`.
+const STX_ATTRIBUTES_OFFSET: usize = 8;
+const STX_ATTRIBUTES_MASK_OFFSET: usize = 56;
+
 pub fn enter(tracee: &mut Tracee) -> Result<()> {
     let sys_num = tracee.regs.get_sys_num(Current);
     let dirfd = tracee.regs.get(Current, SysArg(SysArg1)) as RawFd;
@@ -35,6 +45,9 @@ pub fn enter(tracee: &mut Tracee) -> Result<()> {
         }
     };
     let flags = AtFlags::from_bits_truncate(tracee.regs.get(Current, SysArg(flags_arg_index)) as _);
+    if Tracee::is_empty_path_of_fd(dirfd, &raw_path, flags.contains(AtFlags::AT_EMPTY_PATH)) {
+        return Ok(());
+    }
 
     // Determines whether we need to dereference a path if it is a symbolic link.
     // Some system calls will dereference the path by default, while others do not,
@@ -64,14 +77,60 @@ pub fn enter(tracee: &mut Tracee) -> Result<()> {
     Ok(())
 }
 
+/// A binding is a mount point in the guest, but its host path is usually not
+/// the root of a mount on the host (e.g. `-b /etc:/home`), so the
+/// `STATX_ATTR_MOUNT_ROOT` attribute reported by `statx()` is set for the
+/// bindings (including the root of the guest), as mount-aware tools (e.g.
+/// `findmnt` or `stat -c %m`) expect.
+///
+/// The attribute is left to the kernel for the other paths, since a real
+/// mount point under a binding is also one in the guest.
+pub fn exit(tracee: &mut Tracee) -> Result<()> {
+    if tracee.regs.get_sys_num(Original) != sc::nr::STATX {
+        return Ok(());
+    }
+    // Error reported by the kernel.
+    if tracee.regs.get(Current, SysResult) != 0 {
+        return Ok(());
+    }
+    // The path given to the kernel, i.e. the host path.
+    let host_path = tracee.regs.get_sysarg_path(SysArg2)?;
+    let is_binding = tracee
+        .fs
+        .borrow()
+        .get_bindings()
+        .any(|binding| *binding.get_path(Side::Host) == host_path);
+    if !is_binding {
+        return Ok(());
+    }
+
+    let statxbuf = tracee.regs.get(Original, SysArg(SysArg5)) as usize;
+    for &offset in &[STX_ATTRIBUTES_OFFSET, STX_ATTRIBUTES_MASK_OFFSET] {
+        let address = (statxbuf + offset) as *mut c_void;
+        let bytes = tracee.regs.read_data(address, 8)?;
+        let mut attributes = [0u8; 8];
+        attributes.copy_from_slice(&bytes);
+        let attributes = u64::from_ne_bytes(attributes) | STATX_ATTR_MOUNT_ROOT;
+        tracee
+            .regs
+            .write_data(address, &attributes.to_ne_bytes(), false)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs::File;
+    use std::path::PathBuf;
 
     use nc::file_handle_t;
+    use nix::fcntl::readlinkat;
+    use nix::sys::stat::fstatat;
     use nix::{fcntl::OFlag, sys::stat::Mode};
 
-    use crate::utils::tests::test_with_proot;
+    use super::*;
+    use crate::filesystem::temp::TempDir;
+    use crate::utils::tests::{test_with_proot, test_with_proot_and_fs};
 
     // TODO: reference MAX_HANDLE_SZ which is defined in <fcntl.h>. see:
     // https://elixir.bootlin.com/linux/v5.12.12/source/include/linux/exportfs.h#L15
@@ -214,4 +273,73 @@ mod tests {
             },
         )
     }
+
+    #[test]
+    fn test_stat_at_empty_path_of_o_path_fd() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let dir = TempDir::new("prefix_test_stat_at_empty_path_of_o_path_fd");
+                dir.create_dir().unwrap();
+                let filepath = &format!("{}/file", dir.path.to_str().unwrap());
+                let filename = "file";
+                let linkpath = &format!("{}/link", dir.path.to_str().unwrap());
+
+                File::create(filepath).unwrap();
+                std::os::unix::fs::symlink(filename, linkpath).unwrap();
+
+                // An `O_PATH` fd of the symlink itself.
+                let fd =
+                    nix::fcntl::open(linkpath, OFlag::O_PATH | OFlag::O_NOFOLLOW, Mode::empty())
+                        .unwrap();
+                let stat = fstatat(fd, "", AtFlags::AT_EMPTY_PATH).unwrap();
+                assert_eq!(stat.st_mode & libc::S_IFMT, libc::S_IFLNK);
+                assert_eq!(
+                    readlinkat(fd, "").map(PathBuf::from),
+                    Ok(PathBuf::from(filename))
+                );
+                // Without `AT_EMPTY_PATH`, an empty path is an error.
+                assert_eq!(fstatat(fd, "", AtFlags::empty()), Err(Errno::ENOENT));
+
+                // An `O_PATH` fd of a file which has no name anymore.
+                let fd = nix::fcntl::open(filepath, OFlag::O_PATH, Mode::empty()).unwrap();
+                std::fs::remove_file(filepath).unwrap();
+                let stat = fstatat(fd, "", AtFlags::AT_EMPTY_PATH).unwrap();
+                assert_eq!(stat.st_mode & libc::S_IFMT, libc::S_IFREG);
+                assert_eq!(stat.st_nlink, 0);
+
+                // `AT_FDCWD` refers to the cwd of the guest.
+                nix::unistd::chdir("/etc").unwrap();
+                assert_eq!(
+                    fstatat(libc::AT_FDCWD, "", AtFlags::AT_EMPTY_PATH).map(|stat| stat.st_ino),
+                    nix::sys::stat::stat("/etc").map(|stat| stat.st_ino)
+                );
+            },
+        )
+    }
+
+    #[test]
+    fn test_statx_mount_root_of_binding() {
+        test_with_proot_and_fs(
+            |fs| fs.add_binding("/etc", "/home").unwrap(),
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let attributes_of = |path: &str| {
+                    let mut statx = nc::statx_t::default();
+                    nc::statx(nc::AT_FDCWD, path, 0, nc::STATX_TYPE, &mut statx).unwrap();
+                    (statx.stx_attributes, statx.stx_attributes_mask)
+                };
+
+                let (attributes, mask) = attributes_of("/home");
+                assert_ne!(attributes & STATX_ATTR_MOUNT_ROOT, 0);
+                assert_ne!(mask & STATX_ATTR_MOUNT_ROOT, 0);
+                assert_ne!(attributes_of("/").0 & STATX_ATTR_MOUNT_ROOT, 0);
+
+                // Neither the content of the binding, nor a directory of the
+                // rootfs.
+                assert_eq!(attributes_of("/home/passwd").0 & STATX_ATTR_MOUNT_ROOT, 0);
+                assert_eq!(attributes_of("/etc").0 & STATX_ATTR_MOUNT_ROOT, 0);
+            },
+        )
+    }
 }
@@ -0,0 +1,64 @@
+use crate::errors::*;
+use crate::filesystem::Translator;
+use crate::process::tracee::Tracee;
+use crate::register::{Current, PtraceReader, SysArg1};
+
+/// The errno returned to the tracee by `swapon()` and `swapoff()`.
+pub const SWAP_ERRNO: Errno = EPERM;
+
+/// `swapon()` and `swapoff()` require privilege, and would affect the whole
+/// host if they were forwarded to the kernel. They are rejected with
+/// `SWAP_ERRNO` instead, the path being only translated for logging.
+pub fn enter(tracee: &mut Tracee) -> Result<()> {
+    let sys_num = tracee.regs.get_sys_num(Current);
+    let name = if sys_num == sc::nr::SWAPON {
+        "swapon"
+    } else {
+        "swapoff"
+    };
+
+    let raw_path = tracee.regs.get_sysarg_path(SysArg1)?;
+    match tracee.fs.borrow().translate_path(&raw_path, true) {
+        Ok((_, host_path)) => debug!(
+            "rejecting {}() on guest path {:?} (host path {:?})",
+            name, raw_path, host_path
+        ),
+        Err(error) => debug!(
+            "rejecting {}() on guest path {:?} (translation failed: {})",
+            name, raw_path, error
+        ),
+    }
+
+    Err(Error::errno_with_msg(
+        SWAP_ERRNO,
+        format!("{}() is not allowed in the sandbox", name),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tests::test_with_proot;
+    use std::ffi::CString;
+
+    #[test]
+    fn test_swapon_swapoff_rejected() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let path = CString::new("/etc/passwd").unwrap();
+
+                assert_eq!(unsafe { libc::swapon(path.as_ptr(), 0) }, -1);
+                assert_eq!(Errno::last(), SWAP_ERRNO);
+
+                assert_eq!(unsafe { libc::swapoff(path.as_ptr()) }, -1);
+                assert_eq!(Errno::last(), SWAP_ERRNO);
+
+                // Even with a path which is not canonical.
+                let path = CString::new("/tmp/../etc/passwd").unwrap();
+                assert_eq!(unsafe { libc::swapon(path.as_ptr(), 0) }, -1);
+                assert_eq!(Errno::last(), SWAP_ERRNO);
+            },
+        )
+    }
+}
@@ -1,23 +1,8 @@
-use crate::errors::*;
-
-use crate::filesystem::Translator;
-use crate::process::tracee::Tracee;
-use crate::register::PtraceWriter;
-use crate::register::{PtraceReader, SysArg2};
-
-pub fn enter(tracee: &mut Tracee) -> Result<()> {
-    let raw_path = tracee.regs.get_sysarg_path(SysArg2)?;
-    // create/delete/rename related system calls cannot follow final component.
-    let host_path = tracee.fs.borrow().translate_path(raw_path, false)?.1;
-
-    tracee.regs.set_sysarg_path(
-        SysArg2,
-        &host_path,
-        "during enter open translation, setting host path",
-    )?;
-
-    Ok(())
-}
+// create/delete/rename related system calls cannot follow final component.
+translate_path_handler!(
+    path: SysArg2,
+    deref_final: |_tracee, _raw_path| false,
+);
 
 #[cfg(test)]
 mod tests {
@@ -1,25 +1,9 @@
-use std::os::unix::prelude::RawFd;
-
-use crate::errors::*;
-use crate::process::tracee::Tracee;
-use crate::register::PtraceWriter;
-use crate::register::{Current, PtraceReader, SysArg, SysArg2, SysArg3};
-
-pub fn enter(tracee: &mut Tracee) -> Result<()> {
-    let dirfd = tracee.regs.get(Current, SysArg(SysArg2)) as RawFd;
-    let raw_path = tracee.regs.get_sysarg_path(SysArg3)?;
-
-    // create/delete/rename related system calls cannot follow final component.
-    let host_path = tracee.translate_path_at(dirfd, raw_path, false)?.1;
-
-    tracee.regs.set_sysarg_path(
-        SysArg3,
-        &host_path,
-        "during enter open translation, setting host path",
-    )?;
-
-    Ok(())
-}
+// create/delete/rename related system calls cannot follow final component.
+translate_path_handler!(
+    dirfd: SysArg2,
+    path: SysArg3,
+    deref_final: |_tracee, _raw_path| false,
+);
 
 #[cfg(test)]
 mod tests {
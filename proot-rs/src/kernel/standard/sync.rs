@@ -0,0 +1,105 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use nix::fcntl::{self, OFlag};
+use nix::sys::stat::{self, Mode};
+use nix::unistd;
+
+use crate::errors::*;
+use crate::filesystem::binding::Side;
+use crate::process::proot::InfoBag;
+use crate::process::tracee::Tracee;
+use crate::register::{SysResult, Word};
+
+/// `sync()` flushes the file-systems of the whole host, not only the ones of
+/// the guest. It is forwarded as is by default, and with the `--scoped-sync`
+/// option it is replaced by a `syncfs()` of the file-systems of the host paths
+/// of the bindings (including the rootfs), done by proot-rs, and cancelled.
+/// Each file-system is flushed once, even if several bindings are on it, and
+/// only the bindings of directories are considered: opening the host path of a
+/// binding of a file could have side effects (e.g. a device).
+///
+/// Like the host-wide `sync()`, these `syncfs()` wait for the data to be
+/// written, and since they are done by proot-rs, all the tracees are blocked
+/// in the meantime.
+///
+/// `syncfs()`, `fsync()` and `fdatasync()` already only flush what the fds of
+/// the guest refer to, so they are always passed through.
+pub fn enter(tracee: &mut Tracee, info_bag: &InfoBag) -> Result<()> {
+    if !info_bag.scoped_sync {
+        return Ok(());
+    }
+    let fs = tracee.fs.borrow();
+    let mut synced_devices = HashSet::new();
+    for binding in fs.get_bindings() {
+        let host_path = binding.get_path(Side::Host);
+        match stat::stat(host_path) {
+            Ok(stat) if stat.st_mode & libc::S_IFMT == libc::S_IFDIR => {
+                if synced_devices.insert(stat.st_dev) {
+                    sync_file_system(host_path);
+                }
+            }
+            Ok(_) => debug!("Skip syncfs() on {:?}, not a directory", host_path),
+            Err(errno) => debug!("Failed to call stat() on {:?}: {}", host_path, errno),
+        }
+    }
+    drop(fs);
+
+    tracee
+        .regs
+        .cancel_syscall("Cancel sync since it is scoped to the guest with --scoped-sync");
+
+    Ok(())
+}
+
+pub fn exit(tracee: &mut Tracee, info_bag: &InfoBag) -> Result<()> {
+    if !info_bag.scoped_sync {
+        return Ok(());
+    }
+    // `sync()` always succeeds, see method `enter()` above.
+    tracee
+        .regs
+        .set(SysResult, 0 as Word, "update return value in sync::exit()");
+    Ok(())
+}
+
+/// Flushes the file-system which contains the directory `host_path`. As
+/// `sync()` cannot fail, the errors are only logged.
+fn sync_file_system(host_path: &Path) {
+    // `O_DIRECTORY`, in case the directory was replaced since it was checked.
+    let flags = OFlag::O_RDONLY | OFlag::O_DIRECTORY | OFlag::O_CLOEXEC;
+    let result = fcntl::open(host_path, flags, Mode::empty()).and_then(|fd| {
+        let result = Errno::result(unsafe { libc::syncfs(fd) });
+        unistd::close(fd)?;
+        result.map(drop)
+    });
+    if let Err(errno) = result {
+        debug!("Failed to call syncfs() on {:?}: {}", host_path, errno);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::register::{Current, Original};
+    use crate::utils::tests::test_with_proot_and_info_bag;
+
+    #[test]
+    fn test_sync_scoped() {
+        test_with_proot_and_info_bag(
+            |info_bag| info_bag.scoped_sync = true,
+            |tracee, is_sysenter, before_translation| {
+                if is_sysenter
+                    && !before_translation
+                    && tracee.regs.get_sys_num(Original) == sc::nr::SYNC
+                {
+                    // The host-wide `sync()` never reaches the kernel.
+                    assert_ne!(tracee.regs.get_sys_num(Current), sc::nr::SYNC);
+                }
+            },
+            || {
+                assert_eq!(unsafe { libc::syscall(libc::SYS_sync) }, 0);
+            },
+        )
+    }
+}
@@ -0,0 +1,141 @@
+use std::mem::size_of;
+
+use libc::c_void;
+
+use crate::errors::*;
+use crate::process::proot::InfoBag;
+use crate::process::tracee::Tracee;
+use crate::register::{
+    Current, Original, PtraceReader, PtraceWriter, SysArg, SysArg1, SysResult, Word,
+};
+
+/// The uptime and the clock ticks of the host when the sandbox was launched.
+///
+/// With the `--virtual-uptime` option, the values reported by `sysinfo()` and
+/// `times()` are rewritten so that they start from the launch of the sandbox
+/// instead of the boot of the host, which makes the guest more reproducible.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LaunchTime {
+    /// Seconds since boot, as reported by `sysinfo()`.
+    pub uptime: Word,
+    /// Clock ticks since an arbitrary point in the past, as returned by
+    /// `times()`.
+    pub ticks: Word,
+}
+
+impl LaunchTime {
+    pub fn now() -> Result<LaunchTime> {
+        let uptime = nix::sys::sysinfo::sysinfo()?.uptime().as_secs() as Word;
+        let mut tms: libc::tms = unsafe { std::mem::zeroed() };
+        let ticks = unsafe { libc::times(&mut tms) };
+        if ticks == -1 as libc::clock_t {
+            return Err(Error::errno_with_msg(
+                Errno::last(),
+                "Failed to call times() when recording launch time",
+            ));
+        }
+        Ok(LaunchTime {
+            uptime,
+            ticks: ticks as Word,
+        })
+    }
+}
+
+/// Only the clocks since the boot are rewritten. The cpu times of the children
+/// (`tms_cutime` and `tms_cstime`, like `getrusage(RUSAGE_CHILDREN)`) are left
+/// to the kernel: the pids are not virtualized, and the tracees are still
+/// reaped by their own parent (proot-rs only sees them as their tracer), so
+/// these times are already the ones of the children of the guest.
+pub fn exit(info_bag: &InfoBag, tracee: &mut Tracee) -> Result<()> {
+    let launch_time = match info_bag.launch_time {
+        Some(launch_time) => launch_time,
+        // These syscalls are passed through by default.
+        None => return Ok(()),
+    };
+    let result = tracee.regs.get(Current, SysResult);
+    let sys_num = tracee.regs.get_sys_num(Original);
+
+    if sys_num == sc::nr::SYSINFO {
+        // Error reported by the kernel.
+        if result != 0 {
+            return Ok(());
+        }
+        // `uptime` is the first field of `struct sysinfo`.
+        let address = tracee.regs.get(Original, SysArg(SysArg1)) as *mut c_void;
+        let bytes = tracee.regs.read_data(address, size_of::<Word>())?;
+        let mut word = [0u8; size_of::<Word>()];
+        word.copy_from_slice(&bytes);
+        let uptime = Word::from_ne_bytes(word).saturating_sub(launch_time.uptime);
+        tracee
+            .regs
+            .write_data(address, &uptime.to_ne_bytes(), false)?;
+    } else if sys_num == sc::nr::TIMES {
+        // Error reported by the kernel (i.e. `-EFAULT`).
+        if (result as isize) < 0 && (result as isize) > -4096 {
+            return Ok(());
+        }
+        tracee.regs.set(
+            SysResult,
+            result.wrapping_sub(launch_time.ticks),
+            "update return value in sysinfo_times::exit()",
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use nix::sys::time::{TimeVal, TimeValLike};
+    use nix::sys::wait::{waitpid, WaitStatus};
+    use nix::time::{clock_gettime, ClockId};
+    use nix::unistd::{fork, ForkResult};
+
+    use crate::utils::tests::test_with_proot;
+
+    fn children_cpu_time_ms() -> i64 {
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        assert_eq!(
+            unsafe { libc::getrusage(libc::RUSAGE_CHILDREN, &mut usage) },
+            0
+        );
+        (TimeVal::from(usage.ru_utime) + TimeVal::from(usage.ru_stime)).num_milliseconds()
+    }
+
+    #[test]
+    fn test_getrusage_children_reflects_tracee_children() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let before = children_cpu_time_ms();
+
+                match unsafe { fork() }.unwrap() {
+                    ForkResult::Child => {
+                        // Burn 200ms of cpu time.
+                        while clock_gettime(ClockId::CLOCK_PROCESS_CPUTIME_ID)
+                            .unwrap()
+                            .num_milliseconds()
+                            < 200
+                        {}
+                        std::process::exit(0);
+                    }
+                    ForkResult::Parent { child } => {
+                        // Not reaped yet: not counted.
+                        assert!(children_cpu_time_ms() - before < 100);
+                        assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+                    }
+                }
+
+                // Once reaped by the tracee, its cpu time is added to the ones
+                // of the children of the tracee.
+                assert!(children_cpu_time_ms() - before >= 150);
+                let mut tms: libc::tms = unsafe { std::mem::zeroed() };
+                unsafe { libc::times(&mut tms) };
+                let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+                assert!(
+                    (tms.tms_cutime + tms.tms_cstime) as i64 * 1000 / ticks_per_second as i64
+                        >= 150
+                );
+            },
+        )
+    }
+}
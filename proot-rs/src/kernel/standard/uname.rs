@@ -1,24 +1,48 @@
+use libc::c_void;
+
 use crate::errors::*;
+use crate::kernel::standard::sethostname::NEW_UTS_LEN;
+use crate::process::tracee::Tracee;
+use crate::register::{Current, Original, PtraceWriter, SysArg, SysArg1, SysResult};
 
-#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
-pub fn exit() -> Result<()> {
-    //    struct utsname utsname;
-    //    word_t address;
-    //    size_t size;
-    //
+/// Size of each field of `struct new_utsname`, including the terminating null
+/// byte.
+const UTS_FIELD_SIZE: usize = NEW_UTS_LEN + 1;
+/// Offset of the `nodename` field in `struct new_utsname`.
+const NODENAME_OFFSET: usize = UTS_FIELD_SIZE;
+/// Offset of the `domainname` field in `struct new_utsname`.
+const DOMAINNAME_OFFSET: usize = 5 * UTS_FIELD_SIZE;
+
+pub fn exit(tracee: &mut Tracee) -> Result<()> {
+    // Error reported by the kernel.
+    if (tracee.regs.get(Current, SysResult) as isize) < 0 {
+        return Ok(());
+    }
+
+    let address = tracee.regs.get(Original, SysArg(SysArg1)) as usize;
+
+    // Report the names set by `sethostname()` and `setdomainname()`, see
+    // `sethostname::VirtualUts`.
+    let uts = tracee.uts.borrow();
+    let fields = [
+        (NODENAME_OFFSET, &uts.hostname),
+        (DOMAINNAME_OFFSET, &uts.domainname),
+    ];
+    for (offset, name) in fields.iter() {
+        if let Some(name) = name {
+            // The field is padded with null bytes, as the kernel does.
+            let mut field = name.clone();
+            field.resize(UTS_FIELD_SIZE, 0);
+            tracee
+                .regs
+                .write_data((address + offset) as *mut c_void, &field, false)?;
+        }
+    }
+
+    // TODO: implement this part for ABI_2 (x32)
     //    if (get_abi(tracee) != ABI_2)
     //        return SyscallExitResult::None;
     //
-    //    /* Error reported by the kernel.  */
-    //    if ((int) syscall_result < 0)
-    //        return SyscallExitResult::None;
-    //
-    //    address = peek_reg(tracee, ORIGINAL, SYSARG_1);
-    //
-    //    status = read_data(tracee, &utsname, address, sizeof(utsname));
-    //    if (status < 0)
-    //        return SyscallExitResult::Value(status);
-    //
     //    /* Some 32-bit programs like package managers can be
     //     * confused when the kernel reports "x86_64".  */
     //    size = sizeof(utsname.machine);
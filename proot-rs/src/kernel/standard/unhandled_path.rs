@@ -0,0 +1,105 @@
+use std::str::FromStr;
+
+use crate::errors::*;
+use crate::kernel::syscall::name_of_syscall;
+use crate::process::proot::InfoBag;
+use crate::process::tracee::Tracee;
+use crate::register::Current;
+
+/// Numbers of the syscalls of the new mount API (Linux 5.2) and of `openat2()`
+/// (Linux 5.6), the same on all architectures, not defined by the sc crate yet.
+pub const OPEN_TREE: usize = 428;
+pub const MOVE_MOUNT: usize = 429;
+pub const FSPICK: usize = 433;
+pub const OPENAT2: usize = 437;
+pub const MOUNT_SETATTR: usize = 442;
+
+/// What to do when a tracee uses a syscall of the `UnhandledPath` group, set
+/// with the `--fail-on-unhandled-syscall` option.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnhandledSyscallAction {
+    /// Log an error, and let the syscall through untranslated.
+    Log,
+    /// Log an error, and make the syscall fail with `ENOSYS`.
+    Enosys,
+}
+
+impl UnhandledSyscallAction {
+    pub const NAMES: &'static [&'static str] = &["log", "enosys"];
+}
+
+impl FromStr for UnhandledSyscallAction {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        match name {
+            "log" => Ok(UnhandledSyscallAction::Log),
+            "enosys" => Ok(UnhandledSyscallAction::Enosys),
+            _ => Err(format!("unknown action: {}", name)),
+        }
+    }
+}
+
+/// Syscalls of the `UnhandledPath` group take a path, but proot-rs does not
+/// translate it yet: they silently operate on the host paths, which escapes
+/// the guest rootfs.
+///
+/// They are let through as is by default, since most of them require
+/// privilege anyway. With `--fail-on-unhandled-syscall`, they are reported
+/// loudly in the log, to find such gaps in the translation, and can be made to
+/// fail with `ENOSYS` (as if the kernel was too old for them, which most
+/// programs handle by falling back to another syscall).
+pub fn enter(tracee: &Tracee, info_bag: &InfoBag) -> Result<()> {
+    let action = match info_bag.unhandled_syscall_action {
+        Some(action) => action,
+        None => return Ok(()),
+    };
+    let sys_num = tracee.regs.get_sys_num(Current);
+    error!(
+        "{} {}() takes a path which is not translated by proot-rs",
+        tracee.pid,
+        name_of_syscall(sys_num).unwrap_or("unknown"),
+    );
+    match action {
+        UnhandledSyscallAction::Log => Ok(()),
+        UnhandledSyscallAction::Enosys => Err(Error::errno_with_msg(
+            ENOSYS,
+            format!("syscall {} is not handled by proot-rs", sys_num),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use super::*;
+    use crate::utils::tests::test_with_proot_and_info_bag;
+
+    #[test]
+    fn test_unhandled_path_syscall_enosys() {
+        test_with_proot_and_info_bag(
+            |info_bag| info_bag.unhandled_syscall_action = Some(UnhandledSyscallAction::Enosys),
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                // struct open_how { flags, mode, resolve }
+                let how: [u64; 3] = [libc::O_RDONLY as u64, 0, 0];
+                let path = CString::new("/etc/passwd").unwrap();
+                let result = unsafe {
+                    libc::syscall(
+                        OPENAT2 as libc::c_long,
+                        libc::AT_FDCWD,
+                        path.as_ptr(),
+                        how.as_ptr(),
+                        std::mem::size_of_val(&how),
+                    )
+                };
+                assert_eq!(result, -1);
+                assert_eq!(Errno::last(), ENOSYS);
+
+                // The translated syscalls are not affected.
+                assert!(std::fs::metadata("/etc/passwd").is_ok());
+            },
+        )
+    }
+}
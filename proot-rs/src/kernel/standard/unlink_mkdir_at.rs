@@ -1,31 +1,14 @@
-use std::os::unix::prelude::RawFd;
-
-use crate::errors::*;
 use crate::filesystem::ext::PathExt;
-use crate::process::tracee::Tracee;
-use crate::register::PtraceWriter;
-use crate::register::{Current, PtraceReader, SysArg, SysArg1, SysArg2};
-
-pub fn enter(tracee: &mut Tracee) -> Result<()> {
-    let sys_num = tracee.regs.get_sys_num(Current);
-    let dirfd = tracee.regs.get(Current, SysArg(SysArg1)) as RawFd;
-    let raw_path = tracee.regs.get_sysarg_path(SysArg2)?;
+use crate::register::Current;
 
-    let deref_final = match sys_num {
+translate_path_handler!(
+    dirfd: SysArg1,
+    path: SysArg2,
+    deref_final: |tracee, raw_path| match tracee.regs.get_sys_num(Current) {
         sc::nr::UNLINKAT | sc::nr::MKDIRAT => false,
         _ => raw_path.with_trailing_slash(),
-    };
-
-    let host_path = tracee.translate_path_at(dirfd, raw_path, deref_final)?.1;
-
-    tracee.regs.set_sysarg_path(
-        SysArg2,
-        &host_path,
-        "during enter open translation, setting host path",
-    )?;
-
-    Ok(())
-}
+    },
+);
 
 #[cfg(test)]
 mod tests {
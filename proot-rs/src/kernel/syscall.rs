@@ -1,6 +1,12 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 
+use crate::errors::*;
+use crate::kernel::standard::chmod_access_mknod_at::{FACCESSAT2, FCHMODAT2};
+use crate::kernel::standard::unhandled_path::{
+    FSPICK, MOUNT_SETATTR, MOVE_MOUNT, OPENAT2, OPEN_TREE,
+};
+use crate::process::proot::InfoBag;
 use crate::process::tracee::Tracee;
 use crate::register::Original;
 use crate::register::RegVersion;
@@ -132,6 +138,7 @@ lazy_static! {
         (sc::nr::EXIT_GROUP, "exit_group"),
         #[cfg(any(target_arch = "x86_64", target_arch = "arm", target_arch = "aarch64", target_arch = "x86"))]
         (sc::nr::FACCESSAT, "faccessat"),
+        (FACCESSAT2, "faccessat2"),
         #[cfg(any(target_arch = "x86_64", target_arch = "aarch64", target_arch = "x86"))]
         (sc::nr::FADVISE64, "fadvise64"),
         #[cfg(any(target_arch = "x86"))]
@@ -148,6 +155,7 @@ lazy_static! {
         (sc::nr::FCHMOD, "fchmod"),
         #[cfg(any(target_arch = "x86_64", target_arch = "arm", target_arch = "aarch64", target_arch = "x86"))]
         (sc::nr::FCHMODAT, "fchmodat"),
+        (FCHMODAT2, "fchmodat2"),
         #[cfg(any(target_arch = "x86_64", target_arch = "arm", target_arch = "aarch64", target_arch = "x86"))]
         (sc::nr::FCHOWN, "fchown"),
         #[cfg(any(target_arch = "arm", target_arch = "x86"))]
@@ -184,6 +192,7 @@ lazy_static! {
         (sc::nr::FSTATFS, "fstatfs"),
         #[cfg(any(target_arch = "arm", target_arch = "x86"))]
         (sc::nr::FSTATFS64, "fstatfs64"),
+        (FSPICK, "fspick"),
         #[cfg(any(target_arch = "x86_64", target_arch = "arm", target_arch = "aarch64", target_arch = "x86"))]
         (sc::nr::FSYNC, "fsync"),
         #[cfg(any(target_arch = "x86"))]
@@ -384,6 +393,8 @@ lazy_static! {
         (sc::nr::MODIFY_LDT, "modify_ldt"),
         #[cfg(any(target_arch = "x86_64", target_arch = "arm", target_arch = "aarch64", target_arch = "x86"))]
         (sc::nr::MOUNT, "mount"),
+        (MOUNT_SETATTR, "mount_setattr"),
+        (MOVE_MOUNT, "move_mount"),
         #[cfg(any(target_arch = "x86_64", target_arch = "arm", target_arch = "aarch64", target_arch = "x86"))]
         (sc::nr::MOVE_PAGES, "move_pages"),
         #[cfg(any(target_arch = "x86_64", target_arch = "arm", target_arch = "aarch64", target_arch = "x86"))]
@@ -444,8 +455,10 @@ lazy_static! {
         (sc::nr::OPEN, "open"),
         #[cfg(any(target_arch = "x86_64", target_arch = "arm", target_arch = "aarch64", target_arch = "x86"))]
         (sc::nr::OPEN_BY_HANDLE_AT, "open_by_handle_at"),
+        (OPEN_TREE, "open_tree"),
         #[cfg(any(target_arch = "x86_64", target_arch = "arm", target_arch = "aarch64", target_arch = "x86"))]
         (sc::nr::OPENAT, "openat"),
+        (OPENAT2, "openat2"),
         #[cfg(any(target_arch = "x86_64", target_arch = "arm", target_arch = "x86"))]
         (sc::nr::PAUSE, "pause"),
         #[cfg(any(target_arch = "arm"))]
@@ -846,11 +859,39 @@ pub fn name_of_syscall(sysnum: usize) -> Option<&'static str> {
     SYSNUM_TO_SYSCALL_NAME.get(&sysnum).map(|s| *s)
 }
 
-pub fn print_syscall<M>(tracee: &Tracee, version: RegVersion, msg: M)
+/// Returns the numbers of all the syscalls known on this architecture.
+pub fn known_syscalls() -> impl Iterator<Item = usize> {
+    SYSNUM_TO_SYSCALL_NAME.keys().cloned()
+}
+
+/// Parses a comma-separated list of syscall names (e.g.
+/// `open,openat,execve`), as given to `--strace-filter`, into their numbers.
+pub fn parse_syscall_list(list: &str) -> Result<HashSet<usize>> {
+    list.split(',')
+        .map(|name| {
+            SYSNUM_TO_SYSCALL_NAME
+                .iter()
+                .find(|(_, known_name)| **known_name == name.trim())
+                .map(|(sysnum, _)| *sysnum)
+                .ok_or_else(|| {
+                    Error::errno_with_msg(EINVAL, format!("unknown syscall: {:?}", name))
+                })
+        })
+        .collect()
+}
+
+/// Prints the registers of the syscall of `tracee` in the trace log, unless
+/// it is filtered out by the `--strace-filter` option.
+pub fn print_syscall<M>(tracee: &Tracee, info_bag: &InfoBag, version: RegVersion, msg: M)
 where
     M: Display,
 {
     let sysnum = tracee.regs.get_sys_num(Original);
+    if let Some(filter) = &info_bag.strace_filter {
+        if !filter.contains(&sysnum) {
+            return;
+        }
+    }
 
     trace!(
         "-- {} {}<{}>(0x{:x?}, 0x{:x?}, 0x{:x?}, 0x{:x?}, 0x{:x?}, 0x{:x?}) = 0x{:x?} [0x{:x?}] {}",
@@ -868,3 +909,24 @@ where
         msg,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_syscall_list() {
+        let filter = parse_syscall_list("openat,execve, chdir").unwrap();
+        assert_eq!(filter.len(), 3);
+        for sysnum in &[sc::nr::OPENAT, sc::nr::EXECVE, sc::nr::CHDIR] {
+            assert!(filter.contains(sysnum));
+        }
+        assert!(!filter.contains(&sc::nr::WRITE));
+
+        assert_eq!(
+            parse_syscall_list("openat,not_a_syscall"),
+            Err(Error::errno(EINVAL))
+        );
+        assert_eq!(parse_syscall_list(""), Err(Error::errno(EINVAL)));
+    }
+}
@@ -0,0 +1,199 @@
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::str::FromStr;
+
+use env_logger::{Builder, WriteStyle};
+use log::Level;
+
+/// The formats of the log records, selected with the `--log-format` option.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    /// The default text format of `env_logger`.
+    Plain,
+    /// One JSON object per line.
+    Json,
+    /// One line of `key=value` pairs per record.
+    Logfmt,
+}
+
+impl LogFormat {
+    pub const NAMES: &'static [&'static str] = &["plain", "json", "logfmt"];
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "plain" => Ok(LogFormat::Plain),
+            "json" => Ok(LogFormat::Json),
+            "logfmt" => Ok(LogFormat::Logfmt),
+            _ => Err(format!("unknown log format: {}", name)),
+        }
+    }
+}
+
+/// The values of the `--color` option.
+pub const COLOR_NAMES: &[&str] = &["auto", "always", "never"];
+
+/// Returns the `WriteStyle` of the `--color` option `name`.
+pub fn parse_color(name: &str) -> Option<WriteStyle> {
+    match name {
+        "auto" => Some(WriteStyle::Auto),
+        "always" => Some(WriteStyle::Always),
+        "never" => Some(WriteStyle::Never),
+        _ => None,
+    }
+}
+
+/// The fields of a log record, as printed by the structured formats.
+struct Record<'a> {
+    timestamp: &'a str,
+    level: Level,
+    pid: u32,
+    target: &'a str,
+    message: &'a str,
+}
+
+/// Initializes the logger of proot-rs with the records printed in `format`.
+///
+/// The records are still filtered with the `RUST_LOG` environment variable.
+/// The colors of the plain format follow `color` if set, and otherwise the
+/// `RUST_LOG_STYLE` environment variable (colored only on a terminal by
+/// default). The structured formats are never colored.
+pub fn init(format: LogFormat, color: Option<WriteStyle>) {
+    let mut builder = Builder::from_default_env();
+    if let Some(color) = color {
+        builder.write_style(color);
+    }
+    if format != LogFormat::Plain {
+        let pid = std::process::id();
+        builder.format(move |buf, record| {
+            let timestamp = buf.timestamp_millis().to_string();
+            let message = record.args().to_string();
+            let record = Record {
+                timestamp: &timestamp,
+                level: record.level(),
+                pid,
+                target: record.target(),
+                message: &message,
+            };
+            writeln!(buf, "{}", format_record(format, &record))
+        });
+    }
+    builder.init();
+}
+
+fn format_record(format: LogFormat, record: &Record) -> String {
+    match format {
+        LogFormat::Json => format!(
+            "{{\"timestamp\":{},\"level\":{},\"pid\":{},\"target\":{},\"message\":{}}}",
+            json_string(record.timestamp),
+            json_string(&record.level.to_string()),
+            record.pid,
+            json_string(record.target),
+            json_string(record.message)
+        ),
+        LogFormat::Logfmt => format!(
+            "ts={} level={} pid={} target={} msg={}",
+            logfmt_value(record.timestamp),
+            record.level.to_string().to_lowercase(),
+            record.pid,
+            logfmt_value(record.target),
+            logfmt_value(record.message)
+        ),
+        LogFormat::Plain => record.message.to_string(),
+    }
+}
+
+/// Returns `value` as a JSON string, with its quotes.
+fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Returns `value` as a logfmt value, which is quoted only if needed.
+fn logfmt_value(value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c > ' ' && c != '"' && c != '=' && c != '\\')
+    {
+        value.to_string()
+    } else {
+        // The escapes of logfmt are the ones of JSON strings.
+        json_string(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(message: &str) -> Record {
+        Record {
+            timestamp: "2021-08-19T12:00:00.000Z",
+            level: Level::Debug,
+            pid: 42,
+            target: "proot_rs::cli",
+            message,
+        }
+    }
+
+    #[test]
+    fn test_log_format_from_str() {
+        for name in LogFormat::NAMES {
+            assert!(name.parse::<LogFormat>().is_ok());
+        }
+        assert_eq!("json".parse(), Ok(LogFormat::Json));
+        assert!("yaml".parse::<LogFormat>().is_err());
+    }
+
+    #[test]
+    fn test_parse_color() {
+        for name in COLOR_NAMES {
+            assert!(parse_color(name).is_some());
+        }
+        assert_eq!(parse_color("never"), Some(WriteStyle::Never));
+        assert_eq!(parse_color("no"), None);
+    }
+
+    #[test]
+    fn test_format_record_json() {
+        assert_eq!(
+            format_record(LogFormat::Json, &record("proot-rs startup")),
+            "{\"timestamp\":\"2021-08-19T12:00:00.000Z\",\"level\":\"DEBUG\",\"pid\":42,\
+             \"target\":\"proot_rs::cli\",\"message\":\"proot-rs startup\"}"
+        );
+        // The messages often contain paths between quotes, and several lines.
+        let line = format_record(LogFormat::Json, &record("args:\n\t\"/bin\\sh\"\u{1}"));
+        assert!(!line.contains('\n'));
+        assert!(line.ends_with("\"message\":\"args:\\n\\t\\\"/bin\\\\sh\\\"\\u0001\"}"));
+    }
+
+    #[test]
+    fn test_format_record_logfmt() {
+        assert_eq!(
+            format_record(LogFormat::Logfmt, &record("startup")),
+            "ts=2021-08-19T12:00:00.000Z level=debug pid=42 target=proot_rs::cli msg=startup"
+        );
+        assert!(format_record(LogFormat::Logfmt, &record("path \"/a=b\""))
+            .ends_with(" msg=\"path \\\"/a=b\\\"\""));
+        assert!(format_record(LogFormat::Logfmt, &record("")).ends_with(" msg=\"\""));
+    }
+}
@@ -9,50 +9,179 @@ extern crate sc;
 #[macro_use]
 extern crate lazy_static;
 extern crate byteorder;
+extern crate serde;
+extern crate toml;
 #[macro_use]
 extern crate log;
 
+mod benchmark;
 mod cli;
+mod config_file;
 mod errors;
 mod filesystem;
 mod kernel;
+mod logging;
 mod process;
 mod register;
 mod utils;
 
-use crate::errors::Result;
+use std::cell::RefCell;
+use std::path::Path;
+
+use nix::sched::sched_setaffinity;
+use nix::unistd::Pid;
+
+use crate::errors::{Result, WithContext};
+use crate::filesystem::trace;
+use crate::kernel::execve::dump;
+use crate::kernel::fake_root::FakeRoot;
+use crate::kernel::seccomp;
+use crate::kernel::standard::sysinfo_times::LaunchTime;
 use crate::process::proot::{show_info, stop_program, PRoot};
 use crate::process::sigactions;
+use crate::process::stats::Stats;
+use crate::process::timeout::{Timeout, TIMEOUT_EXIT_CODE};
+
+/// The exit code of proot-rs when the one of the first tracee is unknown.
+const UNKNOWN_EXIT_CODE: i32 = 1;
 
 fn run() -> Result<()> {
     // step 1: CLI parsing
-    let (fs, command) = cli::parse_config()?;
+    let config = cli::parse_config()?;
+
+    if let Some(iterations) = config.benchmark {
+        return benchmark::run(config.fs, iterations, config.chroot_only);
+    }
+    if config.seccomp_bpf_dump {
+        let filter = seccomp::build_filter(&seccomp::intercepted_syscalls());
+        print!("{}", seccomp::dump_filter(&filter));
+        return Ok(());
+    }
+    if config.dump_elf {
+        let executable = Path::new(&config.command[0]);
+        print!("{}", dump::dump_elf(&config.fs, executable)?);
+        return Ok(());
+    }
+    if let Some(guest_path) = &config.trace_path {
+        print!("{}", trace::trace_path(&config.fs, guest_path)?);
+        return Ok(());
+    }
 
     let mut proot: PRoot = PRoot::new();
 
     // step 2: initialize Proot and start the first tracee
     proot.init()?;
-    proot.launch_process(fs, command)?;
+    if config.virtual_uptime {
+        proot.info_bag.launch_time = Some(LaunchTime::now()?);
+    }
+    proot.info_bag.proc_emulation = config.proc_emulation;
+    proot.info_bag.dev_emulation = config.dev_emulation;
+    proot.info_bag.hide_host_pids = config.hide_host_pids;
+    if config.chroot_only {
+        warn!("--chroot-only: the paths given to the system calls are not translated, only the executables of execve() are looked up in the rootfs");
+        proot.info_bag.chroot_only = true;
+    }
+    proot.info_bag.subreaper = config.init;
+    proot.info_bag.max_interp_depth = config.max_interp_depth;
+    proot.info_bag.allow_io_uring = config.allow_io_uring;
+    proot.info_bag.allow_bpf = config.allow_bpf;
+    proot.info_bag.allow_perf = config.allow_perf;
+    proot.info_bag.allow_keyring = config.allow_keyring;
+    proot.info_bag.allow_setns = config.allow_setns;
+    proot.info_bag.scoped_sync = config.scoped_sync;
+    if let Some(cpus) = config.cpus {
+        // The init process inherits the affinity of proot-rs.
+        sched_setaffinity(Pid::from_raw(0), &cpus)
+            .context("Failed to restrict proot-rs to the cpus of --cpus")?;
+        proot.info_bag.cpus = Some(cpus);
+    }
+    if config.mq_namespace {
+        proot.info_bag.mq_prefix = Some(format!("proot-rs-{}.", std::process::id()));
+    }
+    if config.collect_stats {
+        proot.info_bag.stats = Some(RefCell::new(Stats::new()));
+    }
+    if config.fake_root {
+        let mut fake_root = FakeRoot::new();
+        for &(host, guest) in &config.uid_mappings {
+            fake_root.map_uid(host, guest);
+        }
+        for &(host, guest) in &config.gid_mappings {
+            fake_root.map_gid(host, guest);
+        }
+        proot.info_bag.fake_root = Some(RefCell::new(fake_root));
+    }
+    proot.info_bag.strace_filter = config.strace_filter;
+    proot.info_bag.socket_families = config.socket_families;
+    proot.info_bag.unhandled_syscall_action = config.unhandled_syscall_action;
+    proot.launch_process(config.fs, config.command, &config.env)?;
 
     // what follows (event loop) is only for the main thread,
     // as the child thread will stop after executing the `kernel.execve` command
 
     // step 3: Configure the signal actions
     sigactions::prepare_sigactions(stop_program, show_info);
+    if let Some(seconds) = config.timeout {
+        proot.timeout = Some(Timeout::start(seconds)?);
+    }
 
     // step 4: Listen to and deal with tracees events
     proot.event_loop()?;
 
-    debug!(
-        "first tracee exit with exit code: {}",
-        proot.init_exit_code.unwrap()
-    );
+    // The exit of the first tracee may never be reported, e.g. when the event
+    // loop stops on `ECHILD`.
+    let init_exit_code = match proot.init_exit_code {
+        Some(exit_code) => {
+            debug!("first tracee exit with exit code: {}", exit_code);
+            exit_code
+        }
+        None => {
+            error!(
+                "The exit code of the first tracee is unknown, exiting with {}",
+                UNKNOWN_EXIT_CODE
+            );
+            UNKNOWN_EXIT_CODE
+        }
+    };
+    if proot.init_loader_failed {
+        error!(
+            "The loader failed during program startup of {:?} (see the error of loader-shim above), this is not the exit code of the program",
+            config.command[0]
+        );
+    }
+
+    if let Some(stats) = &proot.info_bag.stats {
+        eprintln!("{}", stats.borrow().summary());
+    }
+
+    let timed_out = proot.timeout.as_ref().map_or(false, Timeout::expired);
+    let exit_code = if timed_out {
+        TIMEOUT_EXIT_CODE
+    } else {
+        init_exit_code
+    };
+    let term_signal = proot.init_term_signal;
+    // Neither `std::process::exit()` nor a signal run the destructors, so the
+    // loader, the extracted rootfs and the synthetic /proc and /sys are
+    // deleted now.
+    drop(proot);
+    drop(config.rootfs_image);
+    drop(config.synthetic_proc);
+    drop(config.synthetic_sys);
+
+    if config.exit_code_passthrough && !timed_out {
+        if let Some(signal) = term_signal {
+            debug!("re-raising the signal which killed the first tracee");
+            sigactions::raise_with_default_action(signal);
+        }
+    }
 
-    std::process::exit(proot.init_exit_code.unwrap());
+    std::process::exit(exit_code);
 }
 
 fn main() {
-    env_logger::init();
+    // The logger is initialized by `cli::parse_config()`, with the format
+    // given by `--log-format`.
     if let Err(err) = run() {
         error!("Exited with error: {}", err);
         std::process::exit(1);
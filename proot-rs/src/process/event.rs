@@ -2,6 +2,7 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use libc::pid_t;
+use loader_shim::script::PANIC_EXIT_CODE;
 use nix::sched::CloneFlags;
 use nix::sys::ptrace::Event as PtraceEvent;
 use nix::unistd::Pid;
@@ -11,7 +12,7 @@ use crate::filesystem::FileSystem;
 use crate::process::proot::InfoBag;
 use crate::process::tracee::{Tracee, TraceeRestartMethod, TraceeStatus};
 use crate::process::translation::SyscallTranslator;
-use crate::register::{RegVersion, SysArg, SysArg1};
+use crate::register::{InstrPointer, RegVersion, SysArg, SysArg1};
 
 use super::tracee::SigStopStatus;
 
@@ -25,6 +26,7 @@ pub trait EventHandler {
     fn handle_seccomp_event(&mut self, info_bag: &mut InfoBag, event: PtraceEvent);
     fn handle_exec_vfork_event(&mut self);
     fn handle_new_child_event(&mut self) -> Result<Tracee>;
+    fn handle_exit_event(&mut self, info_bag: &InfoBag) -> Result<bool>;
 }
 
 impl EventHandler for Tracee {
@@ -160,6 +162,13 @@ impl EventHandler for Tracee {
         // call to execve(2).
         child_tracee.exe = self.exe.clone();
 
+        // Since UTS namespaces are not emulated, the hostname is shared by all
+        // tracees.
+        child_tracee.uts = self.uts.clone();
+
+        child_tracee.proc_emulation = self.proc_emulation;
+        child_tracee.dev_emulation = self.dev_emulation;
+
         // child->qemu = talloc_reference(child, parent->qemu);
         // child->glue = talloc_reference(child, parent->glue);
 
@@ -199,4 +208,21 @@ impl EventHandler for Tracee {
 
         Ok(child_tracee)
     }
+
+    /// Returns whether the tracee, about to exit (`PTRACE_EVENT_EXIT`), is
+    /// the loader which failed to start the program: it exits with
+    /// `PANIC_EXIT_CODE` from its own code. Otherwise, the same exit code
+    /// is the one of the program.
+    fn handle_exit_event(&mut self, info_bag: &InfoBag) -> Result<bool> {
+        let status = nix::sys::ptrace::getevent(self.pid)? as libc::c_int;
+        if !libc::WIFEXITED(status) || libc::WEXITSTATUS(status) != PANIC_EXIT_CODE {
+            return Ok(false);
+        }
+        self.regs.fetch_regs()?;
+        let instr_pointer = self.regs.get(RegVersion::Current, InstrPointer);
+        Ok(info_bag
+            .loader_ranges
+            .iter()
+            .any(|range| range.contains(&instr_pointer)))
+    }
 }
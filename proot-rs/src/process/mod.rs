@@ -1,5 +1,7 @@
 pub mod event;
 pub mod proot;
 pub mod sigactions;
+pub mod stats;
+pub mod timeout;
 pub mod tracee;
 pub mod translation;
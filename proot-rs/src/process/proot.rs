@@ -1,19 +1,34 @@
 use std::cell::RefCell;
 use std::ffi::CString;
 
+use std::ops::Range;
+use std::os::unix::ffi::OsStrExt;
 use std::process;
 use std::rc::Rc;
-use std::{collections::HashMap, convert::TryFrom};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+};
 
 use libc::{c_int, c_void, pid_t, siginfo_t};
+use loader_shim::script::PANIC_EXIT_CODE;
+use nix::sched::CpuSet;
 use nix::sys::ptrace::{self, Event as PtraceEvent};
 use nix::sys::signal::{self, Signal};
 use nix::sys::wait::{self, WaitPidFlag, WaitStatus::*};
 use nix::unistd::{self, ForkResult, Pid};
 
 use crate::kernel::execve::loader::LoaderFile;
+use crate::kernel::execve::INTERPRETER_REWRITE_LIMIT;
+use crate::kernel::fake_root::FakeRoot;
+use crate::kernel::standard::sysinfo_times::LaunchTime;
+use crate::kernel::standard::unhandled_path::UnhandledSyscallAction;
 use crate::process::event::EventHandler;
+use crate::process::sigactions;
+use crate::process::stats::Stats;
+use crate::process::timeout::Timeout;
 use crate::process::tracee::{SigStopStatus, Tracee};
+use crate::register::{Registers, Word};
 use crate::{
     errors::*,
     filesystem::{temp::TempFile, FileSystem},
@@ -31,6 +46,71 @@ pub struct InfoBag {
     /// before use. This temporary file struct makes sure the file is
     /// deleted when it's dropped.
     pub loader: TempFile,
+    /// Address ranges where the loader is mapped in the tracees, which must
+    /// not be modified by them (see `kernel::standard::memory`).
+    pub loader_ranges: Vec<Range<Word>>,
+    /// Uptime of the host when the sandbox was launched, only set when the
+    /// `--virtual-uptime` option is used (see `sysinfo_times::LaunchTime`).
+    pub launch_time: Option<LaunchTime>,
+    /// Whether the paths in `/proc` are emulated, which can be disabled with
+    /// the `--no-proc-emulation` option to let the host `/proc` show through.
+    pub proc_emulation: bool,
+    /// Whether `/dev/null`, `/dev/zero` and `/dev/full` are emulated, see
+    /// `Tracee::translate_emulated_dev()`.
+    pub dev_emulation: bool,
+    /// Whether the pids which are not tracees are removed from the listings
+    /// of the host `/proc`, see `kernel::standard::getdents`.
+    pub hide_host_pids: bool,
+    /// Whether only the executables of `execve()` are translated, see
+    /// `SyscallGroup::translates_paths()`.
+    pub chroot_only: bool,
+    /// Whether proot-rs is the subreaper of the tracees, so that the orphans
+    /// are reparented to it and reaped by `PRoot::event_loop()`, see `--init`.
+    pub subreaper: bool,
+    /// The number of interpreters followed by `execve()`, see
+    /// `--max-interp-depth`.
+    pub max_interp_depth: usize,
+    /// Whether io_uring can be used by the tracees, see
+    /// `kernel::standard::denied`.
+    pub allow_io_uring: bool,
+    /// Whether `bpf()` can be used by the tracees, see
+    /// `kernel::standard::denied`.
+    pub allow_bpf: bool,
+    /// Whether `perf_event_open()` can be used by the tracees, see
+    /// `kernel::standard::denied`.
+    pub allow_perf: bool,
+    /// Whether the kernel keyrings can be used by the tracees, see
+    /// `kernel::standard::denied`.
+    pub allow_keyring: bool,
+    /// Whether `setns()` can be used by the tracees, see
+    /// `kernel::standard::denied`.
+    pub allow_setns: bool,
+    /// Whether `sync()` only flushes the file-systems of the guest, see
+    /// `kernel::standard::sync`.
+    pub scoped_sync: bool,
+    /// The cpus the tracees are restricted to, only set when the `--cpus`
+    /// option is used (see `kernel::standard::sched_affinity`).
+    pub cpus: Option<CpuSet>,
+    /// Prefix added to the names of the message queues, only set when the
+    /// `--mq-namespace` option is used (see `kernel::standard::mq`).
+    pub mq_prefix: Option<String>,
+    /// Statistics of the syscalls of the tracees, only collected when the
+    /// `--collect-stats` option is used.
+    pub stats: Option<RefCell<Stats>>,
+    /// The ids and the ownerships seen by the tracees, only set when the
+    /// `--fake-root` option is used (see `kernel::fake_root`).
+    pub fake_root: Option<RefCell<FakeRoot>>,
+    /// The syscalls printed in the trace log, only set when the
+    /// `--strace-filter` option is used (see `syscall::print_syscall()`).
+    pub strace_filter: Option<HashSet<usize>>,
+    /// The address families of the sockets allowed in the tracees, only set
+    /// when the `--socket-families` option is used (see
+    /// `kernel::socket::socket_family`).
+    pub socket_families: Option<Vec<c_int>>,
+    /// What to do with the syscalls whose path is not translated yet, only set
+    /// when the `--fail-on-unhandled-syscall` option is used (see
+    /// `kernel::standard::unhandled_path`).
+    pub unhandled_syscall_action: Option<UnhandledSyscallAction>,
 }
 
 impl InfoBag {
@@ -38,18 +118,48 @@ impl InfoBag {
         InfoBag {
             options_already_set: false,
             loader: TempFile::new("prooted"),
+            loader_ranges: vec![],
+            launch_time: None,
+            proc_emulation: true,
+            dev_emulation: false,
+            hide_host_pids: false,
+            chroot_only: false,
+            subreaper: false,
+            max_interp_depth: INTERPRETER_REWRITE_LIMIT,
+            allow_io_uring: false,
+            allow_bpf: false,
+            allow_perf: false,
+            allow_keyring: false,
+            allow_setns: false,
+            scoped_sync: false,
+            cpus: None,
+            mq_prefix: None,
+            stats: None,
+            fake_root: None,
+            strace_filter: None,
+            socket_families: None,
+            unhandled_syscall_action: None,
         }
     }
 }
 
 pub struct PRoot {
-    info_bag: InfoBag,
+    pub info_bag: InfoBag,
     tracees: HashMap<Pid, Tracee>,
     alive_tracees: Vec<Pid>,
     /// The `pid` of init process (i.e. the first tracee)
     pub init_pid: Option<Pid>,
     /// The exit code of the init process (i.e. the first tracee)
     pub init_exit_code: Option<i32>,
+    /// The signal which killed the init process, if any
+    pub init_term_signal: Option<Signal>,
+    /// Whether the init process exited because the loader failed to start its
+    /// program, in which case its exit code is the one of the loader (see
+    /// `EventHandler::handle_exit_event()`).
+    pub init_loader_failed: bool,
+    /// The deadline of the tracees, only set when the `--timeout` option is
+    /// used.
+    pub timeout: Option<Timeout>,
     /// A pointer to a function used to check the running status of Proot.
     /// For each syscall-stop, it will be called four times (at the beginning
     /// and end of both syscall-enter-stop and syscall-exit-stop).
@@ -68,6 +178,9 @@ impl PRoot {
             alive_tracees: vec![],
             init_pid: None,
             init_exit_code: None,
+            init_term_signal: None,
+            init_loader_failed: false,
+            timeout: None,
             #[cfg(test)]
             func_syscall_hook: None,
         }
@@ -81,6 +194,11 @@ impl PRoot {
             .loader
             .prepare_loader()
             .context("Error while prepare loader file")?;
+        self.info_bag.loader_ranges = self
+            .info_bag
+            .loader
+            .get_loader_ranges()
+            .context("Error while reading the address ranges of the loader")?;
         Ok(())
     }
 
@@ -93,10 +211,19 @@ impl PRoot {
     /// on all the shared memory of the parent and child processes
     /// (heap, libraries...), so both of them will have their own (owned)
     /// version of the PRoot memory.
-    pub fn launch_process(&mut self, initial_fs: FileSystem, command: Vec<String>) -> Result<()> {
+    ///
+    /// The command inherits the environment of proot-rs, in which the
+    /// variables of `env` are added (or replaced). The command is still looked
+    /// up in the `PATH` of proot-rs.
+    pub fn launch_process(
+        &mut self,
+        initial_fs: FileSystem,
+        command: Vec<String>,
+        env: &[(String, String)],
+    ) -> Result<()> {
         debug!(
-            "launch_process with fs:\n{:#?}\ncommand: {:?}",
-            initial_fs, command
+            "launch_process with fs:\n{:#?}\ncommand: {:?}\nenv: {:?}",
+            initial_fs, command, env
         );
 
         // parse command
@@ -109,8 +236,53 @@ impl PRoot {
             })
             .collect::<Result<Vec<_>>>()?;
         let filename = &args[0];
+        let env = std::env::vars_os()
+            .filter(|(name, _)| {
+                env.iter()
+                    .all(|(added, _)| name.as_bytes() != added.as_bytes())
+            })
+            .map(|(name, value)| [name.as_bytes(), b"=", value.as_bytes()].concat())
+            .chain(
+                env.iter()
+                    .map(|(name, value)| format!("{}={}", name, value).into_bytes()),
+            )
+            .map(|variable| {
+                CString::new(variable)
+                    .context("Illegal environment variable, should not contain \0 bytes")
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.launch(initial_fs, || {
+            sigactions::reset_sigpipe();
+            unistd::execvpe(&filename, &args, &env)
+                .with_context(|| format!("Failed to call execvpe() with command: {:?}", command))?;
+            unreachable!()
+        })
+    }
+
+    /// Same as `launch_process()`, except that the first tracee runs `func`
+    /// instead of executing a program, and exits once `func` returns.
+    ///
+    /// This is used to run some built-in workloads under proot-rs (e.g. see
+    /// `benchmark::run()`).
+    pub fn launch_function<F: FnOnce()>(&mut self, initial_fs: FileSystem, func: F) -> Result<()> {
+        debug!("launch_function with fs:\n{:#?}", initial_fs);
+
+        self.launch(initial_fs, || {
+            func();
+            process::exit(0)
+        })
+    }
+
+    /// Fork the first tracee, which will run `child_func` once it is ready to be
+    /// traced. `child_func` is not expected to return, unless an error occurs.
+    fn launch<F: FnOnce() -> Result<()>>(
+        &mut self,
+        initial_fs: FileSystem,
+        child_func: F,
+    ) -> Result<()> {
         match unsafe { unistd::fork() }.context("Failed to fork() when starting process")? {
             ForkResult::Parent { child } => {
+                PRoot::seize_first_tracee(child)?;
                 // create the first tracee
                 self.create_tracee(
                     child,
@@ -121,20 +293,15 @@ impl PRoot {
             }
             ForkResult::Child => {
                 let init_child_func = || -> Result<()> {
-                    // Declare the tracee as ptraceable
-                    ptrace::traceme()
-                        .context("Failed to execute ptrace::traceme() in a child process")?;
-                    // Synchronise with the parent's event loop by waiting until it's ready
-                    // (otherwise the execvp is executed too quickly)
+                    // Synchronise with the parent, which attaches to the tracee
+                    // while it is stopped (otherwise the execvp is executed too
+                    // quickly)
                     signal::kill(unistd::getpid(), Signal::SIGSTOP)
                         .context("Child process failed to synchronize with parent process")?;
                     //TODO: seccomp
                     //if (getenv("PROOT_NO_SECCOMP") == NULL)
                     //    (void) enable_syscall_filtering(tracee);
-                    unistd::execvp(&filename, &args).with_context(|| {
-                        format!("Failed to call execvp() with command: {:?}", command)
-                    })?;
-                    unreachable!()
+                    child_func()
                 };
 
                 if let Err(e) = init_child_func() {
@@ -147,16 +314,77 @@ impl PRoot {
         Ok(())
     }
 
+    /// Attaches to the first tracee `child` with `PTRACE_SEIZE`, once it has
+    /// stopped itself with `SIGSTOP`, and resumes it with `SIGCONT`. Its first
+    /// stop is then reported to the event loop as a `PTRACE_EVENT_STOP` (see
+    /// `SigStopStatus::EventloopSync`).
+    ///
+    /// The tracees are seized, rather than traced with `PTRACE_TRACEME`, so
+    /// that their group-stops are reported as such and can be left stopped
+    /// with `PTRACE_LISTEN`. Their children are seized the same way.
+    pub fn seize_first_tracee(child: Pid) -> Result<()> {
+        match wait::waitpid(child, Some(WaitPidFlag::WUNTRACED))
+            .context("Failed to wait for the first tracee to stop")?
+        {
+            Stopped(_, Signal::SIGSTOP) => {}
+            status => {
+                return Err(Error::errno_with_msg(
+                    ECHILD,
+                    format!("The first tracee did not stop itself: {:?}", status),
+                ))
+            }
+        }
+        ptrace::seize(child, Tracee::ptrace_options())
+            .context("Failed to execute ptrace::seize() on the first tracee")?;
+        signal::kill(child, Signal::SIGCONT).context("Failed to resume the first tracee")
+    }
+
     /// Infinite loop where PRoot will wait for tracees signals with `waitpid`.
     /// Tracees will be stopped when they use a system call.
     /// The tracer will be notified through `waitpid` and will be able to alter
     /// the parameters of the system call, before restarting the tracee.
+    ///
+    /// When a thread calls `exit_group()`, each thread of the group is
+    /// reported as exited. The exit of the leader is reported last, once all
+    /// the other threads are reaped, with the status given to `exit_group()`,
+    /// so the exit code of the init process is right even if another thread
+    /// made the call.
+    ///
+    /// With `InfoBag::subreaper`, the tracees whose parent exits are
+    /// reparented to proot-rs instead of the init process of the host, so
+    /// their exit is reported (and reaped) here, as for the other tracees.
     pub fn event_loop(&mut self) -> Result<()> {
+        if self.info_bag.subreaper {
+            // The orphans are reparented to the closest subreaper when their
+            // parent exits, so the tracees which already run are covered.
+            if unsafe { libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1, 0, 0, 0) } != 0 {
+                return Err(Error::errno_with_msg(
+                    Errno::last(),
+                    "Failed to call prctl(PR_SET_CHILD_SUBREAPER) for --init",
+                ));
+            }
+        }
         // TODO: what should we do if there is a terrible error in eventloop?
         while !self.alive_tracees.is_empty() {
-            match wait::waitpid(Pid::from_raw(-1), Some(WaitPidFlag::__WALL))
-                .context("Error calling waitpid() in event loop")?
-            {
+            self.check_timeout();
+            let wait_status = match wait::waitpid(Pid::from_raw(-1), Some(WaitPidFlag::__WALL)) {
+                // The remaining tids disappeared without being reported, which
+                // happens to the former tid of a thread which called `execve()`
+                // (it takes the pid of the leader).
+                Err(Errno::ECHILD) => {
+                    warn!(
+                        "-- no tracee left, but {:?} were not reported as exited",
+                        self.alive_tracees
+                    );
+                    self.alive_tracees.clear();
+                    self.tracees.clear();
+                    break;
+                }
+                // Interrupted by the timer of `--timeout`, checked above.
+                Err(Errno::EINTR) => continue,
+                result => result.context("Error calling waitpid() in event loop")?,
+            };
+            match wait_status {
                 Exited(pid, exit_status) => {
                     trace!("-- {}, Exited with status: {}", pid, exit_status);
                     self.register_tracee_finished(pid);
@@ -164,8 +392,8 @@ impl PRoot {
                         // The "init" process was exited. We need to record the exit code.
                         debug!("init process exited with exit code: {}", exit_status);
                         self.init_exit_code = Some(exit_status);
-                        // TODO: maybe we also need to take care of all the
-                        // "orphans" process?
+                        // Its orphans are still traced, and reaped by
+                        // proot-rs with `--init`.
                     }
                 }
                 Signaled(pid, term_signal, dumped_core) => {
@@ -181,8 +409,9 @@ impl PRoot {
                         // 128+signal
                         debug!("init process was killed by a signal: {}", term_signal);
                         self.init_exit_code = Some(128 + (term_signal as i32));
-                        // TODO: maybe we also need to take care of all the
-                        // "orphans" process?
+                        self.init_term_signal = Some(term_signal);
+                        // Its orphans are still traced, and reaped by
+                        // proot-rs with `--init`.
                     }
                 }
                 // The tracee was stopped by a normal signal (signal-delivery-stop), or was stopped
@@ -195,57 +424,20 @@ impl PRoot {
                         stop_signal as c_int
                     );
 
-                    let mut signal_to_delivery = Some(stop_signal);
-
-                    let maybe_tracee = self.tracees.get_mut(&pid);
-
-                    let tracee = if maybe_tracee.is_none() {
-                        if stop_signal == Signal::SIGSTOP {
-                            debug!("-- {}, SIGSTOP arrives before ptrace event but tracee is not initialized, so create a placeholder to record this.", pid);
-                            // Get tracee instance of init process, note that at this point
-                            // `init_pid` must not be none, so we can unwrap() it safely.
-                            let init_tracee = self.tracees.get(&self.init_pid.unwrap()).unwrap();
-                            // Create a new tracee instance as placeholder, only for record the pid
-                            // and sigstop status of this newly created process.
-                            // Since the `fs` field cannot be none value, we'll temporarily use the
-                            // value of the init process's fs field in its place, even though it
-                            // should be actually derived from the parent process. But please
-                            // remember that the `fs` field should not be used until the tracee is
-                            // fully initialized in the ptrace event handler function.
-                            let mut tracee = Tracee::new(pid, init_tracee.fs.clone());
-                            // We are waiting for a ptrace event to initialize this tracee.
-                            tracee.sigstop_status = SigStopStatus::WaitForEventClone;
-                            self.insert_new_tracee(tracee);
-                            signal_to_delivery = None;
-                            self.tracees.get_mut(&pid).unwrap()
-                        } else {
+                    let tracee = match self.tracees.get_mut(&pid) {
+                        Some(tracee) => tracee,
+                        None => {
                             error!("-- {}, Received a signal from an unknown tracee.", pid);
-                            // Deliver this SIGSTOP signal to this unknown tracee
+                            // Deliver this signal to this unknown tracee
                             ptrace::syscall(pid, Some(stop_signal))
-                                .expect("deliver stop signal to unknown tracee");
+                                .expect("deliver signal to unknown tracee");
                             // continue the event loop
                             continue;
                         }
-                    } else {
-                        maybe_tracee.unwrap()
                     };
                     tracee.reset_restart_how();
                     match stop_signal {
-                        Signal::SIGSTOP => {
-                            if tracee.sigstop_status == SigStopStatus::EventloopSync {
-                                // When the first child process starts, it sends a SIGSTOP to
-                                // itself. And we need to set ptrace
-                                // options at this point.
-                                tracee.check_and_set_ptrace_options(&mut self.info_bag)?;
-                                signal_to_delivery = None;
-                                tracee.sigstop_status = SigStopStatus::AllowDelivery;
-                            } else if tracee.sigstop_status == SigStopStatus::WaitForSigStopClone {
-                                signal_to_delivery = None;
-                                tracee.sigstop_status = SigStopStatus::AllowDelivery;
-                            }
-
-                            tracee.handle_sigstop_event();
-                        }
+                        Signal::SIGSTOP => tracee.handle_sigstop_event(),
                         Signal::SIGTRAP => {
                             // Since PTRACE_O_TRACESYSGOOD is not supported on older versions of
                             // Linux (version<2.4.6) and some architectures, we need to use
@@ -272,7 +464,7 @@ impl PRoot {
                     // the tracee in the next ptrace restart request.
                     // TODO: we should deliver this signal(sig) with ptrace(PTRACE_restart, pid, 0,
                     // sig)
-                    tracee.restart(signal_to_delivery);
+                    tracee.restart(Some(stop_signal));
                 }
                 // The tracee was stopped by a SIGTRAP with additional status (PTRACE_EVENT stops).
                 PtraceEvent(pid, signal, status_additional) => {
@@ -298,10 +490,17 @@ impl PRoot {
                         x if x == PtraceEvent::PTRACE_EVENT_SECCOMP as i32 => {
                             Some(PtraceEvent::PTRACE_EVENT_SECCOMP)
                         }
+                        x if x == PtraceEvent::PTRACE_EVENT_STOP as i32 => {
+                            Some(PtraceEvent::PTRACE_EVENT_STOP)
+                        }
                         _ => None,
                     };
 
                     trace!("-- {}, Ptrace event, {:?}, {:?}", pid, signal, maybe_event);
+                    if maybe_event == Some(PtraceEvent::PTRACE_EVENT_STOP) {
+                        self.handle_event_stop(pid, signal)?;
+                        continue;
+                    }
                     let tracee = self.tracees.get_mut(&pid).expect("get stopped tracee");
                     tracee.reset_restart_how();
 
@@ -315,17 +514,24 @@ impl PRoot {
                                     info!("-- {}, new process with pid {}", pid, child_tracee.pid);
                                     // If a placeholder exists, replace it with fully initialized
                                     // tracee.
-                                    if let Some(tracee_placeholder) =
-                                        self.tracees.get(&child_tracee.pid)
-                                    {
-                                        if tracee_placeholder.sigstop_status
-                                            == SigStopStatus::WaitForEventClone
-                                        {
-                                            child_tracee.sigstop_status =
-                                                SigStopStatus::AllowDelivery;
-                                        }
+                                    let child_pid = child_tracee.pid;
+                                    let waiting_placeholder =
+                                        self.tracees.get(&child_pid).map_or(false, |placeholder| {
+                                            placeholder.sigstop_status
+                                                == SigStopStatus::WaitForEventClone
+                                        });
+                                    if waiting_placeholder {
+                                        child_tracee.sigstop_status = SigStopStatus::AllowDelivery;
+                                    }
+                                    self.insert_new_tracee(child_tracee);
+                                    // The child was kept stopped since its first stop, it
+                                    // can now be restarted.
+                                    if waiting_placeholder {
+                                        let child_tracee =
+                                            self.tracees.get_mut(&child_pid).unwrap();
+                                        child_tracee.reset_restart_how();
+                                        child_tracee.restart(None);
                                     }
-                                    self.insert_new_tracee(child_tracee)
                                 }
                                 Err(error) => {
                                     error!(
@@ -340,6 +546,25 @@ impl PRoot {
                         | Some(PtraceEvent::PTRACE_EVENT_VFORK_DONE) => {
                             tracee.handle_exec_vfork_event();
                         }
+                        // handle_exit_event
+                        Some(PtraceEvent::PTRACE_EVENT_EXIT) => {
+                            match tracee.handle_exit_event(&self.info_bag) {
+                                // The failure of the init process is reported
+                                // by `main()`.
+                                Ok(true) if Some(pid) == self.init_pid => {
+                                    self.init_loader_failed = true
+                                }
+                                Ok(true) => error!(
+                                    "-- {}, the loader failed during program startup (see the error of loader-shim above), exiting with {}",
+                                    pid, PANIC_EXIT_CODE
+                                ),
+                                Ok(false) => {}
+                                Err(error) => warn!(
+                                    "Error while handling exit event for pid {}. {}",
+                                    pid, error
+                                ),
+                            }
+                        }
                         // handle_seccomp_event
                         Some(PtraceEvent::PTRACE_EVENT_SECCOMP) => {
                             // TODO: consider PTRACE_EVENT_SECCOMP2
@@ -378,6 +603,15 @@ impl PRoot {
             }
         }
 
+        // Each tracee is registered as alive, so none of them should be left,
+        // but their states would be stale if one was missed.
+        if !self.tracees.is_empty() {
+            warn!(
+                "-- no tracee alive, but the states of {:?} were kept",
+                self.tracees.keys().collect::<Vec<_>>()
+            );
+            self.tracees.clear();
+        }
         Ok(())
     }
 
@@ -389,6 +623,8 @@ impl PRoot {
     ) -> Option<&Tracee> {
         let mut tracee = Tracee::new(pid, fs);
         tracee.sigstop_status = sigstop_status;
+        tracee.proc_emulation = self.info_bag.proc_emulation;
+        tracee.dev_emulation = self.info_bag.dev_emulation;
         self.tracees.insert(pid, tracee);
         self.register_alive_tracee(pid);
         self.tracees.get(&pid)
@@ -401,7 +637,89 @@ impl PRoot {
     }
 
     fn register_alive_tracee(&mut self, pid: Pid) {
-        self.alive_tracees.push(pid);
+        // A placeholder may already have been registered for this pid.
+        if !self.alive_tracees.contains(&pid) {
+            self.alive_tracees.push(pid);
+        }
+    }
+
+    /// Handles a `PTRACE_EVENT_STOP`, which the tracees attached with
+    /// `PTRACE_SEIZE` report for their first stop, for a group-stop (i.e. when
+    /// they are stopped by `SIGSTOP`, `SIGTSTP`, `SIGTTIN` or `SIGTTOU`), and
+    /// once resumed from a group-stop by `SIGCONT`. See ptrace(2): Group-stop
+    fn handle_event_stop(&mut self, pid: Pid, stop_signal: Signal) -> Result<()> {
+        let tracee = match self.tracees.get_mut(&pid) {
+            Some(tracee) => tracee,
+            None => {
+                debug!("-- {}, first stop arrives before ptrace event but tracee is not initialized, so create a placeholder to record this.", pid);
+                // Get tracee instance of init process, note that at this point
+                // `init_pid` must not be none, so we can unwrap() it safely.
+                let init_tracee = self.tracees.get(&self.init_pid.unwrap()).unwrap();
+                // Create a new tracee instance as placeholder, only for record the pid
+                // and sigstop status of this newly created process.
+                // Since the `fs` field cannot be none value, we'll temporarily use the
+                // value of the init process's fs field in its place, even though it
+                // should be actually derived from the parent process. But please
+                // remember that the `fs` field should not be used until the tracee is
+                // fully initialized in the ptrace event handler function.
+                let mut tracee = Tracee::new(pid, init_tracee.fs.clone());
+                // We are waiting for a ptrace event to initialize this tracee.
+                tracee.sigstop_status = SigStopStatus::WaitForEventClone;
+                self.insert_new_tracee(tracee);
+                // It is kept stopped until then, so that its first syscall is
+                // translated with its own state (e.g. the fs of its parent)
+                // rather than the placeholder one.
+                return Ok(());
+            }
+        };
+        match tracee.sigstop_status {
+            SigStopStatus::EventloopSync => {
+                // Fail now with an explanation if ptrace() is restricted,
+                // rather than at the first syscall.
+                Registers::check_access(pid)?;
+                tracee.sigstop_status = SigStopStatus::AllowDelivery;
+            }
+            SigStopStatus::WaitForSigStopClone => {
+                tracee.sigstop_status = SigStopStatus::AllowDelivery;
+            }
+            // Still waiting for the ptrace event of its parent.
+            SigStopStatus::WaitForEventClone => return Ok(()),
+            SigStopStatus::AllowDelivery => match stop_signal {
+                // The tracee is stopped by job control. Restarting it would
+                // resume it immediately, so it is left stopped until it
+                // receives `SIGCONT`, which is reported by another
+                // `PTRACE_EVENT_STOP` (with `SIGTRAP`).
+                Signal::SIGSTOP | Signal::SIGTSTP | Signal::SIGTTIN | Signal::SIGTTOU => {
+                    debug!("-- {}, group-stop by {:?}", pid, stop_signal);
+                    tracee.listen();
+                    return Ok(());
+                }
+                _ => {}
+            },
+        }
+        tracee.reset_restart_how();
+        tracee.restart(None);
+        Ok(())
+    }
+
+    /// Sends the signal of `--timeout` once its timer fired: `SIGTERM` to the
+    /// init process, then `SIGKILL` to all the tracees if they are still
+    /// alive after `timeout::KILL_DELAY`.
+    fn check_timeout(&mut self) {
+        let signal = match self.timeout.as_mut().and_then(|timeout| timeout.poll()) {
+            Some(signal) => signal,
+            None => return,
+        };
+        let pids: Vec<Pid> = if signal == Signal::SIGKILL {
+            self.alive_tracees.clone()
+        } else {
+            self.init_pid.into_iter().collect()
+        };
+        warn!("--timeout expired, sending {:?} to {:?}", signal, pids);
+        for pid in pids {
+            // The tracee may already be gone.
+            let _ = signal::kill(pid, signal);
+        }
     }
 
     fn register_tracee_finished(&mut self, finished_pid: Pid) {
@@ -424,7 +742,17 @@ pub extern "C" fn show_info(pid: pid_t) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use nix::unistd::Pid;
+    use crate::filesystem::temp::TempDir;
+    use crate::utils::tests::{
+        test_in_subprocess, test_with_proot, test_with_proot_and_exit_code,
+        test_with_proot_and_info_bag,
+    };
+    use nix::sys::mman::{mmap, MapFlags, ProtFlags};
+    use nix::sys::signal::kill;
+    use nix::sys::wait::{waitpid, WaitStatus};
+    use nix::unistd::{fork, ForkResult, Pid};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
 
     #[test]
     fn create_proot_and_tracee() {
@@ -451,4 +779,193 @@ mod tests {
             assert!(tracee.is_some());
         }
     }
+
+    #[test]
+    fn test_exit_group_from_another_thread() {
+        test_with_proot_and_exit_code(
+            3,
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                // A thread which is blocked, and must be reaped along with the
+                // others.
+                std::thread::spawn(|| loop {
+                    std::thread::park();
+                });
+                std::thread::spawn(|| unsafe {
+                    libc::syscall(libc::SYS_exit_group, 3);
+                });
+                // The leader never exits by itself.
+                loop {
+                    std::thread::park();
+                }
+            },
+        )
+    }
+
+    #[test]
+    fn test_group_stop_and_continue() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                // A counter shared with the child, which keeps incrementing it.
+                let counter = unsafe {
+                    &*(mmap(
+                        std::ptr::null_mut(),
+                        std::mem::size_of::<AtomicUsize>(),
+                        ProtFlags::PROT_READ | ProtFlags::PROT_WRITE,
+                        MapFlags::MAP_SHARED | MapFlags::MAP_ANONYMOUS,
+                        -1,
+                        0,
+                    )
+                    .unwrap() as *const AtomicUsize)
+                };
+                let wait_for_progress = || {
+                    let start = counter.load(Ordering::SeqCst);
+                    while counter.load(Ordering::SeqCst) == start {
+                        std::thread::sleep(Duration::from_millis(1));
+                    }
+                };
+
+                match unsafe { fork() }.unwrap() {
+                    ForkResult::Child => loop {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        // A syscall, so that the child is also stopped by the tracer.
+                        std::thread::sleep(Duration::from_millis(1));
+                    },
+                    ForkResult::Parent { child } => {
+                        wait_for_progress();
+
+                        kill(child, Signal::SIGSTOP).unwrap();
+                        assert_eq!(
+                            waitpid(child, Some(WaitPidFlag::WUNTRACED)),
+                            Ok(WaitStatus::Stopped(child, Signal::SIGSTOP))
+                        );
+                        // The child is really stopped.
+                        let stopped_at = counter.load(Ordering::SeqCst);
+                        std::thread::sleep(Duration::from_millis(100));
+                        assert_eq!(counter.load(Ordering::SeqCst), stopped_at);
+
+                        kill(child, Signal::SIGCONT).unwrap();
+                        assert_eq!(
+                            waitpid(child, Some(WaitPidFlag::WCONTINUED)),
+                            Ok(WaitStatus::Continued(child))
+                        );
+                        wait_for_progress();
+
+                        kill(child, Signal::SIGKILL).unwrap();
+                        assert_eq!(
+                            waitpid(child, None),
+                            Ok(WaitStatus::Signaled(child, Signal::SIGKILL, false))
+                        );
+                    }
+                }
+            },
+        )
+    }
+
+    #[test]
+    fn test_fork_getpid_in_child() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let parent = nix::unistd::getpid();
+                let (read_fd, write_fd) = nix::unistd::pipe().unwrap();
+                match unsafe { fork() }.unwrap() {
+                    ForkResult::Child => {
+                        // The very first syscalls of the child.
+                        let pids = [nix::unistd::getpid(), nix::unistd::getppid()]
+                            .iter()
+                            .map(|pid| pid.as_raw().to_ne_bytes())
+                            .collect::<Vec<_>>()
+                            .concat();
+                        nix::unistd::write(write_fd, &pids).unwrap();
+                        unsafe { libc::_exit(0) };
+                    }
+                    ForkResult::Parent { child } => {
+                        nix::unistd::close(write_fd).unwrap();
+                        let mut pids = [0u8; 8];
+                        assert_eq!(nix::unistd::read(read_fd, &mut pids), Ok(8));
+                        let pid_at = |i: usize| {
+                            let mut bytes = [0u8; 4];
+                            bytes.copy_from_slice(&pids[i * 4..i * 4 + 4]);
+                            Pid::from_raw(i32::from_ne_bytes(bytes))
+                        };
+                        assert_eq!(pid_at(0), child);
+                        assert_eq!(pid_at(1), parent);
+                        assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+                    }
+                }
+            },
+        )
+    }
+
+    #[test]
+    fn test_subreaper_reaps_orphan() {
+        test_with_proot_and_info_bag(
+            |info_bag| info_bag.subreaper = true,
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let proot = nix::unistd::getppid();
+                let (read_fd, write_fd) = nix::unistd::pipe().unwrap();
+                match unsafe { fork() }.unwrap() {
+                    ForkResult::Child => {
+                        let parent = nix::unistd::getpid();
+                        if let ForkResult::Parent { .. } = unsafe { fork() }.unwrap() {
+                            // The grandchild becomes an orphan.
+                            unsafe { libc::_exit(0) };
+                        }
+                        let mut new_parent = nix::unistd::getppid();
+                        while new_parent == parent {
+                            std::thread::sleep(Duration::from_millis(1));
+                            new_parent = nix::unistd::getppid();
+                        }
+                        nix::unistd::write(write_fd, &new_parent.as_raw().to_ne_bytes()).unwrap();
+                        unsafe { libc::_exit(0) };
+                    }
+                    ForkResult::Parent { child } => {
+                        nix::unistd::close(write_fd).unwrap();
+                        assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+                        let mut new_parent = [0u8; 4];
+                        assert_eq!(nix::unistd::read(read_fd, &mut new_parent), Ok(4));
+                        // The orphan is adopted by proot-rs, not by the init
+                        // process of the host.
+                        assert_eq!(Pid::from_raw(i32::from_ne_bytes(new_parent)), proot);
+                    }
+                }
+            },
+        )
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn test_loader_failure_is_reported() {
+        use std::os::unix::fs::PermissionsExt;
+
+        use crate::utils::tests::x86_64_executable;
+
+        // The only segment is out of the address space of the user, so that
+        // the loader fails to map it.
+        let elf = x86_64_executable(
+            0xffff_8000_0000_0000,
+            &[(0xffff_8000_0000_0000, 0x1000, 0x1000)],
+        );
+
+        let rootfs = TempDir::new("prefix_test_loader_failure_is_reported");
+        rootfs.create_dir().unwrap();
+        let executable = rootfs.path.join("unmappable");
+        std::fs::write(&executable, &elf).unwrap();
+        std::fs::set_permissions(&executable, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        test_in_subprocess(|| {
+            let fs = FileSystem::with_root(&rootfs.path).unwrap();
+            let mut proot = PRoot::new();
+            proot.init().unwrap();
+            proot
+                .launch_process(fs, vec!["/unmappable".into()], &[])
+                .unwrap();
+            proot.event_loop().unwrap();
+            assert_eq!(proot.init_exit_code, Some(PANIC_EXIT_CODE));
+            assert!(proot.init_loader_failed);
+        })
+    }
 }
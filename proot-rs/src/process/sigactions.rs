@@ -2,10 +2,15 @@
 use libc::{c_int, c_void, pid_t, siginfo_t};
 use nix::sys::signal::SaFlags;
 use nix::sys::signal::Signal::*;
-use nix::sys::signal::{sigaction, SigAction, SigHandler, SigSet, Signal};
+use nix::sys::signal::{raise, sigaction, SigAction, SigHandler, SigSet, Signal};
 
 /// Configures the actions associated with specific critical signals.
 /// All signals are blocked when the signal handler is called.
+///
+/// Must be called once the first tracee is forked: the ignored signals would
+/// stay ignored in the guest, even through `execve()`. The guest only ever
+/// changes its own actions and alternate stack (`rt_sigaction()` and
+/// `sigaltstack()` are forwarded as is), never the ones of proot-rs.
 pub fn prepare_sigactions(
     stop_program: extern "C" fn(c_int, *mut siginfo_t, *mut c_void),
     show_info: extern "C" fn(pid: pid_t),
@@ -49,9 +54,52 @@ pub fn prepare_sigactions(
     }
 }
 
+/// Restores the default action of `SIGPIPE`, in the first tracee before it
+/// executes *command*.
+///
+/// The Rust runtime ignores `SIGPIPE` at the startup of proot-rs, which would
+/// be inherited by the guest through `execve()`: a write to a closed pipe
+/// would fail with `EPIPE` instead of killing the writer (e.g. `yes | head`).
+pub fn reset_sigpipe() {
+    let default_action = SigAction::new(SigHandler::SigDfl, SaFlags::empty(), SigSet::empty());
+    if let Err(err) = unsafe { sigaction(SIGPIPE, &default_action) } {
+        warn!("Failed to reset the action of SIGPIPE: {:?}", err);
+    }
+}
+
+/// Makes the current process die from `signal`, as if it was not handled.
+///
+/// Used to report the signal which killed the init process to the parent of
+/// proot-rs (see the `--exit-code-passthrough` option). The core dump of
+/// proot-rs would not be useful, so it is disabled. Returns only if the
+/// signal did not terminate the process.
+pub fn raise_with_default_action(signal: Signal) {
+    let no_core_dump = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    unsafe { libc::setrlimit(libc::RLIMIT_CORE, &no_core_dump) };
+
+    let default_action = SigAction::new(SigHandler::SigDfl, SaFlags::empty(), SigSet::empty());
+    // The action of `SIGKILL` cannot be changed, there is no need to.
+    let _ = unsafe { sigaction(signal, &default_action) };
+    let mut signal_set = SigSet::empty();
+    signal_set.add(signal);
+    let _ = signal_set.thread_unblock();
+
+    if let Err(err) = raise(signal) {
+        warn!("Failed to raise signal {:?}: {:?}", signal, err);
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
     use super::*;
+    use crate::utils::tests::test_with_proot;
+    use nix::sys::wait::{waitpid, WaitStatus};
+    use nix::unistd::{fork, ForkResult};
 
     pub extern "C" fn mock_stop_program(_: c_int, _: *mut siginfo_t, _: *mut c_void) {}
     pub extern "C" fn mock_show_info(_: pid_t) {}
@@ -62,5 +110,111 @@ mod tests {
         prepare_sigactions(mock_stop_program, mock_show_info);
     }
 
+    #[test]
+    fn test_raise_with_default_action() {
+        match unsafe { fork() }.unwrap() {
+            ForkResult::Child => {
+                // `SIGTERM` is ignored by proot-rs
+                prepare_sigactions(mock_stop_program, mock_show_info);
+                raise_with_default_action(SIGTERM);
+                unsafe { libc::_exit(0) };
+            }
+            ForkResult::Parent { child } => {
+                assert_eq!(
+                    waitpid(child, None),
+                    Ok(WaitStatus::Signaled(child, SIGTERM, false))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_reset_sigpipe() {
+        match unsafe { fork() }.unwrap() {
+            ForkResult::Child => {
+                let ignore = SigAction::new(SigHandler::SigIgn, SaFlags::empty(), SigSet::empty());
+                unsafe { sigaction(SIGPIPE, &ignore) }.unwrap();
+                reset_sigpipe();
+                let _ = raise(SIGPIPE);
+                unsafe { libc::_exit(0) };
+            }
+            ForkResult::Parent { child } => {
+                assert_eq!(
+                    waitpid(child, None),
+                    Ok(WaitStatus::Signaled(child, SIGPIPE, false))
+                );
+            }
+        }
+    }
+
+    /// The address of a local variable of `record_stack_address()`.
+    static HANDLER_STACK_ADDRESS: AtomicUsize = AtomicUsize::new(0);
+
+    extern "C" fn record_stack_address(_: c_int) {
+        let local = 0u8;
+        HANDLER_STACK_ADDRESS.store(&local as *const u8 as usize, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_guest_sigaction_on_alternate_stack() {
+        test_with_proot(
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let mut alt_stack = vec![0u8; 4 * libc::SIGSTKSZ];
+                let stack = libc::stack_t {
+                    ss_sp: alt_stack.as_mut_ptr() as *mut c_void,
+                    ss_flags: 0,
+                    ss_size: alt_stack.len(),
+                };
+                assert_eq!(
+                    unsafe { libc::sigaltstack(&stack, std::ptr::null_mut()) },
+                    0
+                );
+                let mut current: libc::stack_t = unsafe { std::mem::zeroed() };
+                assert_eq!(
+                    unsafe { libc::sigaltstack(std::ptr::null(), &mut current) },
+                    0
+                );
+                assert_eq!(
+                    (current.ss_sp, current.ss_size),
+                    (stack.ss_sp, stack.ss_size)
+                );
+
+                let action = SigAction::new(
+                    SigHandler::Handler(record_stack_address),
+                    SaFlags::SA_ONSTACK,
+                    SigSet::empty(),
+                );
+                unsafe { sigaction(SIGUSR1, &action) }.unwrap();
+                // The action of the tracee, not the one of proot-rs.
+                let old_action = unsafe { sigaction(SIGUSR1, &action) }.unwrap();
+                assert_eq!(
+                    old_action.handler(),
+                    SigHandler::Handler(record_stack_address)
+                );
+                assert!(old_action.flags().contains(SaFlags::SA_ONSTACK));
+
+                raise(SIGUSR1).unwrap();
+                let address = HANDLER_STACK_ADDRESS.load(Ordering::SeqCst);
+                let start = alt_stack.as_ptr() as usize;
+                assert!(
+                    start <= address && address < start + alt_stack.len(),
+                    "{:#x} is not on the alternate stack",
+                    address
+                );
+
+                let disabled = libc::stack_t {
+                    ss_sp: std::ptr::null_mut(),
+                    ss_flags: libc::SS_DISABLE,
+                    ss_size: 0,
+                };
+                assert_eq!(
+                    unsafe { libc::sigaltstack(&disabled, std::ptr::null_mut()) },
+                    0
+                );
+            },
+        )
+    }
+
     //TODO: test show_info
 }
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::kernel::syscall::name_of_syscall;
+
+/// Counters of a single system call.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SyscallStats {
+    /// Number of times the syscall was entered by a tracee.
+    pub calls: usize,
+    /// Number of times the enter stage of the translation failed (e.g. a path
+    /// could not be translated), in which case the syscall was not run.
+    pub errors: usize,
+}
+
+/// Statistics accumulated during the run with the `--collect-stats` option,
+/// and printed when the sandbox exits.
+///
+/// There is no cache of the path translations yet, so the overhead can only be
+/// estimated with the number of calls of each syscall.
+#[derive(Debug, Default)]
+pub struct Stats {
+    syscalls: HashMap<usize, SyscallStats>,
+}
+
+impl Stats {
+    pub fn new() -> Stats {
+        Stats::default()
+    }
+
+    /// Records the enter stage of the syscall `sys_num`, whose translation
+    /// succeeded or not.
+    pub fn record_syscall(&mut self, sys_num: usize, translated: bool) {
+        let stats = self.syscalls.entry(sys_num).or_default();
+        stats.calls += 1;
+        if !translated {
+            stats.errors += 1;
+        }
+    }
+
+    pub fn get_syscall_stats(&self, sys_num: usize) -> SyscallStats {
+        self.syscalls.get(&sys_num).cloned().unwrap_or_default()
+    }
+
+    /// Returns a table of the syscalls, the most called first.
+    pub fn summary(&self) -> String {
+        let mut syscalls: Vec<(&usize, &SyscallStats)> = self.syscalls.iter().collect();
+        syscalls.sort_by(|(sys_num_a, a), (sys_num_b, b)| {
+            b.calls.cmp(&a.calls).then(sys_num_a.cmp(sys_num_b))
+        });
+
+        let mut summary = format!("{:<24} {:>10} {:>10}\n", "syscall", "calls", "errors");
+        for (sys_num, stats) in &syscalls {
+            let name = match name_of_syscall(**sys_num) {
+                Some(name) => name.to_string(),
+                None => format!("#{}", sys_num),
+            };
+            let _ = writeln!(
+                summary,
+                "{:<24} {:>10} {:>10}",
+                name, stats.calls, stats.errors
+            );
+        }
+        let _ = write!(
+            summary,
+            "{:<24} {:>10} {:>10}",
+            "total",
+            syscalls.iter().map(|(_, stats)| stats.calls).sum::<usize>(),
+            syscalls
+                .iter()
+                .map(|(_, stats)| stats.errors)
+                .sum::<usize>()
+        );
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_summary() {
+        let mut stats = Stats::new();
+        stats.record_syscall(sc::nr::GETCWD, true);
+        stats.record_syscall(sc::nr::CHDIR, true);
+        stats.record_syscall(sc::nr::CHDIR, false);
+
+        assert_eq!(
+            stats.get_syscall_stats(sc::nr::CHDIR),
+            SyscallStats {
+                calls: 2,
+                errors: 1
+            }
+        );
+        assert_eq!(
+            stats.get_syscall_stats(sc::nr::EXECVE),
+            SyscallStats::default()
+        );
+
+        let lines: Vec<Vec<&str>> = stats
+            .summary()
+            .lines()
+            .map(|line| line.split_whitespace().collect())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                vec!["syscall", "calls", "errors"],
+                vec!["chdir", "2", "1"],
+                vec!["getcwd", "1", "0"],
+                vec!["total", "3", "1"],
+            ]
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use libc::c_int;
+use nix::sys::signal::{sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal};
+
+use crate::errors::*;
+
+/// The exit code of proot-rs when *command* was killed by `--timeout`, the
+/// same as the `timeout` program of coreutils.
+pub const TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// The delay between the `SIGTERM` and the `SIGKILL` sent by `--timeout`.
+pub const KILL_DELAY: Duration = Duration::from_secs(5);
+
+/// Set by the handler of `SIGALRM`, and reset by `Timeout::poll()`.
+static ALARM_RECEIVED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_alarm(_: c_int) {
+    ALARM_RECEIVED.store(true, Ordering::SeqCst);
+}
+
+/// The deadline of the `--timeout` option.
+///
+/// It is implemented with `SIGALRM`, whose handler interrupts the `waitpid()`
+/// of the event loop (`EINTR`), then the event loop calls `Timeout::poll()`.
+/// Once the deadline has passed, the timer fires every second, so that a
+/// signal received just before `waitpid()` is noticed at the next one.
+#[derive(Debug)]
+pub struct Timeout {
+    /// When the deadline passed, i.e. when `SIGTERM` was sent.
+    expired_at: Option<Instant>,
+}
+
+impl Timeout {
+    /// Starts a timer of `seconds`. Must be called after
+    /// `sigactions::prepare_sigactions()`, which ignores `SIGALRM`.
+    pub fn start(seconds: u32) -> Result<Timeout> {
+        // No `SA_RESTART`, so that `waitpid()` is interrupted.
+        let action = SigAction::new(
+            SigHandler::Handler(on_alarm),
+            SaFlags::empty(),
+            SigSet::all(),
+        );
+        unsafe { sigaction(Signal::SIGALRM, &action) }
+            .context("Failed to set the action of SIGALRM for --timeout")?;
+
+        let timer = libc::itimerval {
+            it_interval: libc::timeval {
+                tv_sec: 1,
+                tv_usec: 0,
+            },
+            it_value: libc::timeval {
+                tv_sec: seconds as libc::time_t,
+                tv_usec: 0,
+            },
+        };
+        if unsafe { libc::setitimer(libc::ITIMER_REAL, &timer, std::ptr::null_mut()) } != 0 {
+            return Err(Error::errno_with_msg(
+                Errno::last(),
+                "Failed to call setitimer() for --timeout",
+            ));
+        }
+        Ok(Timeout { expired_at: None })
+    }
+
+    /// Whether the deadline has passed.
+    pub fn expired(&self) -> bool {
+        self.expired_at.is_some()
+    }
+
+    /// Returns the signal to send to the tracees if the timer fired since the
+    /// last call: `SIGTERM` at the deadline, then `SIGKILL` once `KILL_DELAY`
+    /// has passed.
+    pub fn poll(&mut self) -> Option<Signal> {
+        if !ALARM_RECEIVED.swap(false, Ordering::SeqCst) {
+            return None;
+        }
+        match self.expired_at {
+            None => {
+                self.expired_at = Some(Instant::now());
+                Some(Signal::SIGTERM)
+            }
+            Some(expired_at) if expired_at.elapsed() >= KILL_DELAY => Some(Signal::SIGKILL),
+            Some(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeout_poll() {
+        let mut timeout = Timeout { expired_at: None };
+        assert_eq!(timeout.poll(), None);
+        assert!(!timeout.expired());
+
+        on_alarm(libc::SIGALRM);
+        assert_eq!(timeout.poll(), Some(Signal::SIGTERM));
+        assert!(timeout.expired());
+        assert_eq!(timeout.poll(), None);
+
+        // Before the end of the delay.
+        on_alarm(libc::SIGALRM);
+        assert_eq!(timeout.poll(), None);
+
+        timeout.expired_at = Some(Instant::now() - KILL_DELAY);
+        on_alarm(libc::SIGALRM);
+        assert_eq!(timeout.poll(), Some(Signal::SIGKILL));
+    }
+}
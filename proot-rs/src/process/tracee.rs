@@ -1,6 +1,7 @@
 use std::cell::RefCell;
+use std::ffi::OsStr;
 use std::os::unix::io::RawFd;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::rc::Rc;
 
 use nix::sys::ptrace::{self, Options};
@@ -9,12 +10,43 @@ use nix::unistd::Pid;
 use nix::NixPath;
 
 use crate::errors::*;
+use crate::filesystem::Canonicalizer;
 use crate::filesystem::Substitutor;
 use crate::filesystem::Translator;
 use crate::filesystem::{binding::Side, FileSystem};
 use crate::kernel::execve::load_info::LoadInfo;
+use crate::kernel::standard::chmod_access_mknod_at::FACCESSAT2;
+use crate::kernel::standard::sethostname::VirtualUts;
 use crate::process::proot::InfoBag;
-use crate::register::{Registers, Word};
+use crate::register::{Original, Registers, Word};
+
+/// See ptrace(2), which is not defined by the libc crate for every target.
+const PTRACE_LISTEN: usize = 0x4208;
+
+/// The devices which are emulated with `--emulate-dev`, see
+/// `Tracee::translate_emulated_dev()`.
+pub const EMULATED_DEVICES: [&str; 3] = ["/dev/null", "/dev/zero", "/dev/full"];
+
+/// The files of the guest `/dev` which are usually symlinks to the magic links
+/// of the fds, see `Tracee::translate_dev_fd_link()`.
+const DEV_FD_LINKS: [&str; 3] = ["stdin", "stdout", "stderr"];
+
+/// Returns whether the emulated devices are translated for the syscall
+/// `sys_num`, i.e. for the syscalls of the `open()`, `stat()` and `access()`
+/// families. The other ones (e.g. `unlink()` or `mknod()`) must not reach the
+/// devices of the host.
+fn uses_emulated_devices(sys_num: usize) -> bool {
+    match sys_num {
+        sc::nr::OPENAT | sc::nr::STATX | sc::nr::FACCESSAT | FACCESSAT2 => true,
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "arm"))]
+        sc::nr::OPEN | sc::nr::CREAT | sc::nr::STAT | sc::nr::LSTAT | sc::nr::ACCESS => true,
+        #[cfg(any(target_arch = "x86", target_arch = "arm"))]
+        sc::nr::STAT64 | sc::nr::LSTAT64 | sc::nr::FSTATAT64 => true,
+        #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+        sc::nr::NEWFSTATAT => true,
+        _ => false,
+    }
+}
 
 #[derive(Debug, PartialEq)]
 pub enum TraceeStatus {
@@ -53,25 +85,26 @@ pub enum TraceeRestartMethod {
     None,
 }
 
-/// In the `ptrace()` environment, the `SIGSTOP` signal received by tracee
-/// sometimes has a special meaning, and it is necessary to record the `SIGSTOP`
-/// status for tracee to tell `Proot` how to handle the next incoming `SIGSTOP`
-/// signal correctly.
+/// The tracees are attached with `PTRACE_SEIZE`, so that their first stop,
+/// and their group-stops, are reported as `PTRACE_EVENT_STOP`. It is necessary
+/// to record the status of the tracee to tell `Proot` how to handle the next
+/// incoming `PTRACE_EVENT_STOP` correctly.
 #[derive(Debug, PartialEq)]
 pub enum SigStopStatus {
-    /// Allow SIGSTOP to be passed to tracee, which is the most common case.
+    /// The first stop of the tracee was handled, the next ones are
+    /// group-stops (or their end), which is the most common case.
     AllowDelivery,
-    /// The next SIGSTOP signal is used to synchronize with Proot process, and
-    /// is only used during creating the first tracee.
+    /// The first stop is used to synchronize with Proot process, and is only
+    /// used during creating the first tracee.
     EventloopSync,
     /// The current process is a new process created by
     /// `fork()`/`vfork()`/`clone()`, and one of the ptrace events
     /// `PTRACE_EVENT_(FORK|VFORK|CLONE)` has been received. So we are waiting
-    /// to eliminate the next associated SIGSTOP signal.
+    /// for its first stop to restart it.
     /// See the description of PTRACE_O_TRACE(FORK|VFORK|CLONE) in ptrace(2).
     WaitForSigStopClone,
     /// The current process is a new process created by
-    /// `fork()`/`vfork()`/`clone()`, and the SIGSTOP signal arrives before the
+    /// `fork()`/`vfork()`/`clone()`, and its first stop arrives before the
     /// ptrace events. In this case, initialization of this tracee object is not
     /// completed, because we have no way to known the parent id of this tracee.
     /// So that we are waiting for one of the ptrace events
@@ -91,6 +124,14 @@ pub struct Tracee {
     pub restart_how: TraceeRestartMethod,
     /// Contains the bindings and functions used for path translation.
     pub fs: Rc<RefCell<FileSystem>>,
+    /// Hostname and domainname of the sandbox, shared by all tracees.
+    pub uts: Rc<RefCell<VirtualUts>>,
+    /// Whether the paths in `/proc` are emulated for this tracee, which is
+    /// disabled by the `--no-proc-emulation` option.
+    pub proc_emulation: bool,
+    /// Whether the devices of `EMULATED_DEVICES` are emulated for this tracee,
+    /// which is enabled by the `--emulate-dev` option.
+    pub dev_emulation: bool,
     /// Cached version of the process' general purpose registers.
     pub regs: Registers,
     /// State of the seccomp acceleration for this tracee.
@@ -117,6 +158,9 @@ impl Tracee {
             status: TraceeStatus::SysEnter, // it always starts by the enter stage
             restart_how: TraceeRestartMethod::None,
             fs: fs,
+            uts: Rc::new(RefCell::new(VirtualUts::default())),
+            proc_emulation: true,
+            dev_emulation: false,
             regs: Registers::new(pid),
             seccomp: false,
             sysexit_pending: false,
@@ -162,6 +206,14 @@ impl Tracee {
         self.restart_how = TraceeRestartMethod::None;
     }
 
+    /// Leaves the tracee, which is in group-stop, stopped until it receives a
+    /// `SIGCONT`, as if it was not traced: the tracer is then notified by
+    /// another `PTRACE_EVENT_STOP`. See ptrace(2): PTRACE_LISTEN
+    pub fn listen(&self) {
+        let res = unsafe { libc::ptrace(PTRACE_LISTEN as _, self.pid, 0, 0) };
+        nix::errno::Errno::result(res).expect("leave tracee in group-stop");
+    }
+
     /// Distinguish some events from others and
     /// automatically trace each new process with
     /// the same options.
@@ -177,17 +229,22 @@ impl Tracee {
             info_bag.options_already_set = true;
         }
 
-        let default_options = Options::PTRACE_O_TRACESYSGOOD
+        ptrace::setoptions(self.pid, Tracee::ptrace_options())
+            .context("Failed to set ptrace options")
+    }
+
+    /// The ptrace options of all the tracees, which are inherited by the
+    /// children they create.
+    pub fn ptrace_options() -> Options {
+        //TODO: seccomp
+        Options::PTRACE_O_TRACESYSGOOD
             | Options::PTRACE_O_TRACEFORK
             | Options::PTRACE_O_TRACEVFORK
             | Options::PTRACE_O_TRACEVFORKDONE
             | Options::PTRACE_O_TRACEEXEC
             | Options::PTRACE_O_TRACECLONE
             | Options::PTRACE_O_EXITKILL
-            | Options::PTRACE_O_TRACEEXIT;
-
-        //TODO: seccomp
-        ptrace::setoptions(self.pid, default_options).context("Failed to set ptrace options")
+            | Options::PTRACE_O_TRACEEXIT
     }
 
     /// Return the byte size of a Word in tracee
@@ -198,6 +255,10 @@ impl Tracee {
     /// Get file path from file descriptor,
     ///
     /// The returned path is always canonical.
+    ///
+    /// The fds are not tracked by proot-rs: the link `/proc/<pid>/fd/<fd>` is
+    /// read at each call, so the fds closed by the kernel (e.g. the
+    /// `O_CLOEXEC` ones at `execve()`) never leave a stale path behind.
     pub fn get_path_from_fd(&self, fd: RawFd, side: Side) -> Result<PathBuf> {
         #[cfg(any(target_os = "linux", target_os = "android"))]
         {
@@ -251,6 +312,18 @@ impl Tracee {
     /// relationship similar to `openat()` and `open()`, except that it accepts
     /// a `dirfd` argument.
     ///
+    /// A relative `guest_path` is appended to the guest path of `dirfd`, never
+    /// to its host path: the result is then canonicalized on the guest side
+    /// like any absolute path, so that e.g. `..` from a directory inside a
+    /// binding leads to the parent of the binding in the guest.
+    ///
+    /// The magic paths which refer to a file descriptor of the tracee are also
+    /// handled here, see `Tracee::translate_magic_fd_path()`, as well as the
+    /// emulated devices, see `Tracee::translate_emulated_dev()`. If the emulation
+    /// of `/proc` is disabled, paths in `/proc` are only substituted instead,
+    /// so that the magic links are resolved by the kernel as seen by the
+    /// tracee, see `Tracee::translate_raw_proc_path()`.
+    ///
     /// For the definition of the return value, please refer to
     /// [`Translator::translate_absolute_path()`]
     pub fn translate_path_at<P: AsRef<Path>>(
@@ -259,19 +332,364 @@ impl Tracee {
         guest_path: P,
         deref_final: bool,
     ) -> Result<(PathBuf, PathBuf)> {
-        if guest_path.as_ref().is_relative() {
+        let absolute_guest_path = if guest_path.as_ref().is_relative() {
             let mut dir_path = self.get_path_from_fd(dirfd, Side::Guest)?;
             // Check if guest_path is empty to avoid side effects of .push()
             if !guest_path.as_ref().is_empty() {
                 dir_path.push(guest_path);
             }
-            self.fs
-                .borrow()
-                .translate_absolute_path(dir_path, deref_final)
+            dir_path
+        } else {
+            guest_path.as_ref().to_path_buf()
+        };
+        if self.dev_emulation && uses_emulated_devices(self.regs.get_sys_num(Original)) {
+            if let Some(paths) = self.translate_emulated_dev(&absolute_guest_path, deref_final) {
+                return Ok(paths);
+            }
+        }
+        if self.proc_emulation {
+            if let Some(paths) = self.translate_magic_fd_path(&absolute_guest_path, deref_final)? {
+                return Ok(paths);
+            }
+            if let Some(paths) =
+                self.translate_magic_proc_link(&absolute_guest_path, deref_final)?
+            {
+                return Ok(paths);
+            }
+        } else if let Some(paths) = self.translate_raw_proc_path(&absolute_guest_path)? {
+            return Ok(paths);
+        }
+        self.fs
+            .borrow()
+            .translate_absolute_path(absolute_guest_path, deref_final)
+    }
+
+    /// Returns whether the path of a syscall of the `*at()` family must be
+    /// left as is to the kernel, instead of being translated relative to
+    /// `dirfd`: an empty path refers to `dirfd` itself when `empty_path` is
+    /// set (i.e. with `AT_EMPTY_PATH`), and is rejected by the kernel with
+    /// `ENOENT` otherwise.
+    ///
+    /// The file of `dirfd` was opened through a translated path, or has no
+    /// path at all (e.g. an `O_PATH` fd of a symlink, of an unlinked file, or
+    /// a pipe), so the kernel resolves it correctly. `AT_FDCWD` is the
+    /// exception: the cwd of the guest is not the one of the tracee on the
+    /// host, so it is translated.
+    pub fn is_empty_path_of_fd(dirfd: RawFd, raw_path: &Path, empty_path: bool) -> bool {
+        raw_path.as_os_str().is_empty() && !(empty_path && dirfd == libc::AT_FDCWD)
+    }
+
+    /// Same as `Translator::translate_paths()`, but each path is translated
+    /// with `Tracee::translate_path_at()` using its own `dirfd`.
+    pub fn translate_paths_at<P: AsRef<Path>>(
+        &self,
+        guest_paths: &[(RawFd, P, bool)],
+    ) -> Result<Vec<(PathBuf, PathBuf)>> {
+        guest_paths
+            .iter()
+            .enumerate()
+            .map(|(index, (dirfd, guest_path, deref_final))| {
+                self.translate_path_at(*dirfd, guest_path, *deref_final)
+                    .with_context(|| {
+                        format!(
+                            "Failed to translate path #{} {:?}",
+                            index,
+                            guest_path.as_ref()
+                        )
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns whether `fd` refers to a file which has no name in the
+    /// file-system, i.e. its path, as reported by `/proc/<pid>/fd/<fd>`, does
+    /// not exist.
+    fn is_fd_unlinked(&self, fd: RawFd) -> bool {
+        match self.get_path_from_fd(fd, Side::Host) {
+            Ok(host_path) => host_path.symlink_metadata().is_err(),
+            Err(_) => false,
+        }
+    }
+
+    /// Translates a path in `/proc` without canonicalizing it, since the
+    /// symlinks in `/proc/self` would otherwise be resolved in the context of
+    /// proot-rs. Only used when the emulation of `/proc` is disabled.
+    ///
+    /// Paths containing `..` are still canonicalized, so that they cannot go
+    /// out of the binding of `/proc`.
+    ///
+    /// Returns `Ok(None)` if `guest_path` is not such a path.
+    pub fn translate_raw_proc_path(&self, guest_path: &Path) -> Result<Option<(PathBuf, PathBuf)>> {
+        if !guest_path.starts_with("/proc")
+            || guest_path
+                .components()
+                .any(|comp| comp == Component::ParentDir)
+        {
+            return Ok(None);
+        }
+        let host_path = self.fs.borrow().substitute(guest_path, Side::Guest)?;
+        Ok(Some((guest_path.to_path_buf(), host_path)))
+    }
+
+    /// Translates the paths of `EMULATED_DEVICES` to the devices of the host,
+    /// whatever the rootfs contains (e.g. a minimal rootfs has no device
+    /// nodes, or even no `/dev`), so that reading `/dev/null` returns EOF,
+    /// reading `/dev/zero` returns zeros and writing to `/dev/full` fails with
+    /// `ENOSPC`, as usual.
+    ///
+    /// `guest_path` is matched once canonicalized, e.g. `/dev/../dev/null` or a
+    /// symlink to `/dev/zero` are emulated too. If it cannot be canonicalized
+    /// (e.g. without `/dev` in the rootfs), it is matched as is, unless it
+    /// contains `..`.
+    ///
+    /// Returns `None` if `guest_path` is not such a path.
+    pub fn translate_emulated_dev(
+        &self,
+        guest_path: &Path,
+        deref_final: bool,
+    ) -> Option<(PathBuf, PathBuf)> {
+        let canonical_guest_path = match self.fs.borrow().canonicalize(guest_path, deref_final) {
+            Ok(canonical_guest_path) => canonical_guest_path,
+            Err(_)
+                if !guest_path
+                    .components()
+                    .any(|comp| comp == Component::ParentDir) =>
+            {
+                guest_path.to_path_buf()
+            }
+            Err(_) => return None,
+        };
+        EMULATED_DEVICES
+            .iter()
+            .map(Path::new)
+            .find(|device| canonical_guest_path == *device)
+            .map(|device| (device.to_path_buf(), device.to_path_buf()))
+    }
+
+    /// Returns the guest target of the link `name` of `/proc/<pid>` for this
+    /// tracee, for the links which are emulated: `exe` (the program run by
+    /// the last `execve()`) and `cwd` (the cwd of the guest, which is never
+    /// changed on the host).
+    pub fn get_proc_link_target(&self, name: &OsStr) -> Option<PathBuf> {
+        match name.to_str()? {
+            "exe" => {
+                let exe = self.exe.as_ref()?;
+                let host_exe = exe.borrow();
+                match self.fs.borrow().detranslate_path(&*host_exe, None) {
+                    Ok(Some(guest_exe)) => Some(guest_exe),
+                    // The path of the executable is the same in the guest.
+                    Ok(None) => Some(host_exe.clone()),
+                    Err(_) => None,
+                }
+            }
+            "cwd" => Some(self.fs.borrow().get_cwd().to_path_buf()),
+            _ => None,
+        }
+    }
+
+    /// Translates the paths of the links of `/proc/self` emulated by
+    /// `Tracee::get_proc_link_target()`, and of the paths under them, e.g.
+    /// `/proc/self/exe` or `/proc/<pid>/cwd/file`.
+    ///
+    /// Like the magic fd paths, the link itself is not dereferenced (e.g. for
+    /// `readlink()`) on the host path `/proc/<pid>/<name>`, whose target is
+    /// then replaced by `readlink_at::exit()`.
+    ///
+    /// Returns `Ok(None)` if `guest_path` is not such a path.
+    pub fn translate_magic_proc_link(
+        &self,
+        guest_path: &Path,
+        deref_final: bool,
+    ) -> Result<Option<(PathBuf, PathBuf)>> {
+        let pid = self.pid.to_string();
+        let mut components = guest_path
+            .components()
+            .filter(|comp| *comp != Component::CurDir)
+            .map(|comp| comp.as_os_str().to_owned());
+        let name = match (
+            components.next(),
+            components.next(),
+            components.next(),
+            components.next(),
+        ) {
+            (Some(root), Some(proc), Some(who), Some(name))
+                if root == "/"
+                    && proc == "proc"
+                    && (who == "self" || who == "thread-self" || who == pid.as_str()) =>
+            {
+                name
+            }
+            _ => return Ok(None),
+        };
+        let target = match self.get_proc_link_target(&name) {
+            Some(target) => target,
+            None => return Ok(None),
+        };
+        let remaining: PathBuf = components.collect();
+
+        if !deref_final && remaining.as_os_str().is_empty() {
+            let host_path = Path::new("/proc").join(&pid).join(&name);
+            return Ok(Some((guest_path.to_path_buf(), host_path)));
+        }
+        let mut guest_target = target;
+        if !remaining.as_os_str().is_empty() {
+            guest_target.push(remaining);
+        }
+        self.fs
+            .borrow()
+            .translate_absolute_path(guest_target, deref_final)
+            .map(Some)
+    }
+
+    /// Translates the "magic" paths which refer to a file descriptor of the
+    /// tracee: `/dev/fd/N`, `/proc/self/fd/N`, `/proc/thread-self/fd/N` and
+    /// `/proc/<pid>/fd/N`.
+    ///
+    /// The symlinks of the guest `/dev` to these magic links (e.g.
+    /// `/dev/stdout` to `/proc/self/fd/1`) are handled the same way once
+    /// dereferenced, see `translate_dev_fd_link()`.
+    ///
+    /// These paths cannot go through the usual canonicalization, since the
+    /// symlinks in `/proc/self` would be resolved in the context of proot-rs
+    /// rather than the tracee. Instead, the guest path of the fd `N` is used,
+    /// so that the guest view is preserved. Fds which are not associated with
+    /// a path (e.g. pipes or sockets) are left to the kernel, using the host
+    /// path `/proc/<pid>/fd/N`.
+    ///
+    /// Returns `Ok(None)` if `guest_path` is not such a path.
+    pub fn translate_magic_fd_path(
+        &self,
+        guest_path: &Path,
+        deref_final: bool,
+    ) -> Result<Option<(PathBuf, PathBuf)>> {
+        let mut components = guest_path
+            .components()
+            .filter(|comp| *comp != Component::CurDir)
+            .map(|comp| comp.as_os_str().to_owned());
+        let pid = self.pid.to_string();
+        let mut next = || components.next();
+        let prefix_matched = match (next(), next(), next()) {
+            (Some(root), Some(dev), Some(fd)) if root == "/" && dev == "dev" && fd == "fd" => true,
+            (Some(root), Some(dev), Some(name))
+                if root == "/" && dev == "dev" && DEV_FD_LINKS.iter().any(|link| name == *link) =>
+            {
+                let remaining: PathBuf = components.collect();
+                return self.translate_dev_fd_link(
+                    &Path::new("/dev").join(name),
+                    remaining,
+                    deref_final,
+                );
+            }
+            (Some(root), Some(proc), Some(who)) if root == "/" && proc == "proc" => {
+                (who == "self" || who == "thread-self" || who == pid.as_str())
+                    && next().map_or(false, |fd| fd == "fd")
+            }
+            _ => false,
+        };
+        if !prefix_matched {
+            return Ok(None);
+        }
+        let fd = match next()
+            .and_then(|fd| fd.to_str().map(str::to_owned))
+            .and_then(|fd| fd.parse::<RawFd>().ok())
+        {
+            Some(fd) => fd,
+            None => return Ok(None),
+        };
+        let remaining: PathBuf = components.collect();
+
+        // The magic link itself is not dereferenced, or the fd has no path.
+        let host_path = PathBuf::from(format!("/proc/{}/fd/{}", self.pid, fd)).join(&remaining);
+        if !deref_final && remaining.as_os_str().is_empty() {
+            return Ok(Some((host_path.clone(), host_path)));
+        }
+        // The file of the fd has no name anymore (e.g. it was opened with
+        // `O_TMPFILE`, or it was unlinked), so it can only be reached through
+        // the magic link, which is then dereferenced by the kernel (e.g.
+        // `linkat()` with `AT_SYMLINK_FOLLOW`).
+        if remaining.as_os_str().is_empty() && self.is_fd_unlinked(fd) {
+            return Ok(Some((host_path.clone(), host_path)));
+        }
+        match self.get_path_from_fd(fd, Side::Guest) {
+            Ok(mut fd_guest_path) => {
+                if !remaining.as_os_str().is_empty() {
+                    fd_guest_path.push(remaining);
+                }
+                self.fs
+                    .borrow()
+                    .translate_absolute_path(fd_guest_path, deref_final)
+                    .map(Some)
+            }
+            // The guest path of the fd is unknown (e.g. a pipe, or a directory
+            // out of the guest inherited from proot-rs): the magic link itself
+            // is still usable, but nothing is looked up under it, since that
+            // would give access to the host.
+            Err(_) if remaining.as_os_str().is_empty() => Ok(Some((host_path.clone(), host_path))),
+            Err(err) => {
+                let fd_path = format!("/proc/{}/fd/{}", self.pid, fd);
+                let errno = match std::fs::metadata(fd_path) {
+                    Ok(metadata) if metadata.is_dir() => Errno::EACCES,
+                    Ok(_) => Errno::ENOTDIR,
+                    Err(_) => Errno::ENOENT,
+                };
+                Err(Error::errno_with_msg(
+                    errno,
+                    format!(
+                        "Failed to look up {:?} under the fd {}: {}",
+                        remaining, fd, err
+                    ),
+                ))
+            }
+        }
+    }
+
+    /// Translates `guest_link` (one of the `DEV_FD_LINKS` of the guest `/dev`,
+    /// e.g. `/dev/stdout`) followed by `remaining`, if it is a symlink to one
+    /// of the magic links of `translate_magic_fd_path()`, as the ones of a
+    /// usual `/dev` are: its target is then translated in the context of the
+    /// tracee, with the fd it names. The symlink itself (i.e. `deref_final` is
+    /// false and `remaining` is empty) is left to the usual translation.
+    ///
+    /// Returns `Ok(None)` if `guest_link` is not such a symlink.
+    fn translate_dev_fd_link(
+        &self,
+        guest_link: &Path,
+        remaining: PathBuf,
+        deref_final: bool,
+    ) -> Result<Option<(PathBuf, PathBuf)>> {
+        if !deref_final && remaining.as_os_str().is_empty() {
+            return Ok(None);
+        }
+        let host_link = match self.fs.borrow().translate_absolute_path(guest_link, false) {
+            Ok((_, host_link)) => host_link,
+            Err(_) => return Ok(None),
+        };
+        // A relative target is relative to `/dev`, and its `..` are resolved
+        // before matching it (e.g. `../proc/self/fd/1`).
+        let target = match std::fs::read_link(host_link) {
+            Ok(target) => Path::new("/dev").join(target).components().fold(
+                PathBuf::new(),
+                |mut target, comp| {
+                    match comp {
+                        Component::ParentDir => {
+                            target.pop();
+                        }
+                        Component::CurDir => {}
+                        comp => target.push(comp),
+                    }
+                    target
+                },
+            ),
+            Err(_) => return Ok(None),
+        };
+        // Only the magic links are followed here, so that this cannot loop.
+        if !target.starts_with("/proc") && !target.starts_with("/dev/fd") {
+            return Ok(None);
+        }
+        if remaining.as_os_str().is_empty() {
+            self.translate_magic_fd_path(&target, deref_final)
         } else {
-            self.fs
-                .borrow()
-                .translate_absolute_path(guest_path, deref_final)
+            self.translate_magic_fd_path(&target.join(remaining), deref_final)
         }
     }
 }
@@ -279,15 +697,16 @@ impl Tracee {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::register::Current;
     use crate::register::SysResult;
-    use crate::register::{Current, Original};
-    use crate::utils::tests::fork_test;
+    use crate::utils::tests::{fork_test, test_with_proot_and_info_bag};
     use crate::{filesystem::FileSystem, utils::tests::get_test_rootfs_path};
     use nix::fcntl;
     use nix::fcntl::OFlag;
     use nix::sys::stat::Mode;
     use nix::unistd;
     use nix::unistd::Pid;
+    use std::os::unix::fs::FileTypeExt;
 
     #[test]
     fn create_tracee() {
@@ -411,4 +830,41 @@ mod tests {
             },
         )
     }
+
+    #[test]
+    fn test_translate_emulated_dev() {
+        test_with_proot_and_info_bag(
+            |info_bag| info_bag.dev_emulation = true,
+            |_tracee, _is_sysenter, _before_translation| {},
+            || {
+                let open = |path: &str, flags: OFlag| fcntl::open(path, flags, Mode::empty());
+
+                let fd = open("/dev/zero", OFlag::O_RDONLY).unwrap();
+                let mut buf = [0xffu8; 16];
+                assert_eq!(unistd::read(fd, &mut buf), Ok(buf.len()));
+                assert_eq!(buf, [0u8; 16]);
+                unistd::close(fd).unwrap();
+
+                let fd = open("/dev/null", OFlag::O_RDWR).unwrap();
+                assert_eq!(unistd::write(fd, b"discarded"), Ok(9));
+                assert_eq!(unistd::read(fd, &mut buf), Ok(0));
+                unistd::close(fd).unwrap();
+
+                let fd = open("/dev/full", OFlag::O_WRONLY).unwrap();
+                assert_eq!(unistd::write(fd, b"full"), Err(Errno::ENOSPC));
+                unistd::close(fd).unwrap();
+
+                // Once canonicalized.
+                let fd = open("/dev/./../dev//zero", OFlag::O_RDONLY).unwrap();
+                assert_eq!(unistd::read(fd, &mut buf), Ok(buf.len()));
+                unistd::close(fd).unwrap();
+                let metadata = std::fs::metadata("/tmp/../dev/null").unwrap();
+                assert!(metadata.file_type().is_char_device());
+                assert_eq!(
+                    unistd::access("/dev/null", unistd::AccessFlags::W_OK),
+                    Ok(())
+                );
+            },
+        )
+    }
 }
@@ -1,5 +1,5 @@
 use crate::kernel::syscall;
-use crate::kernel::{enter, exit};
+use crate::kernel::{enter, exit, fake_root};
 use crate::process::proot::InfoBag;
 use crate::process::tracee::{Tracee, TraceeRestartMethod, TraceeStatus};
 use crate::register::{Current, Modified, Original, StackPointer, SysResult, Word};
@@ -11,7 +11,7 @@ pub trait SyscallTranslator {
         #[cfg(test)] func_syscall_hook: &Option<Box<dyn Fn(&Tracee, bool, bool)>>,
     );
     fn translate_syscall_enter(&mut self, info_bag: &InfoBag);
-    fn translate_syscall_exit(&mut self);
+    fn translate_syscall_exit(&mut self, info_bag: &InfoBag);
 }
 
 impl SyscallTranslator for Tracee {
@@ -42,7 +42,7 @@ impl SyscallTranslator for Tracee {
                 func_syscall_hook
                     .as_ref()
                     .map(|func| func(self, false, true));
-                self.translate_syscall_exit();
+                self.translate_syscall_exit(info_bag);
                 false
             }
         };
@@ -57,9 +57,9 @@ impl SyscallTranslator for Tracee {
             .map(|func| func(self, is_sysenter, false));
 
         if is_sysenter {
-            syscall::print_syscall(self, Current, "sysenter end");
+            syscall::print_syscall(self, info_bag, Current, "sysenter end");
         } else {
-            syscall::print_syscall(self, Current, "sysexit end");
+            syscall::print_syscall(self, info_bag, Current, "sysexit end");
         }
     }
 
@@ -73,7 +73,7 @@ impl SyscallTranslator for Tracee {
         // the syscall number, in case this one is changed during the enter stage).
         self.regs.save_current_regs(Original);
 
-        syscall::print_syscall(self, Current, "sysenter start");
+        syscall::print_syscall(self, info_bag, Current, "sysenter start");
 
         //TODO: notify extensions for SYSCALL_ENTER_START
         // status = notify_extensions(tracee, SYSCALL_ENTER_START, 0, 0);
@@ -82,7 +82,16 @@ impl SyscallTranslator for Tracee {
         // if (status > 0)
         //     return 0;
 
-        let status = enter::translate(info_bag, self);
+        let status = enter::translate(info_bag, self).and_then(|()| match &info_bag.fake_root {
+            Some(fake_root) => fake_root::enter(self, fake_root),
+            None => Ok(()),
+        });
+
+        if let Some(stats) = &info_bag.stats {
+            stats
+                .borrow_mut()
+                .record_syscall(self.regs.get_sys_num(Original), status.is_ok());
+        }
 
         //TODO: notify extensions for SYSCALL_ENTER_END event
         // status2 = notify_extensions(tracee, SYSCALL_ENTER_END, status, 0);
@@ -122,11 +131,11 @@ impl SyscallTranslator for Tracee {
         }
     }
 
-    fn translate_syscall_exit(&mut self) {
+    fn translate_syscall_exit(&mut self, info_bag: &InfoBag) {
         // By default, restore original register values at the end of this stage.
         self.regs.set_restore_original_regs(true);
 
-        syscall::print_syscall(self, Current, "sysexit start");
+        syscall::print_syscall(self, info_bag, Current, "sysexit start");
 
         //TODO: notify extensions for SYSCALL_EXIT_START event
         // status = notify_extensions(tracee, SYSCALL_EXIT_START, 0, 0);
@@ -138,7 +147,10 @@ impl SyscallTranslator for Tracee {
         //     return;
 
         if self.status.is_ok() {
-            exit::translate(self);
+            exit::translate(info_bag, self);
+            if let Some(fake_root) = &info_bag.fake_root {
+                fake_root::exit(self, fake_root);
+            }
         } else {
             self.regs.set(
                 SysResult,
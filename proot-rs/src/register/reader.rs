@@ -20,6 +20,7 @@ pub fn convert_word_to_bytes(value_to_convert: Word) -> [u8; 8] {
 
 pub trait PtraceReader {
     fn get_sysarg_path(&self, sys_arg: SysArgIndex) -> Result<PathBuf>;
+    fn read_data(&self, src_tracee: *const c_void, size: usize) -> Result<Vec<u8>>;
 }
 
 impl PtraceReader for Registers {
@@ -52,6 +53,29 @@ impl PtraceReader for Registers {
             path
         }
     }
+
+    /// Copy `size` bytes from tracee's memory space at address `src_tracee`
+    /// by ptrace(PTRACE_PEEKDATA). This is the counterpart of
+    /// `PtraceWriter::write_data()`.
+    fn read_data(&self, src_tracee: *const c_void, size: usize) -> Result<Vec<u8>> {
+        //TODO: belongs_to_heap_prealloc
+        // if (belongs_to_heap_prealloc(tracee, src_tracee))
+        //	return -EFAULT;
+
+        let word_size = size_of::<Word>();
+        let nb_words = (size + word_size - 1) / word_size;
+        let mut bytes: Vec<u8> = Vec::with_capacity(nb_words * word_size);
+
+        for i in 0..nb_words as isize {
+            let src_addr = unsafe { (src_tracee as *const Word).offset(i) as *mut c_void };
+            let word = ptrace::read(self.get_pid(), src_addr).errno(EFAULT)? as Word;
+            bytes.extend_from_slice(&convert_word_to_bytes(word));
+        }
+        // The last word may contain bytes beyond the requested area.
+        bytes.truncate(size);
+
+        Ok(bytes)
+    }
 }
 
 /// Intermediary function that retrieves bytes from the tracee's memory space
@@ -153,6 +153,13 @@ const PTRACE_GETREGSET: usize = 0x4204;
 #[cfg(any(target_arch = "aarch64"))]
 const PTRACE_SETREGSET: usize = 0x4205;
 
+/// Name of the ptrace request used to read the registers, for the error
+/// messages.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "arm"))]
+const GET_REGS_REQUEST_NAME: &str = "PTRACE_GETREGS";
+#[cfg(any(target_arch = "aarch64"))]
+const GET_REGS_REQUEST_NAME: &str = "PTRACE_GETREGSET";
+
 impl RegisterSet {
     fn get_from_tracee(pid: Pid) -> Result<Self> {
         #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "arm"))]
@@ -249,6 +256,7 @@ pub enum Register {
     SysArg(SysArgIndex),
     SysResult,
     StackPointer,
+    InstrPointer,
 }
 use self::Register::*;
 
@@ -300,6 +308,30 @@ impl Registers {
         }
     }
 
+    /// Checks that the registers of the stopped tracee `pid` can be read.
+    ///
+    /// Some kernels and containers restrict ptrace() beyond attaching to a
+    /// process (e.g. a seccomp policy on Android, where Termux runs without
+    /// root), in which case proot-rs cannot work at all. This is checked once
+    /// at startup, so that the user gets an explanation instead of an error
+    /// at the first syscall of the guest.
+    pub fn check_access(pid: Pid) -> Result<()> {
+        RegisterSet::get_from_tracee(pid)
+            .map(drop)
+            .map_err(|error| {
+                let errno = error.get_errno();
+                Error::errno_with_msg(
+                    errno,
+                    format!(
+                        "Failed to read the registers of the tracee {} with {} ({}): ptrace() is \
+                     restricted by this kernel or container (e.g. by a seccomp or SELinux \
+                     policy), and proot-rs cannot run without it",
+                        pid, GET_REGS_REQUEST_NAME, errno
+                    ),
+                )
+            })
+    }
+
     #[cfg(test)]
     /// Same, but with the initial regs. Useful for tests.
     pub fn from(pid: Pid, raw_regs: RegisterSet) -> Self {
@@ -465,6 +497,7 @@ impl Registers {
             SysArg(SysArg6) => get_reg!(raw_regs, SysArg6),
             SysResult => get_reg!(raw_regs, SysResult),
             StackPointer => get_reg!(raw_regs, StackPointer),
+            InstrPointer => get_reg!(raw_regs, InstrPointer),
         } as _;
     }
 
@@ -492,6 +525,7 @@ impl Registers {
             SysArg(SysArg6) => get_reg!(raw_regs, SysArg6) = new_value as _,
             SysResult => get_reg!(raw_regs, SysResult) = new_value as _,
             StackPointer => get_reg!(raw_regs, StackPointer) = new_value as _,
+            InstrPointer => get_reg!(raw_regs, InstrPointer) = new_value as _,
         };
     }
 
@@ -567,6 +601,36 @@ impl Registers {
         self.set(SysNum, VOID, justification);
     }
 
+    /// Makes the tracee run its syscall again, with its original arguments,
+    /// once restarted from the exit stage: the instruction pointer is rewound
+    /// to the syscall instruction, and the register overwritten by the result
+    /// (the syscall number on x86, the first argument on arm) is given back.
+    ///
+    /// Requires the original registers to be restored at the end of the exit
+    /// stage (i.e. the default).
+    pub fn restart_syscall(&mut self, justification: &'static str) {
+        // `syscall`, and `int 0x80` (also placed after the `sysenter` of the
+        // vDSO for this purpose).
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        let (instr_size, overwritten) = (2, self.get(Original, SysNum));
+        // `svc`, in the Thumb state if the T bit of the CPSR is set.
+        #[cfg(any(target_arch = "arm"))]
+        let (instr_size, overwritten) = (
+            if self.get_regs(Current).0[16] & 0x20 != 0 {
+                2
+            } else {
+                4
+            },
+            self.get(Original, SysArg(SysArg1)),
+        );
+        #[cfg(any(target_arch = "aarch64"))]
+        let (instr_size, overwritten) = (4, self.get(Original, SysArg(SysArg1)));
+
+        let instr_pointer = self.get(Current, InstrPointer);
+        self.set(InstrPointer, instr_pointer - instr_size, justification);
+        self.set(SysResult, overwritten, justification);
+    }
+
     #[inline]
     pub fn set_restore_original_regs(&mut self, restore_original_regs: bool) {
         self.restore_original_regs = restore_original_regs;
@@ -641,6 +705,17 @@ mod tests {
         assert!(regs.fetch_regs().is_err());
     }
 
+    #[test]
+    fn test_check_access_explains_failure() {
+        // The registers of a process which is not traced cannot be read, as
+        // when ptrace() is restricted.
+        let error = Registers::check_access(Pid::from_raw(-1)).unwrap_err();
+        assert_eq!(error, Error::errno(ESRCH));
+        let message = error.to_string();
+        assert!(message.contains(GET_REGS_REQUEST_NAME), "{}", message);
+        assert!(message.contains("ptrace() is restricted"), "{}", message);
+    }
+
     #[test]
     fn test_fetch_regs_test() {
         let rootfs_path = get_test_rootfs_path();
@@ -44,7 +44,7 @@ pub mod tests {
     /// Since each rust unit tests is executed in a different thread, we
     /// should fork a child process to test the proot, otherwise the
     /// calls to `waitpid(-1)` from different unit tests may affect each other
-    fn test_in_subprocess<F: FnOnce()>(func: F) {
+    pub fn test_in_subprocess<F: FnOnce()>(func: F) {
         let pid = unsafe { fork() };
         match pid {
             Ok(ForkResult::Child) => {
@@ -193,6 +193,70 @@ pub mod tests {
     >(
         func_syscall_hook: FuncSyscallHook,
         func_tracee: FuncTracee,
+    ) {
+        test_with_proot_and_info_bag(|_info_bag| {}, func_syscall_hook, func_tracee)
+    }
+
+    /// Same as `test_with_proot()`, but `func_info_bag` is called first to
+    /// change the configuration of `proot-rs` (e.g. to simulate a command line
+    /// option).
+    pub fn test_with_proot_and_info_bag<
+        FuncInfoBag: FnOnce(&mut InfoBag),
+        FuncSyscallHook: Fn(&Tracee, bool, bool) + 'static,
+        FuncTracee: FnOnce(),
+    >(
+        func_info_bag: FuncInfoBag,
+        func_syscall_hook: FuncSyscallHook,
+        func_tracee: FuncTracee,
+    ) {
+        run_test_with_proot(|_fs| {}, func_info_bag, func_syscall_hook, func_tracee, 0)
+    }
+
+    /// Same as `test_with_proot()`, but `func_fs` is called first to change
+    /// the `FileSystem` of the tracee (e.g. to add bindings).
+    pub fn test_with_proot_and_fs<
+        FuncFs: FnOnce(&mut FileSystem),
+        FuncSyscallHook: Fn(&Tracee, bool, bool) + 'static,
+        FuncTracee: FnOnce(),
+    >(
+        func_fs: FuncFs,
+        func_syscall_hook: FuncSyscallHook,
+        func_tracee: FuncTracee,
+    ) {
+        run_test_with_proot(func_fs, |_info_bag| {}, func_syscall_hook, func_tracee, 0)
+    }
+
+    /// Same as `test_with_proot()`, but the init process is expected to exit
+    /// with `expected_exit_code` instead of 0 (e.g. if `func_tracee` never
+    /// returns).
+    pub fn test_with_proot_and_exit_code<
+        FuncSyscallHook: Fn(&Tracee, bool, bool) + 'static,
+        FuncTracee: FnOnce(),
+    >(
+        expected_exit_code: i32,
+        func_syscall_hook: FuncSyscallHook,
+        func_tracee: FuncTracee,
+    ) {
+        run_test_with_proot(
+            |_fs| {},
+            |_info_bag| {},
+            func_syscall_hook,
+            func_tracee,
+            expected_exit_code,
+        )
+    }
+
+    fn run_test_with_proot<
+        FuncFs: FnOnce(&mut FileSystem),
+        FuncInfoBag: FnOnce(&mut InfoBag),
+        FuncSyscallHook: Fn(&Tracee, bool, bool) + 'static,
+        FuncTracee: FnOnce(),
+    >(
+        func_fs: FuncFs,
+        func_info_bag: FuncInfoBag,
+        func_syscall_hook: FuncSyscallHook,
+        func_tracee: FuncTracee,
+        expected_exit_code: i32,
     ) {
         test_in_subprocess(|| {
             let func = || -> Result<()> {
@@ -200,12 +264,15 @@ pub mod tests {
                 let root_path = get_test_rootfs_path();
                 let mut fs = FileSystem::with_root(root_path)?;
                 fs.set_cwd("/")?;
+                func_fs(&mut fs);
                 let mut proot: PRoot = PRoot::new();
                 proot.init()?;
+                func_info_bag(&mut proot.info_bag);
                 proot.func_syscall_hook = Some(Box::new(func_syscall_hook));
                 // fork first child process as tracee
                 match unsafe { unistd::fork() }.context("Failed to fork() when starting process")? {
                     ForkResult::Parent { child } => {
+                        PRoot::seize_first_tracee(child)?;
                         proot.create_tracee(
                             child,
                             Rc::new(RefCell::new(fs)),
@@ -215,9 +282,6 @@ pub mod tests {
                     }
                     ForkResult::Child => {
                         let init_child_func = || -> Result<()> {
-                            ptrace::traceme().context(
-                                "Failed to execute ptrace::traceme() in a child process",
-                            )?;
                             signal::kill(unistd::getpid(), Signal::SIGSTOP).context(
                                 "Child process failed to synchronize with parent process",
                             )?;
@@ -237,7 +301,7 @@ pub mod tests {
 
                 assert_eq!(
                     proot.init_exit_code,
-                    Some(0),
+                    Some(expected_exit_code),
                     "tracee exited with a bad exit code: {:?}",
                     proot.init_exit_code
                 );
@@ -247,6 +311,53 @@ pub mod tests {
         })
     }
 
+    /// Returns a static executable for x86_64, entering at `entry`, with a
+    /// readable and executable `PT_LOAD` segment for each `(vaddr, filesz,
+    /// memsz)` of `segments`, mapped from the start of the file.
+    pub fn x86_64_executable(entry: u64, segments: &[(u64, u64, u64)]) -> Vec<u8> {
+        let mut elf = vec![0x7f, b'E', b'L', b'F', 2, 1, 1];
+        elf.resize(16, 0);
+        elf.extend_from_slice(&2u16.to_le_bytes()); // e_type: ET_EXEC
+        elf.extend_from_slice(&62u16.to_le_bytes()); // e_machine: EM_X86_64
+        elf.extend_from_slice(&1u32.to_le_bytes()); // e_version
+        elf.extend_from_slice(&entry.to_le_bytes()); // e_entry
+        elf.extend_from_slice(&64u64.to_le_bytes()); // e_phoff
+        elf.extend_from_slice(&0u64.to_le_bytes()); // e_shoff
+        elf.extend_from_slice(&0u32.to_le_bytes()); // e_flags
+        for half in &[64u16, 56, segments.len() as u16, 64, 0, 0] {
+            // e_ehsize, e_phentsize, e_phnum, e_shentsize, e_shnum, e_shstrndx
+            elf.extend_from_slice(&half.to_le_bytes());
+        }
+        for &(vaddr, filesz, memsz) in segments {
+            elf.extend_from_slice(&1u32.to_le_bytes()); // p_type: PT_LOAD
+            elf.extend_from_slice(&5u32.to_le_bytes()); // p_flags: PF_R | PF_X
+            for word in &[0u64, vaddr, vaddr, filesz, memsz, 0x1000] {
+                // p_offset, p_vaddr, p_paddr, p_filesz, p_memsz, p_align
+                elf.extend_from_slice(&word.to_le_bytes());
+            }
+        }
+        elf
+    }
+
+    /// Returns a static executable for x86 (i.e. of the 32-bit class), entering
+    /// at `entry`, with no program headers.
+    pub fn x86_executable(entry: u32) -> Vec<u8> {
+        let mut elf = vec![0x7f, b'E', b'L', b'F', 1, 1, 1];
+        elf.resize(16, 0);
+        elf.extend_from_slice(&2u16.to_le_bytes()); // e_type: ET_EXEC
+        elf.extend_from_slice(&3u16.to_le_bytes()); // e_machine: EM_386
+        elf.extend_from_slice(&1u32.to_le_bytes()); // e_version
+        elf.extend_from_slice(&entry.to_le_bytes()); // e_entry
+        elf.extend_from_slice(&52u32.to_le_bytes()); // e_phoff
+        elf.extend_from_slice(&0u32.to_le_bytes()); // e_shoff
+        elf.extend_from_slice(&0u32.to_le_bytes()); // e_flags
+        for half in &[52u16, 32, 0, 40, 0, 0] {
+            // e_ehsize, e_phentsize, e_phnum, e_shentsize, e_shnum, e_shstrndx
+            elf.extend_from_slice(&half.to_le_bytes());
+        }
+        elf
+    }
+
     /// Get the path to the new root fs for the unit test, which is specified by
     /// the environment variable `PROOT_TEST_ROOTFS`.
     pub fn get_test_rootfs_path() -> PathBuf {
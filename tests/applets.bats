@@ -258,6 +258,16 @@ function script_test_run_applets_common_tools {
 }
 
 
+@test "test proot-rs run date" {
+    # clock_gettime() goes through the vDSO of AT_SYSINFO_EHDR
+    local before="$(date +%s)"
+    runp proot-rs --rootfs "$ROOTFS" -- /bin/date +%s
+    [ "$status" -eq 0 ]
+    [ "$output" -ge "$before" ]
+    [ "$output" -le "$(date +%s)" ]
+}
+
+
 @test "test proot-rs run whoami" {
     check_if_command_exists whoami
 
@@ -432,3 +442,59 @@ function script_test_trailing_slash_in_symlink {
     [ "$status" -eq 0 ]
 
 }
+
+
+
+function script_test_paths_through_applets {
+    PATH=/usr/local/bin:/usr/bin:/bin
+
+    # relative paths
+    mkdir -p /tmp/test_paths_through_applets/dir/sub
+    cd /tmp/test_paths_through_applets
+    echo hello > dir/file
+    cp dir/file dir/sub/copy
+    cd dir/sub
+    [ "$(cat copy ../file)" = $'hello\nhello' ]
+    [ "$(ls ..)" = $'file\nsub' ]
+    rm copy ../file
+    [ "$(ls ..)" = "sub" ]
+
+    # relative and absolute symlinks of the rootfs
+    cd /tmp/test_paths_through_applets
+    echo target > t
+    ln -s t rel
+    ln -s /tmp/test_paths_through_applets/t abs
+    [ "$(cat rel abs)" = $'target\ntarget' ]
+    [ "$(readlink rel)" = "t" ]
+    [ "$(readlink abs)" = "/tmp/test_paths_through_applets/t" ]
+    [ "$(readlink -f rel)" = "/tmp/test_paths_through_applets/t" ]
+
+    # a binding, and an absolute symlink of the host inside it
+    [ "$(cat /home/hostfile)" = "from the host" ]
+    [ "$(ls /home)" = $'abs_link\nhostfile' ]
+    [ "$(readlink /home/abs_link)" = "/home/hostfile" ]
+    [ "$(cat /home/abs_link)" = "from the host" ]
+    cd /home
+    echo guest > guestfile
+    [ "$(cat ./guestfile ../home/hostfile)" = $'guest\nfrom the host' ]
+
+    # relative paths out of the binding
+    [ "$(ls ../tmp/test_paths_through_applets)" = $'abs\ndir\nrel\nt' ]
+    cd ..
+    [ "$(pwd)" = "/" ]
+    [ "$(cat home/../home/./hostfile)" = "from the host" ]
+}
+
+
+@test "test paths through applets with a binding" {
+    local test_dir="$ROOTFS/tmp/test_paths_through_applets"
+    local host_dir="$(mktemp -d)"
+    echo "from the host" > "$host_dir/hostfile"
+    ln -s "$host_dir/hostfile" "$host_dir/abs_link"
+
+    runp proot-rs --rootfs "$ROOTFS" --bind "$host_dir:/home" -- /bin/sh -e -x -c "$(declare -f script_test_paths_through_applets); script_test_paths_through_applets"
+    local guestfile="$(cat "$host_dir/guestfile")"
+    rm -rf "$test_dir" "$host_dir"
+    [ "$status" -eq 0 ]
+    [ "$guestfile" = "guest" ]
+}
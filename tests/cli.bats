@@ -30,3 +30,277 @@ load helper
     proot-rs -b "/etc:/home" -- "$(which stat)" /home/passwd
 }
 
+
+@test "test proot-rs options --benchmark" {
+    runp proot-rs --rootfs "$ROOTFS" --benchmark 100
+    [ "$status" -eq 0 ]
+    [[ "${lines[0]}" == "workload"* ]]
+    [[ "${lines[1]}" == "stat "* ]]
+    [[ "${lines[2]}" == "getcwd "* ]]
+    runp proot-rs --rootfs "$ROOTFS" --benchmark 100 --chroot-only
+    [ "$status" -eq 0 ]
+    [[ "${lines[1]}" == "stat "* ]]
+}
+
+@test "test proot-rs options --chroot-only" {
+    # the executable is still looked up in the rootfs
+    runp proot-rs --rootfs "$ROOTFS" --chroot-only -- /bin/sh -c 'echo chroot-only'
+    [ "$status" -eq 0 ]
+    [ "$output" == "chroot-only" ]
+    # the bindings would not be translated
+    runp proot-rs --rootfs "$ROOTFS" --chroot-only -b /etc:/home -- /bin/true
+    [ "$status" -eq 1 ]
+    runp proot-rs --rootfs "$ROOTFS" --chroot-only --mount-proc -- /bin/true
+    [ "$status" -eq 1 ]
+}
+
+@test "test proot-rs options --virtual-uptime" {
+    # busybox `uptime` gets the uptime from `sysinfo()`
+    runp proot-rs --rootfs "$ROOTFS" --virtual-uptime -- /bin/uptime
+    [ "$status" -eq 0 ]
+    [[ "$output" == *"up 0 min"* ]]
+}
+
+@test "test proot-rs options --no-proc-emulation" {
+    if [ ! -d "/proc" ]; then
+        skip "/proc not found in host"
+    fi
+    # the magic link is resolved by the kernel, so the host path of the
+    # executable (i.e. the loader of proot-rs) is reported.
+    runp proot-rs --no-proc-emulation -- /bin/readlink /proc/self/exe
+    [ "$status" -eq 0 ]
+    [[ "$output" == "${TMPDIR:-/tmp}/prooted-"* ]]
+}
+
+@test "test proot-rs options --exit-code-passthrough" {
+    # bash reports the commands killed by a signal (but not the ones which
+    # exited with 128+signal), unless they are the last command of the script.
+    local script='"$0" --rootfs "$1" $2 -- /bin/sh -c "kill -TERM \$\$"; echo "status: $?"'
+
+    runp bash -c "$script" "$PROOT_RS" "$ROOTFS" --exit-code-passthrough
+    [[ "$output" == *"Terminated"* ]]
+    [[ "$output" == *"status: 143"* ]]
+
+    runp bash -c "$script" "$PROOT_RS" "$ROOTFS" --exit-code-128
+    [[ "$output" != *"Terminated"* ]]
+    [[ "$output" == *"status: 143"* ]]
+}
+
+@test "test proot-rs options --collect-stats" {
+    runp proot-rs --rootfs "$ROOTFS" --collect-stats -- /bin/sh -c 'cd /bin; cd /etc; cd /'
+    [ "$status" -eq 0 ]
+    [[ "${lines[0]}" == "syscall "*" calls "*" errors" ]]
+    [[ "$output" =~ chdir\ +3\ +0 ]]
+}
+
+@test "test proot-rs options --seccomp-bpf-dump" {
+    runp proot-rs --seccomp-bpf-dump
+    [ "$status" -eq 0 ]
+    [[ "${lines[0]}" == "0000: A = seccomp_data.arch" ]]
+    [[ "$output" == *"# execve"* ]]
+    [[ "$output" == *"# chdir"* ]]
+    [[ "$output" != *"# write"$'\n'* ]]
+    [[ "${lines[-1]}" == *": return ALLOW" ]]
+}
+
+@test "test proot-rs options --rootfs-is-file" {
+    local image="$BATS_TMPDIR/rootfs-for-test-rootfs-is-file.tar"
+    tar --create --file "$image" --directory "$ROOTFS" .
+    runp proot-rs --rootfs-is-file --rootfs "$image" -- /bin/sh -c 'echo hello from the image'
+    rm -f "$image"
+    [ "$status" -eq 0 ]
+    [[ "${lines[0]}" == "hello from the image" ]]
+    # the extracted rootfs is deleted at exit
+    [ -z "$(ls -d "${TMPDIR:-/tmp}"/proot-rs-rootfs-* 2>/dev/null)" ]
+}
+
+@test "test proot-rs options --log-format" {
+    RUST_LOG=debug runp proot-rs --log-format json --rootfs "$ROOTFS" -- /bin/true
+    [ "$status" -eq 0 ]
+    [[ "${lines[0]}" == '{"timestamp":"'*'","level":"DEBUG","pid":'*'}' ]]
+    # each line is a valid JSON object
+    if command -v python3 >/dev/null; then
+        printf '%s\n' "${lines[@]}" | python3 -c 'import json, sys; [json.loads(l) for l in sys.stdin]'
+    fi
+
+    RUST_LOG=debug runp proot-rs --log-format logfmt --rootfs "$ROOTFS" -- /bin/true
+    [ "$status" -eq 0 ]
+    [[ "${lines[0]}" == "ts="*" level=debug pid="*" msg="* ]]
+}
+
+@test "test proot-rs options --mount-proc" {
+    runp proot-rs --mount-proc --rootfs "$ROOTFS" -- /bin/sh -c 'readlink /proc/self/exe; cat /proc/mounts'
+    [ "$status" -eq 0 ]
+    [[ "${lines[0]}" == /bin/* ]]
+    [[ "${lines[1]}" == "rootfs / rootfs rw 0 0" ]]
+    # the synthetic /proc is deleted at exit
+    [ -z "$(ls -d "${TMPDIR:-/tmp}"/proot-rs-proc-* 2>/dev/null)" ]
+}
+
+@test "test proot-rs options --mount-sys" {
+    runp proot-rs --mount-sys --rootfs "$ROOTFS" -- /bin/sh -c 'test -d /sys/fs/cgroup && test ! -e /sys/class && cat /sys/devices/system/cpu/online'
+    [ "$status" -eq 0 ]
+    [ "$output" = "$(cat /sys/devices/system/cpu/online)" ]
+    # the filtered /sys is deleted at exit
+    [ -z "$(ls -d "${TMPDIR:-/tmp}"/proot-rs-sys-* 2>/dev/null)" ]
+}
+
+@test "test proot-rs options --strace-filter" {
+    RUST_LOG=trace runp proot-rs --strace-filter execve,chdir --rootfs "$ROOTFS" -- /bin/sh -c 'cd /etc'
+    [ "$status" -eq 0 ]
+    # the registers of the syscalls are printed as "-- <pid> <name><<sysnum>>(...)"
+    local traced="$(printf '%s\n' "${lines[@]}" | grep -o -- '-- [0-9]* [a-z_0-9]*<' | awk '{print $3}' | sort -u)"
+    [[ "$traced" == "chdir<"$'\n'"execve<" ]]
+    runp proot-rs --strace-filter not_a_syscall --rootfs "$ROOTFS" -- /bin/true
+    [ "$status" -ne 0 ]
+}
+
+@test "test proot-rs options --socket-families" {
+    runp proot-rs --socket-families unix --rootfs "$ROOTFS" -- /bin/nc -l -p 12345 127.0.0.1
+    [ "$status" -ne 0 ]
+    [[ "$output" == *"not supported"* ]]
+    runp proot-rs --socket-families ipx --rootfs "$ROOTFS" -- /bin/true
+    [ "$status" -ne 0 ]
+}
+
+@test "test proot-rs options --color" {
+    RUST_LOG=debug runp proot-rs --color never --rootfs "$ROOTFS" -- /bin/true
+    [ "$status" -eq 0 ]
+    [[ "$output" == *"DEBUG"* ]]
+    [[ "$output" != *$'\e['* ]]
+    RUST_LOG=debug runp proot-rs --color always --rootfs "$ROOTFS" -- /bin/true
+    [ "$status" -eq 0 ]
+    [[ "$output" == *$'\e['* ]]
+}
+
+@test "test proot-rs options --fail-on-unhandled-syscall" {
+    runp proot-rs --fail-on-unhandled-syscall enosys --rootfs "$ROOTFS" -- /bin/sh -c 'cat /etc/passwd > /dev/null'
+    [ "$status" -eq 0 ]
+    runp proot-rs --fail-on-unhandled-syscall abort --rootfs "$ROOTFS" -- /bin/true
+    [ "$status" -ne 0 ]
+}
+
+@test "test proot-rs options --dump-elf" {
+    runp proot-rs --dump-elf --rootfs "$ROOTFS" -- /bin/sleep
+    [ "$status" -eq 0 ]
+    [[ "${lines[0]}" == "ELF header:" ]]
+    [[ "$output" == *"Interpreter: /"* ]]
+    [[ "$output" == *"Mappings of the interpreter:"* ]]
+    runp proot-rs --dump-elf --rootfs "$ROOTFS" -- /etc/passwd
+    [ "$status" -ne 0 ]
+}
+
+@test "test proot-rs options --config" {
+    local config="$BATS_TMPDIR/proot-rs-for-test-config.toml"
+    cat > "$config" <<EOT
+rootfs = "$ROOTFS"
+cwd = "/home"
+command = ["/bin/sh", "-c", "echo \$PROOT_RS_CONFIG \$PWD"]
+
+[[bind]]
+host = "/etc"
+guest = "/home"
+
+[env]
+PROOT_RS_CONFIG = "loaded"
+EOT
+    runp proot-rs --config "$config"
+    [ "$status" -eq 0 ]
+    [ "$output" = "loaded /home" ]
+    # the command line takes precedence
+    runp proot-rs --config "$config" --cwd / -- /bin/sh -c 'cat /home/passwd > /dev/null && echo $PWD'
+    [ "$status" -eq 0 ]
+    [ "$output" = "/" ]
+    # the environment is only the one of the command, which is still looked up in the PATH of proot-rs
+    printf 'rootfs = "%s"\n[env]\nPATH = "/nonexistent"\n' "$ROOTFS" > "$config"
+    runp proot-rs --config "$config" -- sh -c 'echo $PATH'
+    rm -f "$config"
+    [ "$status" -eq 0 ]
+    [ "$output" = "/nonexistent" ]
+}
+
+@test "test proot-rs options --timeout" {
+    runp proot-rs --timeout 1 --rootfs "$ROOTFS" -- /bin/sleep 30
+    [ "$status" -eq 124 ]
+    # SIGTERM is ignored, SIGKILL follows
+    runp proot-rs --timeout 1 --rootfs "$ROOTFS" -- /bin/sh -c 'trap "" TERM; sleep 30'
+    [ "$status" -eq 124 ]
+    runp proot-rs --timeout 10 --rootfs "$ROOTFS" -- /bin/true
+    [ "$status" -eq 0 ]
+}
+
+@test "test proot-rs options --hide-host-pids" {
+    # pid 1 of the host is not traced by proot-rs
+    runp proot-rs --hide-host-pids --rootfs "$ROOTFS" -b /proc -- /bin/sh -c 'ls /proc | grep -qx 1 || echo hidden'
+    [ "$status" -eq 0 ]
+    [ "$output" = "hidden" ]
+    runp proot-rs --hide-host-pids --rootfs "$ROOTFS" -b /proc -- /bin/sh -c 'ls /proc | grep -qx $$ && echo listed'
+    [ "$status" -eq 0 ]
+    [ "$output" = "listed" ]
+}
+
+@test "test proot-rs options --trace-path" {
+    runp proot-rs --rootfs "$ROOTFS" -b /etc:/home --trace-path /home/../home/passwd
+    [ "$status" -eq 0 ]
+    [ "${lines[0]}" = "Guest path: /home/../home/passwd" ]
+    [ "${lines[1]}" = "Canonical guest path: /home/passwd" ]
+    [[ "$output" == *"/etc:/home [used]"* ]]
+    [[ "$output" == *":/ [shadowed]"* ]]
+    [ "${lines[-1]}" = "Host path: /etc/passwd" ]
+    # a non-directory in the middle of the path
+    runp proot-rs --rootfs "$ROOTFS" --trace-path /etc/passwd/file
+    [ "$status" -eq 1 ]
+}
+
+@test "test proot-rs options --max-interp-depth" {
+    local dir="$(mktemp -d)"
+    for i in 1 2 3 4; do printf '#!/tmp/script%d\n' $((i + 1)) > "$dir/script$i"; done
+    printf '#!/bin/sh\necho chained\n' > "$dir/script5"
+    chmod +x "$dir"/script*
+    # 5 interpreters, one more than the kernel allows
+    runp proot-rs --rootfs "$ROOTFS" -b "$dir:/tmp" -- /tmp/script1
+    local status_default="$status"
+    runp proot-rs --rootfs "$ROOTFS" -b "$dir:/tmp" --max-interp-depth 5 -- /tmp/script1
+    rm -rf "$dir"
+    [ "$status_default" -ne 0 ]
+    [ "$status" -eq 0 ]
+    [ "$output" = "chained" ]
+}
+
+@test "test proot-rs options --scoped-sync" {
+    runp proot-rs --scoped-sync --rootfs "$ROOTFS" -- /bin/sh -c 'sync && echo synced'
+    [ "$status" -eq 0 ]
+    [ "$output" = "synced" ]
+}
+
+@test "test proot-rs options --init" {
+    # the orphaned subshell is adopted by proot-rs, the parent of the init process
+    runp proot-rs --init --rootfs "$ROOTFS" -- /bin/sh -c 'proot=$PPID; (sh -c "sleep 1; [ \$PPID -eq $proot ] && echo adopted" &); wait'
+    [ "$status" -eq 0 ]
+    [ "$output" = "adopted" ]
+}
+
+@test "test proot-rs options --emulate-dev" {
+    runp proot-rs --emulate-dev --rootfs "$ROOTFS" -- /bin/sh -c 'echo discarded > /dev/null && head -c 4 /dev/zero | od -An -tx1'
+    [ "$status" -eq 0 ]
+    [ "$output" = " 00 00 00 00" ]
+}
+
+@test "test proot-rs options --fake-root" {
+    runp proot-rs --fake-root --rootfs "$ROOTFS" -- /bin/sh -c 'touch /tmp/fake-root && chown 1000:1000 /tmp/fake-root && id -u && stat -c %u:%g /tmp/fake-root; rm -f /tmp/fake-root'
+    [ "$status" -eq 0 ]
+    [ "${lines[0]}" = "0" ]
+    [ "${lines[1]}" = "1000:1000" ]
+    runp proot-rs --fake-root --rootfs "$ROOTFS" -- /bin/sh -c 'touch /tmp/fake-root && chmod 4755 /tmp/fake-root && stat -c %a /tmp/fake-root; rm -f /tmp/fake-root'
+    [ "$status" -eq 0 ]
+    [ "${lines[0]}" = "4755" ]
+}
+
+@test "test proot-rs options --map-uid and --map-gid" {
+    runp proot-rs --fake-root --map-uid "$(id -u):1234" --map-gid "$(id -g):1234" --rootfs "$ROOTFS" -- /bin/sh -c 'touch /tmp/map-uid && id -u && stat -c %u:%g /tmp/map-uid; rm -f /tmp/map-uid'
+    [ "$status" -eq 0 ]
+    [ "${lines[0]}" = "1234" ]
+    [ "${lines[1]}" = "1234:1234" ]
+    runp proot-rs --map-uid 1000:0 --rootfs "$ROOTFS" -- /bin/true
+    [ "$status" -ne 0 ]
+}
@@ -11,3 +11,22 @@ load helper
     [ "${lines[2]}" = "/etc" ]
     [ "${#lines[@]}" -eq 3 ]
 }
+
+
+@test "test get and set cwd inside a binding" {
+    runp proot-rs --rootfs "$ROOTFS" --bind "$ROOTFS/etc:/home" --cwd /home -- /bin/sh -c "pwd -P; cd /; cd /home/../home/; pwd -P; /bin/ls passwd"
+    [ "$status" -eq 0 ]
+    [ "${lines[0]}" = "/home" ]
+    [ "${lines[1]}" = "/home" ]
+    [ "${lines[2]}" = "passwd" ]
+}
+
+
+@test "test relative paths with AT_FDCWD after changing cwd" {
+    # compile test case
+    compile_c_dynamic "$ROOTFS/bin/openat_at_fdcwd" "$BATS_TEST_DIRNAME/cwd/openat_at_fdcwd.c"
+    runp proot-rs --rootfs "$ROOTFS" --cwd / -- /bin/openat_at_fdcwd
+    # remember to delete the binary file
+    rm "$ROOTFS/bin/openat_at_fdcwd"
+    [ "$status" -eq 0 ]
+}
@@ -0,0 +1,67 @@
+/**
+ * This code is used to test whether relative paths used with `AT_FDCWD` are
+ * resolved against the latest `cwd` of the tracee by proot-rs.
+ *
+ * The program changes its `cwd` several times (with `chdir()` and `fchdir()`),
+ * and after each change it calls `openat(AT_FDCWD, ...)` with a relative path.
+ * The file opened is then compared with the one opened by its absolute path.
+ */
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/stat.h>
+#include <unistd.h>
+
+static void exit_with_error(char *msg) {
+    fprintf(stderr, "%s\n", msg);
+    exit(1);
+}
+
+// Check that `relative_path` (relative to the current cwd) and `absolute_path`
+// refer to the same file.
+static void check_same_file(const char *relative_path, const char *absolute_path) {
+    struct stat relative_stat, absolute_stat;
+    int fd = openat(AT_FDCWD, relative_path, O_RDONLY);
+    if (fd < 0)
+        exit_with_error("openat(AT_FDCWD) failed");
+    if (fstat(fd, &relative_stat) < 0)
+        exit_with_error("fstat() failed");
+    close(fd);
+    if (stat(absolute_path, &absolute_stat) < 0)
+        exit_with_error("stat() failed");
+    if (relative_stat.st_dev != absolute_stat.st_dev ||
+        relative_stat.st_ino != absolute_stat.st_ino)
+        exit_with_error("openat(AT_FDCWD) opened the wrong file");
+}
+
+int main(int argc, char const *argv[]) {
+    if (chdir("/etc") < 0)
+        exit_with_error("chdir(\"/etc\") failed");
+    check_same_file("passwd", "/etc/passwd");
+    check_same_file("./passwd", "/etc/passwd");
+
+    if (chdir("..") < 0)
+        exit_with_error("chdir(\"..\") failed");
+    check_same_file("etc/passwd", "/etc/passwd");
+
+    if (chdir("/bin") < 0)
+        exit_with_error("chdir(\"/bin\") failed");
+    check_same_file("../etc/passwd", "/etc/passwd");
+    // "passwd" does not exist in "/bin"
+    if (openat(AT_FDCWD, "passwd", O_RDONLY) >= 0 || errno != ENOENT)
+        exit_with_error("openat(AT_FDCWD) should be relative to the new cwd");
+
+    int dirfd = open("/etc", O_RDONLY | O_DIRECTORY);
+    if (dirfd < 0)
+        exit_with_error("open(\"/etc\") failed");
+    if (fchdir(dirfd) < 0)
+        exit_with_error("fchdir() failed");
+    close(dirfd);
+    check_same_file("passwd", "/etc/passwd");
+
+    return 0;
+}
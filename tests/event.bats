@@ -7,3 +7,26 @@ load helper
     runp proot-rs --rootfs "$ROOTFS" -- /bin/sh -c '/bin/kill -11 $PPID; /bin/echo "The tracee is still alive, which is not allowed";'
     [[ "$output" != *"still alive"* ]]
 }
+
+@test "test SIGPIPE is not ignored in the guest" {
+    runp proot-rs --rootfs "$ROOTFS" -- /bin/sh -c '/bin/kill -s PIPE $$; /bin/echo "SIGPIPE was ignored"'
+    [ "$status" -eq 141 ]
+    [[ "$output" != *"was ignored"* ]]
+}
+
+@test "test concurrent instances of proot-rs" {
+    # each instance writes its own loader to the temporary directory
+    local tmp_dir="$(mktemp -d)"
+    local script='for i in 1 2 3 4 5; do /bin/true || exit 1; done; /bin/echo done'
+    TMPDIR="$tmp_dir" proot-rs --rootfs "$ROOTFS" -- /bin/sh -c "$script" > "$tmp_dir/output_1" &
+    local pid_1=$!
+    TMPDIR="$tmp_dir" proot-rs --rootfs "$ROOTFS" -- /bin/sh -c "$script" > "$tmp_dir/output_2" &
+    local pid_2=$!
+    wait $pid_1
+    wait $pid_2
+    [ "$(cat "$tmp_dir/output_1")" == "done" ]
+    [ "$(cat "$tmp_dir/output_2")" == "done" ]
+    # the loaders are deleted once both instances exit
+    [ -z "$(ls "$tmp_dir" | grep -v '^output_')" ]
+    rm -rf "$tmp_dir"
+}